@@ -0,0 +1,48 @@
+//! Handling of tracks crossing the antimeridian (±180° longitude), e.g. flights or sailing routes across the Pacific.
+//!
+//! The flat projection measures the longitude difference of a point to its origin, so the points of a track crossing from
+//! 179.9° to -179.9° would end up almost 40000km apart. All points are therefore projected the shorter way around
+//! the globe (see [`project_point`]), and unprojected points are wrapped back into the valid range of longitudes.
+
+use flat_projection::{FlatPoint, FlatProjection};
+use geo::Point;
+
+/// Function to get the longitude equivalent to the given one which is closest to a center longitude.
+/// Longitudes within 180° of the center are returned unchanged, others are moved by a multiple of 360°.
+/// - `longitude`: The longitude in degrees.
+/// - `center`: The longitude in degrees the result should be close to.
+/// - Returns: The equivalent longitude within 180° of the center, which may be outside of -180° to 180°.
+pub fn longitude_near(longitude: f64, center: f64) -> f64 {
+    let difference = longitude - center;
+    if difference.abs() <= 180.0 {
+        longitude
+    } else {
+        center + (difference + 180.0).rem_euclid(360.0) - 180.0
+    }
+}
+
+/// Function to wrap a longitude into the range from -180° to 180°.
+/// - `longitude`: The longitude in degrees.
+/// - Returns: The equivalent longitude within -180° to 180°, the longitude itself if it already is.
+pub fn wrap_longitude(longitude: f64) -> f64 {
+    longitude_near(longitude, 0.0)
+}
+
+/// Function to project a point into a flat coordinate system, taking the shorter way around the globe from its origin.
+/// - `projector`: The flat coordinate system.
+/// - `point`: The point in LatLon coordinates.
+/// - Returns: The projected point, in kilometers relative to the origin.
+pub fn project_point(projector: &FlatProjection<f64>, point: Point) -> FlatPoint<f64> {
+    // The origin is not exposed by the projection, but it is where the projected coordinates are zero
+    let (origin_longitude, _) = projector.unproject(&FlatPoint { x: 0.0, y: 0.0 });
+    projector.project(longitude_near(point.x(), origin_longitude), point.y())
+}
+
+/// Function to convert a projected point back to LatLon coordinates, wrapping its longitude into the valid range.
+/// - `projector`: The flat coordinate system.
+/// - `point`: The projected point, in kilometers relative to the origin.
+/// - Returns: The point in LatLon coordinates.
+pub fn unproject_point(projector: &FlatProjection<f64>, point: &FlatPoint<f64>) -> Point {
+    let (longitude, latitude) = projector.unproject(point);
+    Point::new(wrap_longitude(longitude), latitude)
+}
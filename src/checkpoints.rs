@@ -0,0 +1,93 @@
+//! Comparison of a track to a reference path given as discrete checkpoints instead of a continuous line, e.g. for rallies.
+//!
+//! Instead of searching the closest point on the reference path for every point of the track, the closest point on the track
+//! is searched for every point of the reference path. A checkpoint counts as hit if the track passed within a tolerance of it.
+
+use flat_projection::FlatProjection;
+use geo::{Closest, Point};
+use gpx::Track;
+
+use crate::{join_and_project_segments, join_segments, DistanceMode, ReferenceIndex};
+
+/// Distance of the track to a single checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CheckpointDistance {
+    /// The checkpoint, in LatLon coordinates
+    pub checkpoint: Point,
+    /// Smallest distance of the track (measured along its lines, not only at its points) to the checkpoint in meters, `None` if the track has no points
+    pub distance_m: Option<f64>,
+}
+
+/// Distances of the track to all checkpoints of the reference path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckpointStatistics {
+    /// The distance to every checkpoint, in the order of the reference path
+    pub checkpoints: Vec<CheckpointDistance>,
+    /// The tolerance within which a checkpoint counts as hit, in meters
+    pub tolerance_m: Option<f64>,
+    /// Number of checkpoints within the tolerance, only set if a tolerance was given
+    pub hit_count: Option<usize>,
+}
+
+/// Function to calculate the distance of a track to every point of the reference path.
+/// - `reference`: The reference track, every point of which is a checkpoint.
+/// - `track`: The track which should pass the checkpoints.
+/// - `distance_mode`: How the distances are measured.
+/// - `projector`: The flat coordinate system used in the flat distance mode.
+/// - `tolerance`: The distance in meters within which a checkpoint counts as hit.
+/// - Returns: The distance to every checkpoint and, if a tolerance was given, the number of hit checkpoints.
+pub fn checkpoint_distances(
+    reference: &Track,
+    track: &Track,
+    distance_mode: DistanceMode,
+    projector: &FlatProjection<f64>,
+    tolerance: Option<f64>,
+) -> CheckpointStatistics {
+    let unit = distance_mode.unit_in_meters();
+    let (track_linestring, checkpoints) = match distance_mode {
+        DistanceMode::Flat => (
+            join_and_project_segments(&track.segments, projector),
+            join_and_project_segments(&reference.segments, projector),
+        ),
+        DistanceMode::Geodesic => (
+            join_segments(&track.segments),
+            join_segments(&reference.segments),
+        ),
+    };
+    let track_index = ReferenceIndex::with_distance_mode(&track_linestring, distance_mode);
+
+    let checkpoints: Vec<CheckpointDistance> = join_segments(&reference.segments)
+        .points()
+        .zip(checkpoints.points())
+        .map(|(checkpoint, point)| {
+            // A track with a single point has no lines to search on, so its point is the closest one
+            let closest = match track_linestring.0.as_slice() {
+                [single] => Some(Point::from(*single)),
+                _ => match track_index.closest_point(&point) {
+                    Closest::Intersection(closest) | Closest::SinglePoint(closest) => Some(closest),
+                    Closest::Indeterminate => None,
+                },
+            };
+            CheckpointDistance {
+                checkpoint,
+                distance_m: closest.map(|closest| distance_mode.distance(&closest, &point) * unit),
+            }
+        })
+        .collect();
+
+    let hit_count = tolerance.map(|tolerance| {
+        checkpoints
+            .iter()
+            .filter(|checkpoint| {
+                checkpoint
+                    .distance_m
+                    .is_some_and(|distance| distance <= tolerance)
+            })
+            .count()
+    });
+    CheckpointStatistics {
+        checkpoints,
+        tolerance_m: tolerance,
+        hit_count,
+    }
+}
@@ -0,0 +1,184 @@
+//! Config files with defaults for the command line options, e.g. to share a standardized set of options within a team.
+//!
+//! A config file is a YAML mapping from the names of the options to their values:
+//!
+//! ```yaml
+//! # Shared defaults of the team
+//! simplify-epsilon: 2.5
+//! units: imperial
+//! symmetric: true
+//! track: [monday.gpx, tuesday.fit]
+//! max-average:
+//!   - 10
+//! ```
+//!
+//! Only the flat subset of YAML needed for this is supported: a single mapping with scalar values (plain, single or
+//! double quoted) and lists of scalars (in flow style `[a, b]` or as block of `- a` lines). Comments start with `#`.
+//! Everything else is rejected with the line it is found on, instead of being read differently than a YAML parser would:
+//! nested mappings and lists, anchors, aliases, tags, block scalars (`|` and `>`), values spanning multiple lines,
+//! escape sequences in double quoted values and multiple documents.
+
+/// Value of a single option in a config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigValue {
+    /// A single value, e.g. `2.5` or `true`
+    Scalar(String),
+    /// A list of values, e.g. multiple track files
+    List(Vec<String>),
+}
+
+/// Function to remove a trailing comment from a line, ignoring `#` within quotes.
+/// A `#` only starts a comment at the start of the line or after whitespace, like in YAML.
+/// - `line`: The line of the config file.
+/// - Returns: The line without the comment.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (index, character) in line.char_indices() {
+        match (quote, character) {
+            (None, '\'' | '"') => quote = Some(character),
+            (Some(open), _) if character == open => quote = None,
+            (None, '#') if previous.is_whitespace() => return &line[..index],
+            _ => {}
+        }
+        previous = character;
+    }
+    line
+}
+
+/// Function to parse a single scalar, removing its quotes.
+/// - `value`: The scalar as written in the config file, without surrounding whitespace.
+/// - Returns: The value of the scalar, or an error message.
+fn parse_scalar(value: &str) -> Result<String, String> {
+    if let Some(quoted) = value.strip_prefix('\'') {
+        // Within single quotes, a quote is written as two quotes
+        return quoted
+            .strip_suffix('\'')
+            .map(|quoted| quoted.replace("''", "'"))
+            .ok_or_else(|| format!("missing closing ' in {}", value));
+    }
+    if let Some(quoted) = value.strip_prefix('"') {
+        let quoted = quoted
+            .strip_suffix('"')
+            .ok_or_else(|| format!("missing closing \" in {}", value))?;
+        if quoted.contains('\\') {
+            return Err(format!(
+                "escape sequences are not supported in {}, use single quotes for values with a backslash",
+                value
+            ));
+        }
+        return Ok(quoted.to_string());
+    }
+
+    let unsupported = match value.chars().next() {
+        Some('&') => Some("anchors are"),
+        Some('*') => Some("aliases are"),
+        Some('!') => Some("tags are"),
+        Some('|' | '>') => Some("block scalars spanning multiple lines are"),
+        Some('{') => Some("nested mappings are"),
+        Some('[') => Some("nested lists are"),
+        Some('@' | '`') => Some("values starting with @ or ` without quotes are"),
+        _ if value.contains(": ") || value.ends_with(':') => Some("nested mappings are"),
+        _ => None,
+    };
+    match unsupported {
+        Some(feature) => Err(format!("{} not supported, found {}", feature, value)),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Function to parse the value of an option given on the same line as its name.
+/// - `value`: The value as written in the config file, without surrounding whitespace.
+/// - Returns: The value, or an error message.
+fn parse_value(value: &str) -> Result<ConfigValue, String> {
+    match value.strip_prefix('[') {
+        Some(list) => {
+            let list = list
+                .strip_suffix(']')
+                .ok_or_else(|| format!("missing closing ] in {}", value))?
+                .trim();
+            if list.is_empty() {
+                return Ok(ConfigValue::List(Vec::new()));
+            }
+            list.split(',')
+                .map(|item| parse_scalar(item.trim()))
+                .collect::<Result<_, _>>()
+                .map(ConfigValue::List)
+        }
+        None => parse_scalar(value).map(ConfigValue::Scalar),
+    }
+}
+
+/// Function to parse a config file.
+/// - `content`: The content of the config file.
+/// - Returns: The names of all options and their values in the order of the file, or an error message.
+///
+/// ```
+/// use gps_path_average_distance::config::{parse_config, ConfigValue};
+///
+/// let config = parse_config("units: imperial # for the US team\ntrack:\n  - a.gpx\n  - b.gpx\n").unwrap();
+/// assert_eq!(config[0], ("units".to_string(), ConfigValue::Scalar("imperial".to_string())));
+/// assert_eq!(config[1].1, ConfigValue::List(vec!["a.gpx".to_string(), "b.gpx".to_string()]));
+/// ```
+pub fn parse_config(content: &str) -> Result<Vec<(String, ConfigValue)>, String> {
+    let mut options: Vec<(String, ConfigValue)> = Vec::new();
+    // Name of the option whose value is given as block of list items on the following lines
+    let mut open_list: Option<String> = None;
+
+    for (line_index, line) in content.lines().enumerate() {
+        let error = |message: String| format!("line {}: {}", line_index + 1, message);
+        let line = strip_comment(line).trim_end();
+        if line.trim().is_empty() {
+            continue;
+        }
+        // Only a marker at the start of the single document is allowed
+        if line == "---" {
+            if !options.is_empty() || open_list.is_some() {
+                return Err(error("multiple documents are not supported".to_string()));
+            }
+            continue;
+        }
+
+        // List items belong to the last option without a value
+        if let Some(item) = line
+            .trim_start()
+            .strip_prefix('-')
+            .filter(|item| item.is_empty() || item.starts_with(' '))
+        {
+            let Some(name) = &open_list else {
+                return Err(error("list item without an option".to_string()));
+            };
+            let item = parse_scalar(item.trim()).map_err(error)?;
+            match options.iter_mut().find(|(option, _)| option == name) {
+                Some((_, ConfigValue::List(items))) => items.push(item),
+                _ => options.push((name.clone(), ConfigValue::List(vec![item]))),
+            }
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            return Err(error(
+                "nested values are not supported, expected name: value".to_string(),
+            ));
+        }
+
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(error(format!("expected name: value, found {}", line)));
+        };
+        let name = name.trim().to_string();
+        if options.iter().any(|(option, _)| *option == name) || open_list.as_ref() == Some(&name) {
+            return Err(error(format!("{} is given more than once", name)));
+        }
+        let value = value.trim();
+        match value {
+            // Without a value on the same line, the value is given as list on the following lines
+            "" => open_list = Some(name),
+            // Options set to null keep their default
+            "~" | "null" => open_list = None,
+            _ => {
+                options.push((name, parse_value(value).map_err(error)?));
+                open_list = None;
+            }
+        }
+    }
+    Ok(options)
+}
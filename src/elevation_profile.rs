@@ -0,0 +1,118 @@
+//! Comparison of the elevation profiles of a track and the reference path, independently of their horizontal distance.
+//!
+//! Every point of the compared track is matched by its distance along the track: a point at the distance `s` of a track
+//! with the length `L_track` is matched to the point at the distance `s * L_ref / L_track` along the reference path,
+//! whose elevation is interpolated between the neighbouring points with an elevation. Unlike the closest point, this keeps
+//! the order of both tracks, so e.g. the way up and the way down of an out-and-back route are not mixed up.
+//! The mean absolute difference of these elevations shows how well the elevations of both tracks agree, while the total
+//! ascent and descent of both tracks show how faithfully the climbs were reproduced.
+
+use gpx::Track;
+
+use crate::join_elevations;
+use crate::section::cumulative_lengths;
+
+/// Elevation comparison of a track and the reference path, in meters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElevationProfile {
+    /// Mean absolute difference between the elevation of every point of the compared track and the elevation of the reference path
+    /// at the same fraction of its length. `None` if no point of the compared track has an elevation.
+    pub mean_elevation_diff_m: Option<f64>,
+    /// Sum of all elevation gains between consecutive points of the reference path
+    pub reference_ascent_m: f64,
+    /// Sum of all elevation losses between consecutive points of the reference path, as a positive value
+    pub reference_descent_m: f64,
+    /// Sum of all elevation gains between consecutive points of the compared track
+    pub track_ascent_m: f64,
+    /// Sum of all elevation losses between consecutive points of the compared track, as a positive value
+    pub track_descent_m: f64,
+}
+
+/// Function to calculate the total ascent and descent of a track.
+/// Points without an elevation are skipped, so the change is counted from the previous point with one.
+/// - `track`: The track.
+/// - Returns: The total ascent and descent in meters, both positive.
+pub fn ascent_descent(track: &Track) -> (f64, f64) {
+    join_elevations(&track.segments)
+        .into_iter()
+        .flatten()
+        .collect::<Vec<f64>>()
+        .windows(2)
+        .fold((0.0, 0.0), |(ascent, descent), pair| {
+            let change = pair[1] - pair[0];
+            if change > 0.0 {
+                (ascent + change, descent)
+            } else {
+                (ascent, descent - change)
+            }
+        })
+}
+
+/// Function to compare the elevation profiles of a track and the reference path.
+/// Every point of the track is matched to the point at the same fraction of the length of the reference path, see the module documentation.
+/// - `reference`: The reference track.
+/// - `track`: The compared track.
+/// - Returns: The comparison, `None` if either track has no elevations.
+pub fn elevation_profile(reference: &Track, track: &Track) -> Option<ElevationProfile> {
+    let reference_elevations = join_elevations(&reference.segments);
+    let track_elevations = join_elevations(&track.segments);
+    if reference_elevations.iter().all(Option::is_none)
+        || track_elevations.iter().all(Option::is_none)
+    {
+        return None;
+    }
+
+    // Only the points with an elevation are interpolated between, like the ascent and descent skip the points without one
+    let reference_arcs = cumulative_lengths(reference);
+    let reference_length = reference_arcs.last().copied().unwrap_or(0.0);
+    let reference_profile: Vec<(f64, f64)> = reference_arcs
+        .into_iter()
+        .zip(reference_elevations)
+        .filter_map(|(arc, elevation)| Some((arc, elevation?)))
+        .collect();
+    let track_arcs = cumulative_lengths(track);
+    let track_length = track_arcs.last().copied().unwrap_or(0.0);
+
+    let differences: Vec<f64> = track_arcs
+        .into_iter()
+        .zip(track_elevations)
+        .filter_map(|(arc, elevation)| {
+            // A track without any length is matched to the start of the reference path
+            let reference_arc = if track_length > 0.0 {
+                arc / track_length * reference_length
+            } else {
+                0.0
+            };
+            Some((elevation? - elevation_at(&reference_profile, reference_arc)).abs())
+        })
+        .collect();
+
+    let (reference_ascent_m, reference_descent_m) = ascent_descent(reference);
+    let (track_ascent_m, track_descent_m) = ascent_descent(track);
+    Some(ElevationProfile {
+        mean_elevation_diff_m: (!differences.is_empty())
+            .then(|| differences.iter().sum::<f64>() / differences.len() as f64),
+        reference_ascent_m,
+        reference_descent_m,
+        track_ascent_m,
+        track_descent_m,
+    })
+}
+
+/// Function to interpolate the elevation of a track at a distance along it.
+/// - `profile`: The distance along the track (in meters) and the elevation of every point with an elevation, must not be empty.
+/// - `arc`: The distance along the track at which the elevation is interpolated.
+/// - Returns: The linearly interpolated elevation, the elevation of the first or last point before or after them.
+fn elevation_at(profile: &[(f64, f64)], arc: f64) -> f64 {
+    let next = profile.partition_point(|&(point_arc, _)| point_arc < arc);
+    if next == 0 {
+        return profile[0].1;
+    }
+    if next == profile.len() {
+        return profile[profile.len() - 1].1;
+    }
+    let ((start_arc, start_elevation), (end_arc, end_elevation)) =
+        (profile[next - 1], profile[next]);
+    // `start_arc < arc <= end_arc`, so both points are at different distances
+    start_elevation + (end_elevation - start_elevation) * (arc - start_arc) / (end_arc - start_arc)
+}
@@ -0,0 +1,247 @@
+//! Helpers to export geometries and comparison results.
+
+use std::io::Cursor;
+
+use geo::LineString;
+use gpx::{Track, Waypoint};
+use serde_json::{json, Value};
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::TrackComparison;
+
+/// Namespace of the comparison results written into the `<extensions>` of the exported tracks, see [`add_metrics_extensions`].
+pub const METRICS_NAMESPACE: &str =
+    "https://github.com/NetroScript/gps-path-average-distance/xmlschemas/Metrics/v1";
+
+/// Function to create a GeoJSON LineString feature.
+/// - `linestring`: The LineString in LatLon coordinates (x = longitude, y = latitude).
+/// - `properties`: The properties to attach to the feature.
+/// - Returns: The GeoJSON feature.
+pub fn geojson_linestring_feature(linestring: &LineString, properties: Value) -> Value {
+    let coordinates: Vec<[f64; 2]> = linestring
+        .coords()
+        .map(|coord| [coord.x, coord.y])
+        .collect();
+
+    json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "LineString",
+            "coordinates": coordinates,
+        },
+        "properties": properties,
+    })
+}
+
+/// Function to format a LineString as WKT, e.g. for PostGIS.
+/// - `linestring`: The LineString in LatLon coordinates (x = longitude, y = latitude).
+/// - Returns: The WKT geometry, `LINESTRING EMPTY` if the LineString has no points.
+pub fn wkt_linestring(linestring: &LineString) -> String {
+    if linestring.0.is_empty() {
+        return "LINESTRING EMPTY".to_string();
+    }
+    let coordinates: Vec<String> = linestring
+        .coords()
+        .map(|coord| format!("{} {}", coord.x, coord.y))
+        .collect();
+    format!("LINESTRING({})", coordinates.join(", "))
+}
+
+/// Function to wrap features into a GeoJSON FeatureCollection.
+/// - `features`: The GeoJSON features.
+/// - Returns: The GeoJSON FeatureCollection.
+pub fn geojson_feature_collection(features: Vec<Value>) -> Value {
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
+
+/// Function to map the points of a simplified track back to the waypoints of the original track, so their metadata
+/// (e.g. elevation and time) is kept when the simplified track is exported.
+/// The waypoints are matched by their coordinates, in the order of the track, so repeated coordinates (e.g. while standing still)
+/// are matched to the next unused waypoint. Points without a matching waypoint (e.g. of a resampled or smoothed track) become plain waypoints.
+/// - `track`: The original track, in which all segments are joined.
+/// - `simplified`: The simplified track in LatLon coordinates, usually [`crate::TrackComparison::simplified_track`].
+/// - Returns: A waypoint for every point of the simplified track.
+pub fn simplified_waypoints(track: &Track, simplified: &LineString) -> Vec<Waypoint> {
+    let mut waypoints = track.segments.iter().flat_map(|segment| &segment.points);
+    simplified
+        .points()
+        .map(|point| {
+            // Search forward from the last match, so the waypoints are matched in order
+            match waypoints
+                .clone()
+                .position(|waypoint| waypoint.point() == point)
+            {
+                Some(offset) => waypoints
+                    .nth(offset)
+                    .expect("The matching waypoint exists")
+                    .clone(),
+                None => Waypoint::new(point),
+            }
+        })
+        .collect()
+}
+
+/// Distances of a comparison which are stored in the exported GPX file, all in meters.
+/// Metrics which were not available (e.g. the curve metrics of a single point track) are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportedMetrics {
+    /// Average distance in meters, `None` if NaN
+    pub average_distance_m: Option<f64>,
+    /// Location dependent average distance in meters, `None` if NaN
+    pub simplified_average_distance_m: Option<f64>,
+    /// Fréchet distance in meters, `None` if the tracks are not curves
+    pub frechet_distance_m: Option<f64>,
+    /// Hausdorff distance in meters, `None` if the tracks are not curves
+    pub hausdorff_distance_m: Option<f64>,
+    /// Dynamic Time Warping distance in meters, `None` if the tracks are not curves
+    pub dtw_distance_m: Option<f64>,
+}
+
+impl ExportedMetrics {
+    /// Function to take the exported distances from a comparison.
+    /// - `comparison`: The comparison, with its distances in meters.
+    /// - Returns: The distances to export, the averages are left out if they are NaN (i.e. no point could be compared).
+    pub fn from_comparison(comparison: &TrackComparison) -> Self {
+        let finite = |value: f64| Some(value).filter(|value| value.is_finite());
+        ExportedMetrics {
+            average_distance_m: finite(comparison.average_distance_m),
+            simplified_average_distance_m: finite(comparison.simplified_average_distance_m),
+            frechet_distance_m: comparison.frechet_distance_m,
+            hausdorff_distance_m: comparison.hausdorff_distance_m,
+            dtw_distance_m: comparison.dtw_distance_m,
+        }
+    }
+
+    /// Function to list the distances together with the names of their elements.
+    /// - Returns: The name of the element and the value of every distance.
+    fn elements(&self) -> [(&'static str, Option<f64>); 5] {
+        [
+            ("average_distance_m", self.average_distance_m),
+            (
+                "simplified_average_distance_m",
+                self.simplified_average_distance_m,
+            ),
+            ("frechet_distance_m", self.frechet_distance_m),
+            ("hausdorff_distance_m", self.hausdorff_distance_m),
+            ("dtw_distance_m", self.dtw_distance_m),
+        ]
+    }
+
+    /// Function to set a distance by the name of its element.
+    /// - `name`: The local name of the element.
+    /// - `value`: The distance.
+    fn set(&mut self, name: &str, value: f64) {
+        match name {
+            "average_distance_m" => self.average_distance_m = Some(value),
+            "simplified_average_distance_m" => self.simplified_average_distance_m = Some(value),
+            "frechet_distance_m" => self.frechet_distance_m = Some(value),
+            "hausdorff_distance_m" => self.hausdorff_distance_m = Some(value),
+            "dtw_distance_m" => self.dtw_distance_m = Some(value),
+            // Elements of later versions are ignored, so older versions can still read the known distances
+            _ => {}
+        }
+    }
+}
+
+/// Function to add the comparison results to the `<extensions>` of the tracks of a GPX file.
+/// The file has to be written by [`gpx::write`], which never writes extensions itself. Every track with metrics gets
+/// ```xml
+/// <extensions>
+///   <gpad:comparison xmlns:gpad="https://github.com/NetroScript/gps-path-average-distance/xmlschemas/Metrics/v1">
+///     <gpad:average_distance_m>2.114</gpad:average_distance_m>
+///     ...
+///   </gpad:comparison>
+/// </extensions>
+/// ```
+/// before its first `<trkseg>`, as required by the GPX 1.1 schema, with one element for every available distance of [`ExportedMetrics`].
+/// - `content`: The GPX file written by [`gpx::write`].
+/// - `metrics`: The metrics of every track of the file in the order of the file, `None` for tracks which were not compared.
+/// - Returns: The GPX file with the extensions.
+pub fn add_metrics_extensions(content: &str, metrics: &[Option<ExportedMetrics>]) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut rest = content;
+    for track_metrics in metrics {
+        // The tracks are written as `<trk>` without attributes, and `<` is always escaped within text
+        let Some(track_start) = rest.find("<trk>") else {
+            break;
+        };
+        let (before, track) = rest.split_at(track_start + "<trk>".len());
+        result.push_str(before);
+        rest = track;
+
+        let Some(track_metrics) = track_metrics else {
+            continue;
+        };
+        let insert_at = [rest.find("<trkseg"), rest.find("</trk>")]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap_or(0);
+        let (before, after) = rest.split_at(insert_at);
+        result.push_str(before);
+        result.push_str(&format!(
+            "<extensions><gpad:comparison xmlns:gpad=\"{}\">",
+            METRICS_NAMESPACE
+        ));
+        for (name, value) in track_metrics.elements() {
+            if let Some(value) = value {
+                // The shortest representation which parses back to the same value, so no precision is lost
+                result.push_str(&format!("<gpad:{0}>{1}</gpad:{0}>", name, value));
+            }
+        }
+        result.push_str("</gpad:comparison></extensions>\n    ");
+        rest = after;
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Function to read the comparison results stored by [`add_metrics_extensions`] from a GPX file.
+/// - `data`: The content of the GPX file.
+/// - Returns: The metrics of every track in the order of the file, `None` for tracks without them, or the error of the XML parser.
+pub fn read_metrics_extensions(
+    data: &[u8],
+) -> Result<Vec<Option<ExportedMetrics>>, xml::reader::Error> {
+    let mut tracks = Vec::new();
+    // Metrics of the track which is currently parsed, and the distance element which is currently parsed
+    let mut in_track = false;
+    let mut metrics: Option<ExportedMetrics> = None;
+    let mut element: Option<String> = None;
+
+    for event in EventReader::new(Cursor::new(data)) {
+        match event? {
+            XmlEvent::StartElement { name, .. } => {
+                if name.namespace.as_deref() == Some(METRICS_NAMESPACE) {
+                    if name.local_name == "comparison" {
+                        metrics = Some(metrics.unwrap_or_default());
+                    } else {
+                        element = Some(name.local_name);
+                    }
+                } else if name.local_name == "trk" {
+                    in_track = true;
+                    metrics = None;
+                }
+            }
+            XmlEvent::Characters(text) => {
+                if let (Some(metrics), Some(element), Ok(value)) =
+                    (metrics.as_mut(), &element, text.trim().parse::<f64>())
+                {
+                    metrics.set(element, value);
+                }
+            }
+            XmlEvent::EndElement { name } => {
+                element = None;
+                if name.local_name == "trk" && in_track {
+                    in_track = false;
+                    tracks.push(metrics.take());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(tracks)
+}
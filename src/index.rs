@@ -0,0 +1,189 @@
+//! Spatial index over the segments of the reference path.
+
+use geo::{
+    Closest, ClosestPoint, EuclideanDistance, HaversineClosestPoint, HaversineDistance, Line,
+    LineString, Point,
+};
+use rstar::primitives::GeomWithData;
+use rstar::RTree;
+
+use crate::DistanceMode;
+
+#[derive(Debug, Clone)]
+enum Segments {
+    /// R-tree over the projected segments, each one annotated with its index in the LineString
+    Flat(RTree<GeomWithData<Line, usize>>),
+    /// The LatLon LineString, which is searched completely as an R-tree does not work on the sphere
+    Geodesic(LineString),
+}
+
+/// Result of a closest point search on the reference path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClosestMatch {
+    /// The closest point on the reference path
+    pub point: Closest<f64>,
+    /// The elevation of the closest point, if the index has elevations and both points of the segment have one
+    pub elevation: Option<f64>,
+    /// The segment of the reference path the closest point lies on
+    pub segment: Option<Line>,
+}
+
+/// Index over all segments of a LineString, used to find the closest point on the reference path.
+/// In the flat distance mode this is an R-tree, so not every single segment has to be checked for every compared point.
+#[derive(Debug, Clone)]
+pub struct ReferenceIndex {
+    segments: Segments,
+    /// Optional elevations of the points of the LineString, in the units of the distance mode
+    elevations: Option<Vec<Option<f64>>>,
+}
+
+impl ReferenceIndex {
+    /// Function to build the index for a projected LineString.
+    /// - `linestring`: The (projected) reference path.
+    /// - Returns: The index over all segments of the LineString.
+    pub fn new(linestring: &LineString) -> Self {
+        Self::with_distance_mode(linestring, DistanceMode::Flat)
+    }
+
+    /// Function to build the index for a LineString.
+    /// - `linestring`: The reference path, projected for the flat mode and in LatLon coordinates for the geodesic mode.
+    /// - `distance_mode`: How distances between points are measured.
+    /// - Returns: The index over all segments of the LineString.
+    pub fn with_distance_mode(linestring: &LineString, distance_mode: DistanceMode) -> Self {
+        let segments = match distance_mode {
+            DistanceMode::Flat => Segments::Flat(RTree::bulk_load(
+                linestring
+                    .lines()
+                    .enumerate()
+                    .map(|(index, line)| GeomWithData::new(line, index))
+                    .collect(),
+            )),
+            DistanceMode::Geodesic => Segments::Geodesic(linestring.clone()),
+        };
+        ReferenceIndex {
+            segments,
+            elevations: None,
+        }
+    }
+
+    /// Function to build the index for a LineString whose points have an elevation.
+    /// - `linestring`: The reference path, projected for the flat mode and in LatLon coordinates for the geodesic mode.
+    /// - `distance_mode`: How distances between points are measured.
+    /// - `elevations`: The elevation of every point of the LineString (in the units of the distance mode), `None` for points without one.
+    /// - Returns: The index over all segments of the LineString.
+    pub fn with_elevations(
+        linestring: &LineString,
+        distance_mode: DistanceMode,
+        elevations: Vec<Option<f64>>,
+    ) -> Self {
+        ReferenceIndex {
+            elevations: Some(elevations),
+            ..Self::with_distance_mode(linestring, distance_mode)
+        }
+    }
+
+    /// Function to get the distance mode the index was built for.
+    /// - Returns: The distance mode.
+    pub fn distance_mode(&self) -> DistanceMode {
+        match self.segments {
+            Segments::Flat(_) => DistanceMode::Flat,
+            Segments::Geodesic(_) => DistanceMode::Geodesic,
+        }
+    }
+
+    /// Function to find the closest point on the indexed LineString.
+    /// This returns the same result as [`ClosestPoint::closest_point`] (or [`HaversineClosestPoint::haversine_closest_point`]) on the LineString itself.
+    /// - `point`: The point for which the closest point is searched.
+    /// - Returns: The closest point, or `Closest::Indeterminate` if the LineString has no segments.
+    pub fn closest_point(&self, point: &Point) -> Closest<f64> {
+        self.closest_segment_point(point).0
+    }
+
+    /// Function to find the closest point on the indexed LineString together with its elevation and segment.
+    /// The closest point is searched horizontally, its elevation is linearly interpolated between the two points of the segment it lies on.
+    /// - `point`: The point for which the closest point is searched.
+    /// - Returns: The closest point with its elevation and the segment it lies on.
+    pub fn closest_match(&self, point: &Point) -> ClosestMatch {
+        let (closest, segment) = self.closest_segment_point(point);
+
+        let elevation = match (&self.elevations, closest, segment) {
+            (
+                Some(elevations),
+                Closest::Intersection(p) | Closest::SinglePoint(p),
+                Some((line, index)),
+            ) => match (elevations[index], elevations[index + 1]) {
+                (Some(start), Some(end)) => {
+                    // Fraction of the segment between its start and the closest point
+                    let (along, length) = match self.segments {
+                        Segments::Flat(_) => (
+                            line.start_point().euclidean_distance(&p),
+                            line.start_point().euclidean_distance(&line.end_point()),
+                        ),
+                        Segments::Geodesic(_) => (
+                            line.start_point().haversine_distance(&p),
+                            line.start_point().haversine_distance(&line.end_point()),
+                        ),
+                    };
+                    let fraction = if length > 0.0 {
+                        (along / length).clamp(0.0, 1.0)
+                    } else {
+                        0.0
+                    };
+                    Some(start + (end - start) * fraction)
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+
+        ClosestMatch {
+            point: closest,
+            elevation,
+            segment: segment.map(|(line, _)| line),
+        }
+    }
+
+    /// Function to find the closest point on the indexed LineString and the segment it lies on.
+    /// - `point`: The point for which the closest point is searched.
+    /// - Returns: The closest point, and the segment with its index in the LineString.
+    fn closest_segment_point(&self, point: &Point) -> (Closest<f64>, Option<(Line, usize)>) {
+        match &self.segments {
+            Segments::Flat(tree) => match tree.nearest_neighbor(point) {
+                Some(segment) => {
+                    let line = *segment.geom();
+                    // geo has no closest point on a line of length zero (e.g. between duplicate points of the reference path),
+                    // while the haversine search (like the R-tree) uses its single point
+                    let closest = match line.closest_point(point) {
+                        Closest::Indeterminate => Closest::SinglePoint(line.start_point()),
+                        closest => closest,
+                    };
+                    (closest, Some((line, segment.data)))
+                }
+                None => (Closest::Indeterminate, None),
+            },
+            Segments::Geodesic(linestring) => {
+                // Same search as `HaversineClosestPoint` for LineStrings, but remembering the segment
+                let mut min_distance = f64::MAX;
+                let mut result = (Closest::Indeterminate, None);
+
+                for (index, line) in linestring.lines().enumerate() {
+                    match line.haversine_closest_point(point) {
+                        intersection @ Closest::Intersection(_) => {
+                            return (intersection, Some((line, index)))
+                        }
+                        Closest::SinglePoint(p) => {
+                            let distance = p.haversine_distance(point);
+                            if distance < min_distance {
+                                min_distance = distance;
+                                result = (Closest::SinglePoint(p), Some((line, index)));
+                            }
+                        }
+                        Closest::Indeterminate => return (Closest::Indeterminate, None),
+                    }
+                }
+
+                result
+            }
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! Saving and loading a prebuilt reference path, so repeated runs against the same reference file skip parsing it.
+//!
+//! The index file contains all tracks, routes and named waypoints of the reference file as JSON, together with a hash of the content of
+//! the reference file. Loading the index checks the hash against the current reference file, so an index which was built from
+//! another version of the file is rejected instead of silently comparing against an outdated reference path.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use geo::Point;
+use gpx::{Gpx, GpxVersion, Route, Track, TrackSegment, Waypoint};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// Version of the format of the index file, which is increased whenever the format changes.
+pub const REFERENCE_INDEX_VERSION: u32 = 1;
+
+/// Errors which can occur while saving or loading a reference index.
+#[derive(Debug)]
+pub enum ReferenceIndexError {
+    /// The index file could not be read or written
+    Io(std::io::Error),
+    /// The index file is no valid index
+    Invalid(String),
+    /// The index file was written with another version of the format
+    Version(u32),
+    /// The index file was built from another version of the reference file
+    Stale,
+}
+
+impl fmt::Display for ReferenceIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferenceIndexError::Io(error) => write!(f, "{}", error),
+            ReferenceIndexError::Invalid(error) => write!(f, "invalid index: {}", error),
+            ReferenceIndexError::Version(version) => write!(
+                f,
+                "the index has format version {}, but only version {} is supported",
+                version, REFERENCE_INDEX_VERSION
+            ),
+            ReferenceIndexError::Stale => write!(
+                f,
+                "the index was built from another version of the reference file"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReferenceIndexError {}
+
+impl From<std::io::Error> for ReferenceIndexError {
+    fn from(error: std::io::Error) -> Self {
+        ReferenceIndexError::Io(error)
+    }
+}
+
+/// A point of a track or a named waypoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct IndexPoint {
+    lat: f64,
+    lon: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ele: Option<f64>,
+    /// Unix timestamp in nanoseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hdop: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+impl From<&Waypoint> for IndexPoint {
+    fn from(waypoint: &Waypoint) -> Self {
+        IndexPoint {
+            lat: waypoint.point().y(),
+            lon: waypoint.point().x(),
+            ele: waypoint.elevation,
+            time: waypoint.time.and_then(|time| {
+                i64::try_from(OffsetDateTime::from(time).unix_timestamp_nanos()).ok()
+            }),
+            hdop: waypoint.hdop,
+            name: waypoint.name.clone(),
+        }
+    }
+}
+
+impl From<&IndexPoint> for Waypoint {
+    fn from(point: &IndexPoint) -> Self {
+        let mut waypoint = Waypoint::new(Point::new(point.lon, point.lat));
+        waypoint.elevation = point.ele;
+        waypoint.time = point.time.and_then(|time| {
+            OffsetDateTime::from_unix_timestamp_nanos(time.into())
+                .ok()
+                .map(Into::into)
+        });
+        waypoint.hdop = point.hdop;
+        waypoint.name = point.name.clone();
+        waypoint
+    }
+}
+
+/// A track of the reference file with its segments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct IndexTrack {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    segments: Vec<Vec<IndexPoint>>,
+}
+
+/// A route of the reference file, which is only used if the file has no tracks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct IndexRoute {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    points: Vec<IndexPoint>,
+}
+
+/// Content of an index file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ReferenceIndexFile {
+    version: u32,
+    /// Hash of the content of the reference file, see [`reference_hash`]
+    reference_hash: String,
+    tracks: Vec<IndexTrack>,
+    /// Missing in index files written before routes were supported
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    routes: Vec<IndexRoute>,
+    waypoints: Vec<IndexPoint>,
+}
+
+/// Function to hash the content of a reference file with the 64 bit FNV-1a hash.
+/// The hash only detects changes of the file, it is not meant to be cryptographically secure.
+/// - `data`: The content of the reference file.
+/// - Returns: The hash as hexadecimal string.
+pub fn reference_hash(data: &[u8]) -> String {
+    let hash = data.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Function to save the parsed reference file as index.
+/// Only the tracks, routes and waypoints with the positions, elevations, times, HDOPs and names of their points are saved, which is everything used for comparisons.
+/// - `path`: The path of the index file, which is overwritten.
+/// - `reference_data`: The content of the reference file, whose hash is saved.
+/// - `gpx`: The parsed reference file.
+/// - Returns: An error if the index could not be written.
+pub fn save_reference_index(
+    path: &Path,
+    reference_data: &[u8],
+    gpx: &Gpx,
+) -> Result<(), ReferenceIndexError> {
+    let index = ReferenceIndexFile {
+        version: REFERENCE_INDEX_VERSION,
+        reference_hash: reference_hash(reference_data),
+        tracks: gpx
+            .tracks
+            .iter()
+            .map(|track| IndexTrack {
+                name: track.name.clone(),
+                segments: track
+                    .segments
+                    .iter()
+                    .map(|segment| segment.points.iter().map(IndexPoint::from).collect())
+                    .collect(),
+            })
+            .collect(),
+        routes: gpx
+            .routes
+            .iter()
+            .map(|route| IndexRoute {
+                name: route.name.clone(),
+                points: route.points.iter().map(IndexPoint::from).collect(),
+            })
+            .collect(),
+        waypoints: gpx.waypoints.iter().map(IndexPoint::from).collect(),
+    };
+    let json = serde_json::to_string(&index).expect("The index can always be serialized");
+    fs::write(path, json)?;
+    Ok(())
+}
+
+/// Function to load the reference file from an index.
+/// - `path`: The path of the index file.
+/// - `reference_data`: The current content of the reference file, whose hash has to match the saved one.
+/// - Returns: The reference file as GPX structure, or an error if the index can not be read, has another format version or is stale.
+pub fn load_reference_index(
+    path: &Path,
+    reference_data: &[u8],
+) -> Result<Gpx, ReferenceIndexError> {
+    let content = fs::read_to_string(path)?;
+    let invalid = |error: serde_json::Error| ReferenceIndexError::Invalid(error.to_string());
+
+    // The version is checked first, as other versions of the format may not be parsable at all
+    let value: serde_json::Value = serde_json::from_str(&content).map_err(invalid)?;
+    match value.get("version").and_then(serde_json::Value::as_u64) {
+        Some(version) if version == u64::from(REFERENCE_INDEX_VERSION) => {}
+        Some(version) => {
+            return Err(ReferenceIndexError::Version(
+                version.try_into().unwrap_or(u32::MAX),
+            ))
+        }
+        None => {
+            return Err(ReferenceIndexError::Invalid(
+                "the format version is missing".to_string(),
+            ))
+        }
+    }
+    let index: ReferenceIndexFile = serde_json::from_value(value).map_err(invalid)?;
+    if index.reference_hash != reference_hash(reference_data) {
+        return Err(ReferenceIndexError::Stale);
+    }
+
+    Ok(Gpx {
+        version: GpxVersion::Gpx11,
+        tracks: index
+            .tracks
+            .iter()
+            .map(|index_track| {
+                let mut track = Track::new();
+                track.name = index_track.name.clone();
+                track.segments = index_track
+                    .segments
+                    .iter()
+                    .map(|points| TrackSegment {
+                        points: points.iter().map(Waypoint::from).collect(),
+                    })
+                    .collect();
+                track
+            })
+            .collect(),
+        routes: index
+            .routes
+            .iter()
+            .map(|index_route| Route {
+                name: index_route.name.clone(),
+                points: index_route.points.iter().map(Waypoint::from).collect(),
+                ..Route::default()
+            })
+            .collect(),
+        waypoints: index.waypoints.iter().map(Waypoint::from).collect(),
+        ..Gpx::default()
+    })
+}
@@ -0,0 +1,100 @@
+//! Conversion of Garmin Flexible and Interoperable Data Transfer (FIT) files.
+//!
+//! All `record` messages of the file become the waypoints of a single track with a single segment.
+//! Records without a position (e.g. while the device had no GPS fix) are skipped.
+
+use fitparser::profile::MesgNum;
+use fitparser::Value;
+use geo::Point;
+use gpx::{Gpx, GpxVersion, Track, TrackSegment, Waypoint};
+use time::OffsetDateTime;
+
+use super::InputError;
+
+/// Factor to convert the semicircles used for positions in FIT files to degrees (180° are 2^31 semicircles)
+const DEGREES_PER_SEMICIRCLE: f64 = 180.0 / 2_147_483_648.0;
+
+/// Function to get the numeric value of a field.
+/// - `value`: The value of the field.
+/// - Returns: The value as floating point number, `None` if the value is not numeric.
+fn numeric_value(value: &Value) -> Option<f64> {
+    match *value {
+        Value::SInt8(value) => Some(value as f64),
+        Value::UInt8(value) | Value::UInt8z(value) | Value::Byte(value) => Some(value as f64),
+        Value::SInt16(value) => Some(value as f64),
+        Value::UInt16(value) | Value::UInt16z(value) => Some(value as f64),
+        Value::SInt32(value) => Some(value as f64),
+        Value::UInt32(value) | Value::UInt32z(value) => Some(value as f64),
+        Value::SInt64(value) => Some(value as f64),
+        Value::UInt64(value) | Value::UInt64z(value) => Some(value as f64),
+        Value::Float32(value) => Some(value as f64),
+        Value::Float64(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Function to parse the content of a FIT file.
+/// - `data`: The content of the FIT file.
+/// - Returns: The records of the file converted to a GPX structure.
+pub(super) fn read(data: &[u8]) -> Result<Gpx, InputError> {
+    // The parser does not check the sizes given in the header itself, so truncated files are rejected first
+    // The header contains its own size in the first byte and the size of the data records in the bytes 4 to 7, followed by a 2 byte checksum
+    let truncated = || InputError::Invalid("the FIT file is truncated".to_string());
+    let header_size = *data.first().ok_or_else(truncated)? as usize;
+    let data_size = data
+        .get(4..8)
+        .map(|size| u32::from_le_bytes([size[0], size[1], size[2], size[3]]) as usize)
+        .ok_or_else(truncated)?;
+    if header_size < 12 || data.len() < header_size + data_size + 2 {
+        return Err(truncated());
+    }
+
+    let records = fitparser::from_bytes(data)?;
+
+    let mut segment = TrackSegment::new();
+    for record in records
+        .iter()
+        .filter(|record| record.kind() == MesgNum::Record)
+    {
+        let (mut latitude, mut longitude, mut elevation, mut time) = (None, None, None, None);
+        for field in record.fields() {
+            match (field.name(), field.value()) {
+                ("position_lat", value) => {
+                    latitude = numeric_value(value).map(|lat| lat * DEGREES_PER_SEMICIRCLE)
+                }
+                ("position_long", value) => {
+                    longitude = numeric_value(value).map(|lon| lon * DEGREES_PER_SEMICIRCLE)
+                }
+                // The enhanced altitude has a larger range, so it is preferred if both are present
+                ("enhanced_altitude", value) => elevation = numeric_value(value).or(elevation),
+                ("altitude", value) if elevation.is_none() => elevation = numeric_value(value),
+                ("timestamp", Value::Timestamp(timestamp)) => {
+                    time = OffsetDateTime::from_unix_timestamp(timestamp.timestamp()).ok()
+                }
+                _ => {}
+            }
+        }
+
+        // Records without a GPS fix have no (or an invalid) position
+        if let (Some(latitude), Some(longitude)) = (latitude, longitude) {
+            let mut waypoint = Waypoint::new(Point::new(longitude, latitude));
+            waypoint.elevation = elevation;
+            waypoint.time = time.map(Into::into);
+            segment.points.push(waypoint);
+        }
+    }
+
+    if segment.points.is_empty() {
+        return Err(InputError::Invalid(
+            "the FIT file does not contain any records with position data".to_string(),
+        ));
+    }
+
+    let mut track = Track::new();
+    track.segments.push(segment);
+    Ok(Gpx {
+        version: GpxVersion::Gpx11,
+        tracks: vec![track],
+        ..Gpx::default()
+    })
+}
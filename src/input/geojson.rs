@@ -0,0 +1,105 @@
+//! Conversion of GeoJSON files, e.g. hand-drawn routes.
+//!
+//! The first `LineString` or `MultiLineString` geometry of the file (searched in the order of the features)
+//! becomes a single track, every line string becomes its own track segment. The name of the track is taken
+//! from the `name` property of the feature, if there is one.
+
+use geo::Point;
+use geojson::{Feature, GeoJson, Geometry, GeometryValue, Position};
+use gpx::{Gpx, GpxVersion, Track, TrackSegment, Waypoint};
+
+use super::InputError;
+
+/// Function to convert the positions of a line string into a track segment.
+/// - `positions`: The `[lon, lat, alt?]` positions of the line string.
+/// - Returns: The track segment containing all positions as waypoints.
+fn to_segment(positions: &[Position]) -> Result<TrackSegment, InputError> {
+    let mut segment = TrackSegment::new();
+    for position in positions {
+        let mut waypoint = match position.as_slice() {
+            [longitude, latitude, ..] => Waypoint::new(Point::new(*longitude, *latitude)),
+            values => {
+                return Err(InputError::Invalid(format!(
+                    "invalid GeoJSON position {:?}",
+                    values
+                )))
+            }
+        };
+        waypoint.elevation = position.as_slice().get(2).copied();
+        segment.points.push(waypoint);
+    }
+    Ok(segment)
+}
+
+/// Function to find the first line string geometry, also searching geometry collections.
+/// - `geometry`: The geometry to search.
+/// - Returns: The line strings of the first `LineString` or `MultiLineString` geometry, if any.
+fn find_line_strings(geometry: &Geometry) -> Option<Vec<&[Position]>> {
+    match &geometry.value {
+        GeometryValue::LineString { coordinates } => Some(vec![coordinates.as_slice()]),
+        GeometryValue::MultiLineString { coordinates } => {
+            Some(coordinates.iter().map(Vec::as_slice).collect())
+        }
+        GeometryValue::GeometryCollection { geometries } => {
+            geometries.iter().find_map(find_line_strings)
+        }
+        _ => None,
+    }
+}
+
+/// Function to parse the content of a GeoJSON file.
+/// - `data`: The content of the GeoJSON file.
+/// - Returns: The first line string of the file converted to a GPX structure.
+pub(super) fn read(data: &[u8]) -> Result<Gpx, InputError> {
+    let text = std::str::from_utf8(data)
+        .map_err(|_| InputError::Invalid("the GeoJSON file is not valid UTF-8".to_string()))?;
+    let geojson: GeoJson = text.parse()?;
+
+    // Pairs of the geometries and the names of their features, in the order of the file
+    let feature_name = |feature: &Feature| {
+        feature
+            .property("name")
+            .and_then(|name| name.as_str())
+            .map(str::to_string)
+    };
+    let geometries: Vec<(&Geometry, Option<String>)> = match &geojson {
+        GeoJson::Geometry(geometry) => vec![(geometry, None)],
+        GeoJson::Feature(feature) => feature
+            .geometry
+            .iter()
+            .map(|geometry| (geometry, feature_name(feature)))
+            .collect(),
+        GeoJson::FeatureCollection(collection) => collection
+            .features
+            .iter()
+            .filter_map(|feature| {
+                feature
+                    .geometry
+                    .as_ref()
+                    .map(|geometry| (geometry, feature_name(feature)))
+            })
+            .collect(),
+    };
+
+    let (line_strings, name) = geometries
+        .into_iter()
+        .find_map(|(geometry, name)| find_line_strings(geometry).map(|lines| (lines, name)))
+        .ok_or_else(|| {
+            InputError::Invalid(
+                "the GeoJSON file does not contain a LineString or MultiLineString".to_string(),
+            )
+        })?;
+
+    let mut track = Track::new();
+    track.name = name;
+    track.segments = line_strings
+        .into_iter()
+        .map(to_segment)
+        .collect::<Result<_, _>>()?;
+
+    Ok(Gpx {
+        version: GpxVersion::Gpx11,
+        tracks: vec![track],
+        ..Gpx::default()
+    })
+}
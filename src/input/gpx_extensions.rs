@@ -0,0 +1,117 @@
+//! Elevations stored in the `<extensions>` of GPX points instead of their `<ele>` element.
+//!
+//! Some devices and apps only write the elevation of a point into an extension, which is skipped when parsing the GPX file.
+//! Elements named `ele`, `elevation` or `altitude` (case-insensitive) inside the `<extensions>` of a `<trkpt>` or `<wpt>`
+//! are used as the elevation of the point, if it has no `<ele>`. Only the namespaces in [`ELEVATION_NAMESPACES`] are
+//! recognized, all other extensions are ignored.
+
+use std::io::Cursor;
+
+use gpx::Gpx;
+use xml::reader::{EventReader, XmlEvent};
+
+use super::InputError;
+
+/// The namespaces of the extensions whose elevation elements are recognized.
+const ELEVATION_NAMESPACES: [&str; 4] = [
+    // Garmin GPX extensions, e.g. written by Garmin BaseCamp
+    "http://www.garmin.com/xmlschemas/GpxExtensions/v3",
+    // Garmin track point extensions, e.g. written by Garmin Connect
+    "http://www.garmin.com/xmlschemas/TrackPointExtension/v1",
+    "http://www.garmin.com/xmlschemas/TrackPointExtension/v2",
+    // Cluetrust GPX data extensions
+    "http://www.cluetrust.com/XML/GPXDATA/1/0",
+];
+
+/// The extension elevations of all points of a GPX file, `None` for points without one.
+#[derive(Default)]
+struct ExtensionElevations {
+    /// Elevation of every `<trkpt>`, in the order of the file
+    track_points: Vec<Option<f64>>,
+    /// Elevation of every `<wpt>`, in the order of the file
+    waypoints: Vec<Option<f64>>,
+}
+
+/// Function to check if an element inside of `<extensions>` contains an elevation.
+/// - `name`: The name of the element.
+/// - Returns: `true` if the element is an elevation element of a recognized namespace.
+fn is_elevation_element(name: &xml::name::OwnedName) -> bool {
+    name.namespace
+        .as_deref()
+        .is_some_and(|namespace| ELEVATION_NAMESPACES.contains(&namespace))
+        && ["ele", "elevation", "altitude"]
+            .iter()
+            .any(|local_name| name.local_name.eq_ignore_ascii_case(local_name))
+}
+
+/// Function to collect the elevations of the extensions of all points, in the order of the file.
+/// - `data`: The content of the GPX file.
+/// - Returns: The extension elevation of every `<trkpt>` and every `<wpt>`.
+fn extension_elevations(data: &[u8]) -> Result<ExtensionElevations, InputError> {
+    let mut elevations = ExtensionElevations::default();
+
+    // Elevation of the point which is currently parsed, `None` outside of points
+    let mut point: Option<Option<f64>> = None;
+    // Depth of nested `<extensions>` elements, and whether the current element is an elevation element
+    let mut extensions_depth = 0;
+    let mut in_elevation = false;
+
+    for event in EventReader::new(Cursor::new(data)) {
+        match event? {
+            XmlEvent::StartElement { name, .. } => match name.local_name.as_str() {
+                "trkpt" | "wpt" => point = Some(None),
+                "extensions" => extensions_depth += 1,
+                _ => in_elevation = extensions_depth > 0 && is_elevation_element(&name),
+            },
+            XmlEvent::Characters(text) if in_elevation => {
+                // Unparsable values are ignored like unknown extensions, the first valid one is used
+                if let (Some(elevation @ None), Ok(value)) =
+                    (point.as_mut(), text.trim().parse::<f64>())
+                {
+                    *elevation = Some(value).filter(|value| value.is_finite());
+                }
+            }
+            XmlEvent::EndElement { name } => {
+                in_elevation = false;
+                match name.local_name.as_str() {
+                    "trkpt" => elevations.track_points.push(point.take().flatten()),
+                    "wpt" => elevations.waypoints.push(point.take().flatten()),
+                    "extensions" => extensions_depth -= 1,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(elevations)
+}
+
+/// Function to fill in the elevation of all points without an `<ele>` from their extensions.
+/// - `data`: The content of the GPX file.
+/// - `gpx`: The already parsed GPX file, whose points are updated.
+pub(super) fn apply_extension_elevations(data: &[u8], gpx: &mut Gpx) -> Result<(), InputError> {
+    // Most files have no extensions at all, so they do not have to be parsed a second time
+    if !data
+        .windows(b"extensions".len())
+        .any(|window| window == b"extensions")
+    {
+        return Ok(());
+    }
+
+    let elevations = extension_elevations(data)?;
+    let points = gpx
+        .tracks
+        .iter_mut()
+        .flat_map(|track| &mut track.segments)
+        .flat_map(|segment| &mut segment.points)
+        .zip(elevations.track_points)
+        .chain(gpx.waypoints.iter_mut().zip(elevations.waypoints));
+    for (waypoint, elevation) in points {
+        if waypoint.elevation.is_none() {
+            waypoint.elevation = elevation;
+        }
+    }
+
+    Ok(())
+}
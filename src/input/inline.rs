@@ -0,0 +1,57 @@
+//! Conversion of reference paths given inline on the command line, e.g. `48.1,11.5;48.2,11.6`.
+//!
+//! The points are separated with semicolons and consist of the latitude and longitude separated with a comma.
+//! They become a single track with a single segment.
+
+use geo::Point;
+use gpx::{Gpx, GpxVersion, Track, TrackSegment, Waypoint};
+
+use super::InputError;
+
+/// Function to parse a single `lat,lon` point.
+/// - `point`: The text of the point.
+/// - Returns: The point as waypoint.
+fn parse_point(point: &str) -> Result<Waypoint, InputError> {
+    let invalid = || {
+        InputError::Invalid(format!(
+            "invalid point {:?}, expected the latitude and longitude separated with a comma (e.g. 48.1,11.5)",
+            point.trim()
+        ))
+    };
+    let (lat, lon) = point.split_once(',').ok_or_else(invalid)?;
+    let lat: f64 = lat.trim().parse().map_err(|_| invalid())?;
+    let lon: f64 = lon.trim().parse().map_err(|_| invalid())?;
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return Err(InputError::Invalid(format!(
+            "the point {:?} is outside of the valid range of latitude (-90 to 90) and longitude (-180 to 180)",
+            point.trim()
+        )));
+    }
+    Ok(Waypoint::new(Point::new(lon, lat)))
+}
+
+/// Function to parse an inline list of points.
+/// - `coordinates`: The points, separated with semicolons. A trailing semicolon is ignored.
+/// - Returns: The points converted to a GPX structure with a single track.
+pub(super) fn read(coordinates: &str) -> Result<Gpx, InputError> {
+    let points = coordinates
+        .trim()
+        .trim_end_matches(';')
+        .split(';')
+        .map(parse_point)
+        .collect::<Result<Vec<_>, _>>()?;
+    if points.len() < 2 {
+        return Err(InputError::Invalid(
+            "at least two points are needed for a path".to_string(),
+        ));
+    }
+
+    let mut track = Track::new();
+    track.name = Some("Inline reference".to_string());
+    track.segments.push(TrackSegment { points });
+    Ok(Gpx {
+        version: GpxVersion::Gpx11,
+        tracks: vec![track],
+        ..Gpx::default()
+    })
+}
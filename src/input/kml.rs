@@ -0,0 +1,96 @@
+//! Conversion of Keyhole Markup Language (KML) files.
+//!
+//! All `LineString` geometries of the file are combined into a single track, every `LineString`
+//! (e.g. of separate `Placemark`s) becomes its own track segment.
+
+use std::io::Cursor;
+
+use geo::Point;
+use gpx::{Gpx, GpxVersion, Track, TrackSegment, Waypoint};
+use xml::reader::{EventReader, XmlEvent};
+
+use super::InputError;
+
+/// Function to parse the content of a KML `coordinates` element.
+/// The coordinates are given as whitespace separated `lon,lat[,alt]` tuples.
+/// - `text`: The content of the element.
+/// - Returns: The track segment containing all coordinates as waypoints.
+fn parse_coordinates(text: &str) -> Result<TrackSegment, InputError> {
+    let mut segment = TrackSegment::new();
+
+    for tuple in text.split_whitespace() {
+        let values = tuple
+            .split(',')
+            .map(|value| value.parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()
+            .map_err(|_| InputError::Invalid(format!("invalid KML coordinate {:?}", tuple)))?;
+
+        let mut waypoint = match values.as_slice() {
+            [longitude, latitude, ..] => Waypoint::new(Point::new(*longitude, *latitude)),
+            _ => {
+                return Err(InputError::Invalid(format!(
+                    "invalid KML coordinate {:?}",
+                    tuple
+                )))
+            }
+        };
+        waypoint.elevation = values.get(2).copied();
+        segment.points.push(waypoint);
+    }
+
+    Ok(segment)
+}
+
+/// Function to parse the content of a KML file.
+/// - `data`: The content of the KML file.
+/// - Returns: The LineStrings of the file converted to a GPX structure with a single track.
+pub(super) fn read(data: &[u8]) -> Result<Gpx, InputError> {
+    let mut track = Track::new();
+
+    // Stack of the local names of all currently open elements
+    let mut elements: Vec<String> = Vec::new();
+
+    for event in EventReader::new(Cursor::new(data)) {
+        match event? {
+            XmlEvent::StartElement { name, .. } => elements.push(name.local_name),
+            XmlEvent::EndElement { .. } => {
+                elements.pop();
+            }
+            XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                let element = elements.last().map(String::as_str).unwrap_or_default();
+                let parent = elements
+                    .len()
+                    .checked_sub(2)
+                    .map(|index| elements[index].as_str())
+                    .unwrap_or_default();
+
+                match (parent, element) {
+                    // Use the first name of the document or a placemark as the name of the track
+                    ("Document" | "Placemark", "name") if track.name.is_none() => {
+                        track.name = Some(text.trim().to_string());
+                    }
+                    ("LineString", "coordinates") => {
+                        let segment = parse_coordinates(&text)?;
+                        if !segment.points.is_empty() {
+                            track.segments.push(segment);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if track.segments.is_empty() {
+        return Err(InputError::Invalid(
+            "the KML file does not contain any LineString geometry".to_string(),
+        ));
+    }
+
+    Ok(Gpx {
+        version: GpxVersion::Gpx11,
+        tracks: vec![track],
+        ..Gpx::default()
+    })
+}
@@ -0,0 +1,333 @@
+//! Loading of the supported input file formats.
+//!
+//! Every format is converted into a [`Gpx`] structure, so the rest of the application
+//! only has to deal with GPX tracks, track segments and waypoints.
+
+use std::fmt;
+use std::fs;
+use std::io::{BufRead, BufReader, Cursor, Read};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use gpx::errors::GpxError;
+use gpx::Gpx;
+use time::{OffsetDateTime, UtcOffset};
+use xml::reader::{EventReader, XmlEvent};
+
+mod fit;
+mod geojson;
+mod gpx_extensions;
+mod inline;
+mod kml;
+mod stream;
+mod tcx;
+
+pub use stream::StreamEvent;
+
+/// The file formats which can be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    /// GPS Exchange Format
+    Gpx,
+    /// Garmin Training Center XML
+    Tcx,
+    /// Keyhole Markup Language
+    Kml,
+    /// Garmin Flexible and Interoperable Data Transfer (binary)
+    Fit,
+    /// GeoJSON, e.g. hand-drawn routes
+    GeoJson,
+}
+
+impl InputFormat {
+    /// Function to detect the format of a file by its extension.
+    /// - `path`: The path of the file.
+    /// - Returns: The detected format, or `None` if the extension is unknown.
+    pub fn from_extension(path: &Path) -> Option<InputFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gpx" => Some(InputFormat::Gpx),
+            "tcx" => Some(InputFormat::Tcx),
+            "kml" => Some(InputFormat::Kml),
+            "fit" => Some(InputFormat::Fit),
+            "geojson" => Some(InputFormat::GeoJson),
+            _ => None,
+        }
+    }
+
+    /// Function to detect the format of a file by sniffing the name of the XML root element.
+    /// Binary FIT files are detected by the `.FIT` signature in their header and GeoJSON files by starting with a JSON object instead.
+    /// - `data`: The content of the file.
+    /// - Returns: The detected format, or `None` if the root element is unknown or the data is no XML.
+    pub fn sniff(data: &[u8]) -> Option<InputFormat> {
+        if data.get(8..12) == Some(b".FIT") {
+            return Some(InputFormat::Fit);
+        }
+        if data.trim_ascii_start().starts_with(b"{") {
+            return Some(InputFormat::GeoJson);
+        }
+        for event in EventReader::new(Cursor::new(data)) {
+            match event {
+                Ok(XmlEvent::StartElement { name, .. }) => {
+                    return match name.local_name.as_str() {
+                        "gpx" => Some(InputFormat::Gpx),
+                        "TrainingCenterDatabase" => Some(InputFormat::Tcx),
+                        "kml" => Some(InputFormat::Kml),
+                        _ => None,
+                    };
+                }
+                Ok(_) => continue,
+                Err(_) => return None,
+            }
+        }
+        None
+    }
+}
+
+/// Errors which can occur while reading an input file.
+#[derive(Debug)]
+pub enum InputError {
+    /// The file could not be read
+    Io(std::io::Error),
+    /// The file could not be parsed as GPX
+    Gpx(GpxError),
+    /// The file is no well-formed XML
+    Xml(xml::reader::Error),
+    /// The file could not be parsed as FIT
+    Fit(fitparser::Error),
+    /// The file could not be parsed as GeoJSON
+    GeoJson(::geojson::Error),
+    /// The format of the file could neither be detected by its extension nor by its content
+    UnknownFormat,
+    /// The file was parsed, but contains invalid data
+    Invalid(String),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io(error) => write!(f, "{}", error),
+            InputError::Gpx(error) => write!(f, "invalid GPX: {}", error),
+            InputError::Xml(error) => write!(f, "invalid XML: {}", error),
+            InputError::Fit(error) => write!(f, "invalid FIT: {}", error),
+            InputError::GeoJson(error) => write!(f, "invalid GeoJSON: {}", error),
+            InputError::UnknownFormat => write!(f, "unknown file format"),
+            InputError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl From<std::io::Error> for InputError {
+    fn from(error: std::io::Error) -> Self {
+        InputError::Io(error)
+    }
+}
+
+impl From<GpxError> for InputError {
+    fn from(error: GpxError) -> Self {
+        InputError::Gpx(error)
+    }
+}
+
+impl From<fitparser::Error> for InputError {
+    fn from(error: fitparser::Error) -> Self {
+        InputError::Fit(error)
+    }
+}
+
+impl From<::geojson::Error> for InputError {
+    fn from(error: ::geojson::Error) -> Self {
+        InputError::GeoJson(error)
+    }
+}
+
+impl From<xml::reader::Error> for InputError {
+    fn from(error: xml::reader::Error) -> Self {
+        InputError::Xml(error)
+    }
+}
+
+/// The magic bytes every gzip stream starts with.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Function to check if a path has a `.gz` extension.
+/// - `path`: The path of the file.
+/// - Returns: `true` if the file is gzip compressed according to its extension.
+pub fn is_gzip_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("gz"))
+}
+
+/// Function to get the name of a file of a supported format without its extensions, e.g. `run` for `run.gpx.gz`.
+/// - `path`: The path of the file.
+/// - Returns: The name without the extensions, `None` if the extension is not one of a supported format.
+pub fn input_file_stem(path: &Path) -> Option<String> {
+    let path = if is_gzip_path(path) {
+        Path::new(path.file_stem()?)
+    } else {
+        path
+    };
+    InputFormat::from_extension(path)?;
+    path.file_stem()?.to_str().map(str::to_string)
+}
+
+/// Function to decompress gzip compressed data.
+/// - `data`: The compressed data.
+/// - Returns: The decompressed data.
+fn decompress(data: &[u8]) -> Result<Vec<u8>, InputError> {
+    let mut decompressed = Vec::new();
+    GzDecoder::new(data).read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
+
+/// Function to read a file of any supported format.
+/// The format is detected by the file extension first, falling back to sniffing the XML root element.
+/// Files with a `.gz` extension are decompressed first, their format is detected by the extension
+/// before the `.gz` (e.g. `track.gpx.gz`).
+/// - `path`: The path of the file to read.
+/// - Returns: The content of the file converted to a GPX structure.
+pub fn read_file(path: &Path) -> Result<Gpx, InputError> {
+    let mut data = fs::read(path)?;
+    let mut path = path;
+    let stem;
+    if is_gzip_path(path) {
+        data = decompress(&data)?;
+        // Detect the format by the remaining extension, e.g. `gpx` of `track.gpx.gz`
+        stem = path.with_extension("");
+        path = &stem;
+    }
+    let format = InputFormat::from_extension(path)
+        .or_else(|| InputFormat::sniff(&data))
+        .ok_or(InputError::UnknownFormat)?;
+    read_bytes(&data, format)
+}
+
+/// Function to read a file of any supported format from a reader, e.g. the standard input.
+/// As there is no file extension, the format is always detected by sniffing the content.
+/// Gzip compressed content is detected by its magic bytes and decompressed first.
+/// - `reader`: The reader providing the content of the file.
+/// - Returns: The content of the file converted to a GPX structure.
+pub fn read_reader<R: Read>(mut reader: R) -> Result<Gpx, InputError> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    if data.starts_with(&GZIP_MAGIC) {
+        data = decompress(&data)?;
+    }
+    let format = InputFormat::sniff(&data).ok_or(InputError::UnknownFormat)?;
+    read_bytes(&data, format)
+}
+
+/// Function to read a path given inline as semicolon separated list of `lat,lon` points, e.g. `48.1,11.5;48.2,11.6`.
+/// - `coordinates`: The points of the path.
+/// - Returns: The path converted to a GPX structure with a single track.
+pub fn read_inline(coordinates: &str) -> Result<Gpx, InputError> {
+    inline::read(coordinates)
+}
+
+/// Function to parse the content of a file in the given format.
+/// - `data`: The content of the file.
+/// - `format`: The format of the content.
+/// - Returns: The content converted to a GPX structure.
+pub fn read_bytes(data: &[u8], format: InputFormat) -> Result<Gpx, InputError> {
+    let mut gpx = match format {
+        InputFormat::Gpx => {
+            let mut gpx = gpx::read(data)?;
+            gpx_extensions::apply_extension_elevations(data, &mut gpx)?;
+            gpx
+        }
+        InputFormat::Tcx => tcx::read(data)?,
+        InputFormat::Kml => kml::read(data)?,
+        InputFormat::Fit => fit::read(data)?,
+        InputFormat::GeoJson => geojson::read(data)?,
+    };
+    normalize_times(&mut gpx);
+    Ok(gpx)
+}
+
+/// Function to convert all timestamps of a file to UTC.
+/// Files may store times with any offset (e.g. `2024-05-01T10:00:00+02:00`), which would otherwise be written back with
+/// their original offset and make times of different files harder to compare when reading the output.
+/// - `gpx`: The content of the file, changed in place.
+fn normalize_times(gpx: &mut Gpx) {
+    let to_utc = |time: &mut Option<gpx::Time>| {
+        if let Some(value) = time {
+            *value = OffsetDateTime::from(*value)
+                .to_offset(UtcOffset::UTC)
+                .into();
+        }
+    };
+    if let Some(metadata) = &mut gpx.metadata {
+        to_utc(&mut metadata.time);
+    }
+    let points = gpx
+        .waypoints
+        .iter_mut()
+        .chain(gpx.routes.iter_mut().flat_map(|route| &mut route.points))
+        .chain(
+            gpx.tracks
+                .iter_mut()
+                .flat_map(|track| &mut track.segments)
+                .flat_map(|segment| &mut segment.points),
+        );
+    for point in points {
+        to_utc(&mut point.time);
+    }
+}
+
+/// Function to read the tracks of a file incrementally, see [`StreamEvent`].
+/// GPX files (also gzip compressed ones) are read point by point, so the memory needed does not grow with the size of the file.
+/// All other formats are read completely first, like with [`read_file`].
+/// - `path`: The path of the file to read.
+/// - `on_event`: Called for every track, track name, segment and point of the file.
+/// - Returns: An error if the file could not be read or parsed.
+pub fn stream_file(path: &Path, on_event: impl FnMut(StreamEvent)) -> Result<(), InputError> {
+    let stem = if is_gzip_path(path) {
+        path.with_extension("")
+    } else {
+        path.to_path_buf()
+    };
+    match InputFormat::from_extension(&stem) {
+        Some(InputFormat::Gpx) | None => {
+            stream_reader(BufReader::new(fs::File::open(path)?), on_event)
+        }
+        Some(_) => {
+            stream::replay(&read_file(path)?, on_event);
+            Ok(())
+        }
+    }
+}
+
+/// Function to read the tracks of a reader incrementally, e.g. the standard input, see [`stream_file`].
+/// The format is detected by sniffing the beginning of the content, gzip compressed content is decompressed on the fly.
+/// - `reader`: The reader providing the content of the file.
+/// - `on_event`: Called for every track, track name, segment and point of the file.
+/// - Returns: An error if the content could not be read or parsed.
+pub fn stream_reader<R: BufRead>(
+    mut reader: R,
+    on_event: impl FnMut(StreamEvent),
+) -> Result<(), InputError> {
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        stream_content(BufReader::new(GzDecoder::new(reader)), on_event)
+    } else {
+        stream_content(reader, on_event)
+    }
+}
+
+/// Function to read the tracks of uncompressed content incrementally, see [`stream_reader`].
+fn stream_content<R: BufRead>(
+    mut reader: R,
+    on_event: impl FnMut(StreamEvent),
+) -> Result<(), InputError> {
+    match InputFormat::sniff(reader.fill_buf()?).ok_or(InputError::UnknownFormat)? {
+        InputFormat::Gpx => stream::read(reader, on_event),
+        format => {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            stream::replay(&read_bytes(&data, format)?, on_event);
+            Ok(())
+        }
+    }
+}
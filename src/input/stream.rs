@@ -0,0 +1,111 @@
+//! Incremental reading of GPX files, for files too large to be loaded completely.
+//!
+//! Instead of building a [`Gpx`] structure, the track points are passed one by one to a callback, so the memory
+//! needed does not grow with the size of the file. Only the position of the track points is read, all other
+//! data (e.g. elevation, time, waypoints and routes) is skipped.
+
+use std::io::Read;
+
+use geo::Point;
+use gpx::Gpx;
+use xml::reader::{EventReader, XmlEvent};
+
+use super::InputError;
+
+/// A part of a track, in the order in which it appears in the file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    /// A new track starts
+    Track,
+    /// The name of the current track, which may only be known after some of its points
+    TrackName(String),
+    /// A new segment of the current track starts
+    Segment,
+    /// A point of the current segment, in LatLon coordinates
+    Point(Point),
+}
+
+/// Function to parse a coordinate attribute of a track point.
+fn parse_coordinate(name: &str, value: &str) -> Result<f64, InputError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| InputError::Invalid(format!("invalid {} {:?} of <trkpt>", name, value)))
+}
+
+/// Function to read the tracks of a GPX file incrementally.
+/// - `reader`: The reader providing the content of the GPX file.
+/// - `on_event`: Called for every track, track name, segment and point of the file.
+/// - Returns: An error if the file is no well-formed XML or a track point has no valid position.
+pub(super) fn read<R: Read>(
+    reader: R,
+    mut on_event: impl FnMut(StreamEvent),
+) -> Result<(), InputError> {
+    // Stack of the local names of all currently open elements
+    let mut elements: Vec<String> = Vec::new();
+
+    for event in EventReader::new(reader) {
+        match event? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                match name.local_name.as_str() {
+                    "trk" => on_event(StreamEvent::Track),
+                    "trkseg" => on_event(StreamEvent::Segment),
+                    "trkpt" => {
+                        let attribute = |attribute: &str| {
+                            attributes
+                                .iter()
+                                .find(|candidate| candidate.name.local_name == attribute)
+                                .ok_or_else(|| {
+                                    InputError::Invalid(format!(
+                                        "<trkpt> without the attribute {:?}",
+                                        attribute
+                                    ))
+                                })
+                                .and_then(|candidate| parse_coordinate(attribute, &candidate.value))
+                        };
+                        on_event(StreamEvent::Point(Point::new(
+                            attribute("lon")?,
+                            attribute("lat")?,
+                        )));
+                    }
+                    _ => {}
+                }
+                elements.push(name.local_name);
+            }
+            XmlEvent::Characters(text) => {
+                // Only the name directly inside of the track is its name, e.g. not the name of its link
+                if let [.., parent, element] = elements.as_slice() {
+                    if parent == "trk" && element == "name" {
+                        on_event(StreamEvent::TrackName(text.trim().to_string()));
+                    }
+                }
+            }
+            XmlEvent::EndElement { .. } => {
+                elements.pop();
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Function to pass the tracks of an already parsed file to a callback, like they would be read incrementally.
+/// - `gpx`: The parsed file.
+/// - `on_event`: Called for every track, track name, segment and point of the file.
+pub(super) fn replay(gpx: &Gpx, mut on_event: impl FnMut(StreamEvent)) {
+    for track in &gpx.tracks {
+        on_event(StreamEvent::Track);
+        if let Some(name) = &track.name {
+            on_event(StreamEvent::TrackName(name.clone()));
+        }
+        for segment in &track.segments {
+            on_event(StreamEvent::Segment);
+            for point in &segment.points {
+                on_event(StreamEvent::Point(point.point()));
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Conversion of Garmin Training Center XML (TCX) files.
+//!
+//! Every `Activity` or `Course` becomes a track, every `Track` element inside of it a track segment,
+//! and every `Trackpoint` with a `Position` a waypoint. Trackpoints without a position (e.g. while the
+//! device had no GPS fix) are skipped.
+
+use std::io::Cursor;
+
+use geo::Point;
+use gpx::{Gpx, GpxVersion, Track, TrackSegment, Waypoint};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use xml::reader::{EventReader, XmlEvent};
+
+use super::InputError;
+
+/// Values collected for the `Trackpoint` which is currently parsed
+#[derive(Default)]
+struct TrackpointData {
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    elevation: Option<f64>,
+    time: Option<OffsetDateTime>,
+}
+
+/// Function to parse a numeric value of a TCX element.
+fn parse_number(element: &str, text: &str) -> Result<f64, InputError> {
+    text.trim()
+        .parse()
+        .map_err(|_| InputError::Invalid(format!("invalid value {:?} in <{}>", text, element)))
+}
+
+/// Function to parse the content of a TCX file.
+/// - `data`: The content of the TCX file.
+/// - Returns: The tracks of the file converted to a GPX structure.
+pub(super) fn read(data: &[u8]) -> Result<Gpx, InputError> {
+    let mut gpx = Gpx {
+        version: GpxVersion::Gpx11,
+        ..Gpx::default()
+    };
+
+    // Stack of the local names of all currently open elements
+    let mut elements: Vec<String> = Vec::new();
+    let mut track: Option<Track> = None;
+    let mut segment: Option<TrackSegment> = None;
+    let mut trackpoint: Option<TrackpointData> = None;
+
+    for event in EventReader::new(Cursor::new(data)) {
+        match event? {
+            XmlEvent::StartElement { name, .. } => {
+                match name.local_name.as_str() {
+                    "Activity" | "Course" => track = Some(Track::new()),
+                    "Track" => segment = Some(TrackSegment::new()),
+                    "Trackpoint" => trackpoint = Some(TrackpointData::default()),
+                    _ => {}
+                }
+                elements.push(name.local_name);
+            }
+            XmlEvent::Characters(text) => {
+                let element = elements.last().map(String::as_str).unwrap_or_default();
+                let parent = elements
+                    .len()
+                    .checked_sub(2)
+                    .map(|index| elements[index].as_str())
+                    .unwrap_or_default();
+
+                // Use the course name or the activity id as the name of the track
+                if let Some(track) = track.as_mut() {
+                    if (element == "Name" && parent == "Course")
+                        || (element == "Id" && parent == "Activity")
+                    {
+                        track.name = Some(text.trim().to_string());
+                    }
+                }
+
+                if let Some(trackpoint) = trackpoint.as_mut() {
+                    match element {
+                        "LatitudeDegrees" => {
+                            trackpoint.latitude = Some(parse_number(element, &text)?)
+                        }
+                        "LongitudeDegrees" => {
+                            trackpoint.longitude = Some(parse_number(element, &text)?)
+                        }
+                        "AltitudeMeters" => {
+                            trackpoint.elevation = Some(parse_number(element, &text)?)
+                        }
+                        // Timestamps are not needed for the comparison, so unparsable ones are ignored
+                        "Time" => {
+                            trackpoint.time = OffsetDateTime::parse(text.trim(), &Rfc3339).ok()
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            XmlEvent::EndElement { name } => {
+                elements.pop();
+                match name.local_name.as_str() {
+                    "Trackpoint" => {
+                        let data = trackpoint.take().unwrap_or_default();
+                        if let (Some(latitude), Some(longitude), Some(segment)) =
+                            (data.latitude, data.longitude, segment.as_mut())
+                        {
+                            let mut waypoint = Waypoint::new(Point::new(longitude, latitude));
+                            waypoint.elevation = data.elevation;
+                            waypoint.time = data.time.map(Into::into);
+                            segment.points.push(waypoint);
+                        }
+                    }
+                    "Track" => {
+                        if let (Some(finished), Some(track)) = (segment.take(), track.as_mut()) {
+                            if !finished.points.is_empty() {
+                                track.segments.push(finished);
+                            }
+                        }
+                    }
+                    "Activity" | "Course" => {
+                        if let Some(finished) = track.take() {
+                            if !finished.segments.is_empty() {
+                                gpx.tracks.push(finished);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(gpx)
+}
@@ -0,0 +1,2057 @@
+//! Library to compare a reference GPS path to other tracks.
+//!
+//! By default all computations happen in a flat projection around the reference path (see the [flat_projection crate](https://docs.rs/flat_projection/latest/flat_projection/)),
+//! which is very precise for distances up to about 500 km. Alternatively, distances can be computed on the sphere (see [`DistanceMode`]).
+//! The main entry point is [`compare_tracks`],
+//! which calculates the point-wise average distance, the simplified point-wise average distance,
+//! the Fréchet distance, the Hausdorff distance and the Dynamic Time Warping distance between two tracks.
+//!
+//! Diagnostics, e.g. which direction or reference segment was chosen, are emitted through the [log crate](https://docs.rs/log/latest/log/)
+//! at the debug level. Install any logger implementation to receive them, nothing is printed otherwise.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use flat_projection::{FlatPoint, FlatProjection};
+use geo::EuclideanDistance;
+use geo::{Closest, Coord, Line, LineString, Point, SimplifyIdx, SimplifyVwIdx};
+use gpx::{Gpx, Route, Track, TrackSegment, Waypoint};
+
+mod antimeridian;
+mod checkpoints;
+pub mod config;
+mod elevation_profile;
+pub mod export;
+mod index;
+mod index_file;
+pub mod input;
+pub mod manifest;
+pub mod metrics;
+mod offset;
+mod options_builder;
+pub mod output;
+mod prefilter;
+mod resample;
+mod score;
+mod section;
+mod selection;
+pub mod serve;
+mod smooth;
+mod streaming;
+mod time_matching;
+mod time_stats;
+mod trim;
+mod units;
+mod utm;
+mod validation;
+
+pub use antimeridian::{longitude_near, project_point, unproject_point, wrap_longitude};
+pub use checkpoints::{checkpoint_distances, CheckpointDistance, CheckpointStatistics};
+pub use elevation_profile::{ascent_descent, elevation_profile, ElevationProfile};
+pub use index::{ClosestMatch, ReferenceIndex};
+pub use index_file::{
+    load_reference_index, reference_hash, save_reference_index, ReferenceIndexError,
+    REFERENCE_INDEX_VERSION,
+};
+pub use metrics::{dtw_distance, DistanceMode};
+pub use offset::{estimate_offset, offset_track, TrackOffset, MAX_OFFSET_ITERATIONS};
+pub use options_builder::CompareOptionsBuilder;
+pub use prefilter::bounding_boxes_overlap;
+pub use resample::resample_track;
+pub use score::{distance_score, match_score, ScoreWeights, HALF_SCORE_DISTANCE_M};
+pub use section::{clip_track, locate_fraction, locate_section, Section, SectionError};
+pub use selection::{exclude_reference_track, select_track, SelectionError, TrackSelector};
+pub use smooth::{smooth_track, SmoothingMethod};
+pub use streaming::{StreamingComparison, StreamingStatistics};
+pub use time_matching::{time_matched_distance, TimeMatchedDistance};
+pub use time_stats::{time_statistics, TimeStatistics};
+pub use trim::{trim_track, Trim};
+pub use units::{Units, FEET_PER_METER};
+pub use utm::{UtmProjection, UTM_HALF_ZONE_WIDTH};
+pub use validation::{validate_file, validate_track, FileIssue, TrackIssue};
+
+/// Options which influence how two tracks are compared.
+/// Use [`CompareOptions::builder`] to only give the options which differ from the defaults.
+#[derive(Debug, Clone)]
+pub struct CompareOptions {
+    /// Epsilon used for the simplification of the current track, in meters for the Ramer-Douglas-Peucker algorithm
+    /// and in square meters for the Visvalingam-Whyatt algorithm (see [`SimplifyAlgorithm`]).
+    pub simplify_epsilon: f64,
+    /// Algorithm used for the simplification of the current track.
+    pub simplify_algorithm: SimplifyAlgorithm,
+    /// Width of the Sakoe-Chiba band used for the Dynamic Time Warping distance, in points.
+    /// `None` computes the unconstrained DTW distance.
+    pub dtw_window: Option<usize>,
+    /// Whether the pairs of every point of the compared track and its closest point on the reference path should be collected.
+    pub collect_nearest_points: bool,
+    /// How the distance between two points is measured.
+    pub distance_mode: DistanceMode,
+    /// Additional epsilons (in meters) for which only the simplified average distance is calculated,
+    /// to see how it depends on the epsilon.
+    pub epsilon_sweep: Vec<f64>,
+    /// Whether the elevation of the points is included in the point distances.
+    /// Points where either side has no elevation fall back to the horizontal distance.
+    pub use_elevation: bool,
+    /// Spacing (in meters) to which both tracks are resampled before they are compared, see [`resample_track`].
+    /// `None` compares the recorded points.
+    pub resample: Option<f64>,
+    /// How the compared track is smoothed before it is compared (and resampled), see [`smooth_track`].
+    pub smoothing: SmoothingMethod,
+    /// Number of points within the window of the smoothing, an odd number of at least 3.
+    pub smooth_window: usize,
+    /// Distance (in meters) up to which a point counts as matching the reference path, see [`ToleranceStatistics`].
+    /// `None` skips the tolerance statistics.
+    pub tolerance: Option<f64>,
+    /// Full width (in meters) of the corridor around the reference path in which the compared track should stay, see [`CorridorStatistics`].
+    /// `None` skips the corridor statistics.
+    pub corridor_width: Option<f64>,
+    /// Length (in meters) of the consecutive windows along the compared track in which the distances are averaged, see [`distance_windows`].
+    /// `None` skips the windows.
+    pub window_report: Option<f64>,
+    /// Whether the average distance is also calculated in reverse, from the points of the reference path to the compared track.
+    pub symmetric: bool,
+    /// Whether the average distance is also calculated with every point weighted by its accuracy, see [`hdop_weights`].
+    pub weight_by_hdop: bool,
+    /// How the segments of the reference path are used, see [`ReferenceSegments`].
+    pub reference_segments: ReferenceSegments,
+    /// Whether the compared track is reversed if it runs the reference path backwards, see [`compare_tracks_with_projection`].
+    pub auto_orient: bool,
+    /// Whether the duration and speeds of the compared track are calculated from the timestamps of its points, see [`time_statistics`].
+    pub time_stats: bool,
+    /// Whether the elevation profiles of the compared track and the reference path are compared, see [`elevation_profile`].
+    pub elevation_profile: bool,
+    /// Whether every point of the compared track is also matched to the position of the reference path at its timestamp, see [`time_matched_distance`].
+    pub match_by_time: bool,
+    /// Coordinate system of the distances in the flat distance mode, see [`Projection`].
+    /// The simplification and the other preprocessing always happen in the flat projection.
+    pub projection: Projection,
+    /// Where the flat projection is centered, see [`track_projection`].
+    /// Only used by functions creating the projection themselves like [`compare_tracks`], not by [`compare_tracks_with_projection`].
+    pub projection_origin: ProjectionOrigin,
+    /// Maximum number of points of either track used for the Fréchet, Hausdorff and DTW distance, whose runtime grows quadratically.
+    /// Tracks with more points are uniformly downsampled for these metrics (see [`metrics::downsample`]), `None` uses all points.
+    pub max_points: Option<usize>,
+    /// Whether the lines connecting the end of a segment to the start of the next one are excluded from the lengths, see [`segment_gaps`].
+    /// All segments of a track are still joined for the closest point search and the curve metrics.
+    pub exclude_segment_gaps: bool,
+    /// How much of the start of both tracks is dropped before they are compared, see [`trim_track`].
+    pub start_trim: Option<Trim>,
+    /// How much of the end of both tracks is dropped before they are compared, see [`trim_track`].
+    pub end_trim: Option<Trim>,
+    /// Known offset which is removed from the compared track before it is compared, see [`offset_track`].
+    /// The track is moved by the negated offset, so e.g. a track recorded 2m too far north is moved 2m south.
+    pub track_offset: Option<TrackOffset>,
+    /// Whether the offset of the compared track is estimated and removed before it is compared, see [`estimate_offset`].
+    /// The estimation starts after removing [`CompareOptions::track_offset`], whose offset is included in the reported one.
+    pub auto_offset: bool,
+    /// Relative weights of the Fréchet, Hausdorff and average distance in [`TrackComparison::match_score`].
+    pub score_weights: ScoreWeights,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        CompareOptions {
+            simplify_epsilon: 1.0,
+            simplify_algorithm: SimplifyAlgorithm::Rdp,
+            dtw_window: None,
+            epsilon_sweep: Vec::new(),
+            collect_nearest_points: false,
+            distance_mode: DistanceMode::Flat,
+            use_elevation: false,
+            resample: None,
+            smoothing: SmoothingMethod::None,
+            smooth_window: 5,
+            tolerance: None,
+            corridor_width: None,
+            window_report: None,
+            symmetric: false,
+            weight_by_hdop: false,
+            time_stats: false,
+            elevation_profile: false,
+            match_by_time: false,
+            auto_orient: false,
+            reference_segments: ReferenceSegments::Join,
+            projection: Projection::Flat,
+            projection_origin: ProjectionOrigin::Reference,
+            max_points: None,
+            exclude_segment_gaps: false,
+            start_trim: None,
+            end_trim: None,
+            track_offset: None,
+            auto_offset: false,
+            score_weights: ScoreWeights::default(),
+        }
+    }
+}
+
+/// Result of comparing a single track to the reference path.
+/// All distances and lengths are given in meters.
+#[derive(Debug, Clone)]
+pub struct TrackComparison {
+    /// Total length of the compared track
+    pub current_track_length_m: f64,
+    /// Total length of the reference track
+    pub reference_track_length_m: f64,
+    /// Average distance counting every point of the compared track, NaN if the track has no points
+    pub average_distance_m: f64,
+    /// Average distance counting only the points of the simplified compared track
+    pub simplified_average_distance_m: f64,
+    /// Root mean square of the distances of all points of the compared track, which penalizes large deviations more than the average
+    pub rms_distance_m: f64,
+    /// Average distance of the points of the reference path to the compared track.
+    /// Only calculated if [`CompareOptions::symmetric`] is set, and `None` if the compared track has less than two points.
+    pub average_distance_reverse_m: Option<f64>,
+    /// Mean of the average distance and the reverse average distance, which does not depend on which track is the reference path
+    pub average_distance_symmetric_m: Option<f64>,
+    /// Average distance of the points of the compared track, weighted by the accuracy of every point (see [`hdop_weights`]).
+    /// Only calculated if [`CompareOptions::weight_by_hdop`] is set.
+    pub weighted_average_distance_m: Option<f64>,
+    /// Median distance of the points of the compared track
+    pub median_distance_m: f64,
+    /// 90th percentile of the distances of the points of the compared track
+    pub p90_distance_m: f64,
+    /// 95th percentile of the distances of the points of the compared track
+    pub p95_distance_m: f64,
+    /// Largest distance of a single point of the compared track
+    pub max_distance_m: f64,
+    /// Average signed horizontal offset of the points of the compared track, positive right and negative left of the reference path (in its direction of travel)
+    pub mean_signed_offset_m: f64,
+    /// Fraction of the points of the compared track which are right of the reference path (in its direction of travel)
+    pub fraction_right: f64,
+    /// Average component of the horizontal distances perpendicular to the matched segment of the reference path (lateral drift)
+    pub mean_cross_track_m: f64,
+    /// Average signed component of the horizontal distances along the matched segment of the reference path, positive ahead of it in its direction of travel (lag or lead).
+    /// Only points whose closest point is an end of the segment contribute, as the closest point on a segment is otherwise perpendicular to the point.
+    pub mean_along_track_m: f64,
+    /// 0-based index of the point of the compared track (counted over all segments) with the largest distance
+    pub max_distance_point_index: Option<usize>,
+    /// Closest point on the reference path (in LatLon coordinates) to the point with the largest distance, i.e. the other end of the largest excursion
+    pub max_distance_reference_point: Option<Point>,
+    /// Distance between the first points of the compared track and the reference path, `None` if either has no points.
+    /// Like all metrics it refers to the compared track after its preprocessing (e.g. trimming). If the track was [`reversed`](Self::reversed),
+    /// its first recorded point is compared to the last point of the reference path, where it starts running it backwards.
+    pub start_offset_m: Option<f64>,
+    /// Distance between the last points of the compared track and the reference path, or the first point of the reference path if the track was reversed
+    pub end_offset_m: Option<f64>,
+    /// Distance between the first point of the compared track and the other end of the reference path than the start offset.
+    /// If it is much smaller than the start offset, the track probably runs the reference path in the other direction than it was compared in.
+    pub start_to_end_offset_m: Option<f64>,
+    /// Number of points of the compared track without a closest point on the reference path, which happens if the reference path has no segments (less than two points).
+    /// These points are excluded from the average and all other point statistics, which are NaN if no point is left.
+    pub indeterminate_points: usize,
+    /// Fréchet distance between both tracks.
+    /// This and the other curve metrics are `None` if one of the tracks has less than two points.
+    pub frechet_distance_m: Option<f64>,
+    /// Fréchet distance divided by the length of the reference path, a unitless ratio which is comparable between routes of different lengths.
+    /// `None` if the Fréchet distance is not available or the reference path has no length.
+    pub frechet_normalized: Option<f64>,
+    /// Point of the compared track (in LatLon coordinates) of the pair of points which determines the Fréchet distance,
+    /// i.e. where the leash of the optimal coupling is the longest
+    pub frechet_track_point: Option<Point>,
+    /// Point of the reference path (in LatLon coordinates) of the pair of points which determines the Fréchet distance
+    pub frechet_reference_point: Option<Point>,
+    /// Hausdorff distance between both tracks, the larger of both directed Hausdorff distances
+    pub hausdorff_distance_m: Option<f64>,
+    /// Directed Hausdorff distance from the compared track to the reference path, i.e. the largest distance of a point of the track to the reference path.
+    /// If this is the larger direction, the track has an outlier (e.g. a detour).
+    pub hausdorff_track_to_reference_m: Option<f64>,
+    /// Directed Hausdorff distance from the reference path to the compared track, i.e. the largest distance of a point of the reference path to the track.
+    /// If this is the larger direction, the reference path has an outlier (e.g. a part the track skipped).
+    pub hausdorff_reference_to_track_m: Option<f64>,
+    /// Dynamic Time Warping distance between both tracks
+    pub dtw_distance_m: Option<f64>,
+    /// Match quality from 0 to 100 (a perfect overlay), the weighted mean of the scores of the Fréchet, Hausdorff and average distance, see [`match_score`].
+    /// `None` if none of the weighted distances is available.
+    pub match_score: Option<f64>,
+    /// Area enclosed between both tracks, in square meters.
+    /// Additionally `None` if the reference path has no segments.
+    pub area_between_m2: Option<f64>,
+    /// How much of the compared track stayed within the tolerance, only calculated if [`CompareOptions::tolerance`] is set
+    pub tolerance: Option<ToleranceStatistics>,
+    /// How much of the compared track stayed inside the corridor around the reference path, only calculated if [`CompareOptions::corridor_width`] is set.
+    /// `None` if the reference path has no segments.
+    pub corridor: Option<CorridorStatistics>,
+    /// Whether the compared track was reversed because it runs the reference path backwards, only possible if [`CompareOptions::auto_orient`] is set.
+    /// All metrics are then calculated on the reversed track. The point indices, geometries, speeds, ascent and descent, distance windows
+    /// and start and end offsets still refer to the original order of the points.
+    pub reversed: bool,
+    /// 1-based index of the segment of the reference path the track was compared to, only set if [`CompareOptions::reference_segments`] is [`ReferenceSegments::BestMatch`].
+    /// The reference track length and point count then refer to this segment.
+    pub reference_segment_index: Option<usize>,
+    /// Offset which was removed from the compared track, only set if [`CompareOptions::track_offset`] or [`CompareOptions::auto_offset`] is set.
+    pub track_offset: Option<TrackOffset>,
+    /// Duration and speeds of the compared track, only calculated if [`CompareOptions::time_stats`] is set.
+    /// `None` if the track has no timestamps.
+    pub time_statistics: Option<TimeStatistics>,
+    /// Elevation comparison of the compared track and the reference path, only calculated if [`CompareOptions::elevation_profile`] is set.
+    /// `None` if either track has no elevations.
+    pub elevation_profile: Option<ElevationProfile>,
+    /// Distances to the positions of the reference path at the timestamps of the points, only calculated if [`CompareOptions::match_by_time`] is set.
+    /// `None` if no point of the compared track has a timestamp within the time span of the reference path.
+    pub time_matched_distance: Option<TimeMatchedDistance>,
+    /// Number of points which were used for the average distance, not including the indeterminate points
+    pub point_count: usize,
+    /// Number of points of the reference path
+    pub reference_point_count: usize,
+    /// Number of points which were used for the simplified average distance
+    pub simplified_point_count: usize,
+    /// Average and maximum distances in consecutive windows along the compared track, only calculated if [`CompareOptions::window_report`] is set
+    pub distance_windows: Vec<DistanceWindow>,
+    /// Simplified average distances for every epsilon of [`CompareOptions::epsilon_sweep`]
+    pub epsilon_sweep: Vec<EpsilonSweepEntry>,
+    /// The simplified compared track, in LatLon coordinates.
+    /// The coordinates of the retained points are identical to the ones of the compared track (after its preprocessing).
+    pub simplified_track: LineString,
+    /// Every point of the compared track paired with its closest point on the reference path.
+    /// Only filled if [`CompareOptions::collect_nearest_points`] is set.
+    pub nearest_points: Vec<NearestPoint>,
+    /// Time spent in the different phases of the comparison
+    pub timings: ComparisonTimings,
+}
+
+impl TrackComparison {
+    /// Function to get which of both directed Hausdorff distances is larger, i.e. whether the track or the reference path has the outlier.
+    /// - Returns: The larger direction, `None` if the Hausdorff distance is not available.
+    pub fn hausdorff_direction(&self) -> Option<HausdorffDirection> {
+        let track_to_reference = self.hausdorff_track_to_reference_m?;
+        let reference_to_track = self.hausdorff_reference_to_track_m?;
+        Some(if track_to_reference > reference_to_track {
+            HausdorffDirection::TrackToReference
+        } else if reference_to_track > track_to_reference {
+            HausdorffDirection::ReferenceToTrack
+        } else {
+            HausdorffDirection::Equal
+        })
+    }
+
+    /// Function to get which fraction of the points of the compared track was kept by the simplification, to judge the epsilon.
+    /// - Returns: The number of simplified points divided by the number of points, `None` if no point was compared.
+    pub fn simplified_point_fraction(&self) -> Option<f64> {
+        (self.point_count > 0).then(|| self.simplified_point_count as f64 / self.point_count as f64)
+    }
+
+    /// Function to get how many times longer the longer of both tracks is than the shorter one.
+    /// A large ratio usually means that the track and the reference path do not cover the same route.
+    /// - Returns: The ratio of both lengths (at least 1), `None` if either track has no length.
+    pub fn length_ratio(&self) -> Option<f64> {
+        let (shorter, longer) = if self.current_track_length_m < self.reference_track_length_m {
+            (self.current_track_length_m, self.reference_track_length_m)
+        } else {
+            (self.reference_track_length_m, self.current_track_length_m)
+        };
+        (shorter > 0.0).then(|| longer / shorter)
+    }
+}
+
+/// Which of both directed Hausdorff distances is larger, see [`TrackComparison::hausdorff_direction`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HausdorffDirection {
+    /// A point of the compared track is furthest away from the reference path
+    TrackToReference,
+    /// A point of the reference path is furthest away from the compared track
+    ReferenceToTrack,
+    /// Both directions have the same distance
+    Equal,
+}
+
+impl fmt::Display for HausdorffDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HausdorffDirection::TrackToReference => write!(f, "track_to_reference"),
+            HausdorffDirection::ReferenceToTrack => write!(f, "reference_to_track"),
+            HausdorffDirection::Equal => write!(f, "equal"),
+        }
+    }
+}
+
+/// Time spent in the different phases of comparing a track to the reference path.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComparisonTimings {
+    /// Smoothing and resampling of the tracks
+    pub preprocessing: Duration,
+    /// Projection (or joining in the geodesic mode) of the tracks and calculation of their lengths
+    pub projection: Duration,
+    /// Calculation of the Fréchet distance
+    pub frechet: Duration,
+    /// Calculation of the Hausdorff distance
+    pub hausdorff: Duration,
+    /// Calculation of the Dynamic Time Warping distance
+    pub dtw: Duration,
+    /// Building the spatial index over the reference path
+    pub index: Duration,
+    /// Calculation of the average distance counting every point
+    pub average: Duration,
+    /// Simplification of the track and calculation of the simplified average distance(s), including the epsilon sweep
+    pub simplification: Duration,
+    /// Calculation of the area between both tracks
+    pub area: Duration,
+}
+
+impl ComparisonTimings {
+    /// Function to get the total time spent in all phases.
+    /// - Returns: The sum of all phases.
+    pub fn total(&self) -> Duration {
+        self.preprocessing
+            + self.projection
+            + self.frechet
+            + self.hausdorff
+            + self.dtw
+            + self.index
+            + self.average
+            + self.simplification
+            + self.area
+    }
+}
+
+/// Function to measure how long a computation takes.
+/// - `computation`: The computation to run.
+/// - Returns: The result of the computation and the elapsed time.
+fn timed<T>(computation: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = computation();
+    (result, start.elapsed())
+}
+
+/// How much of the compared track stayed within a tolerance around the reference path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToleranceStatistics {
+    /// The tolerance, in meters
+    pub tolerance_m: f64,
+    /// Fraction of the points of the compared track whose distance is within the tolerance, NaN if the track has no points
+    pub within_tolerance_fraction: f64,
+    /// Number of points of the longest contiguous run of points outside of the tolerance
+    pub longest_out_of_tolerance_points: usize,
+    /// Length along the compared track from the first to the last point of the longest run of points outside of the tolerance
+    pub longest_out_of_tolerance_m: f64,
+}
+
+/// How much of the length of the compared track lies inside a corridor around the reference path.
+/// The corridor contains every point within half its width of the reference path (i.e. the reference path buffered by half the width),
+/// so unlike the tolerance it is measured along the lines between the points instead of only at the points themselves.
+/// geo has no buffer operation, so instead of intersecting the track with a buffered polygon, every line of the track is sampled
+/// in steps of an eighth of the width and every sample counts as inside or outside for the length around it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorridorStatistics {
+    /// Full width of the corridor, in meters
+    pub width_m: f64,
+    /// Fraction of the length of the compared track inside the corridor, not including the indeterminate length, NaN if the track has no length
+    pub inside_length_fraction: f64,
+    /// Length of the compared track outside of the corridor
+    pub outside_length_m: f64,
+    /// Area between the parts of the compared track outside of the corridor and the edge of the corridor, in square meters
+    pub outside_area_m2: f64,
+    /// Length of the compared track without a closest point on the reference path (only if it has less than two points), neither inside nor outside of the corridor
+    pub indeterminate_length_m: f64,
+}
+
+/// Maximum number of samples along a single line of the compared track for the corridor statistics.
+const MAX_CORRIDOR_SAMPLES_PER_LINE: usize = 1000;
+
+/// Distances of the points within a window along the compared track, measured from its first point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceWindow {
+    /// Start of the window along the compared track
+    pub start_m: f64,
+    /// End of the window along the compared track, the length of the track for the last window
+    pub end_m: f64,
+    /// Average distance of the points within the window
+    pub average_m: f64,
+    /// Largest distance of a point within the window
+    pub max_m: f64,
+}
+
+/// A point of the compared track paired with its closest point on the reference path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NearestPoint {
+    /// Line from the point of the compared track to its closest point on the reference path, in LatLon coordinates
+    pub line: Line,
+    /// Distance between both points (including the elevation difference if elevations are used)
+    pub distance_m: f64,
+    /// Elevation of the point of the compared track in meters, if it has one
+    pub elevation: Option<f64>,
+    /// Length of the compared track from its first point up to this point
+    pub cumulative_arc_m: f64,
+}
+
+/// Simplified average distance for a single epsilon of an epsilon sweep.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpsilonSweepEntry {
+    /// Epsilon used for the simplification, in meters
+    pub epsilon_m: f64,
+    /// Average distance counting only the points of the simplified compared track
+    pub simplified_average_distance_m: f64,
+    /// Number of points of the simplified compared track
+    pub simplified_point_count: usize,
+}
+
+/// Algorithm used to simplify the compared track for the simplified average distance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimplifyAlgorithm {
+    /// Ramer-Douglas-Peucker, which keeps every point deviating more than the epsilon (a distance in meters) from the simplified line
+    #[default]
+    Rdp,
+    /// Visvalingam-Whyatt, which repeatedly removes the point forming the triangle with the smallest area with its neighbours,
+    /// until all triangles are larger than the epsilon (an area in square meters). This often simplifies curvy tracks more naturally.
+    Visvalingam,
+}
+
+impl FromStr for SimplifyAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rdp" => Ok(SimplifyAlgorithm::Rdp),
+            "visvalingam" => Ok(SimplifyAlgorithm::Visvalingam),
+            _ => Err(format!("unknown simplify algorithm {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for SimplifyAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimplifyAlgorithm::Rdp => write!(f, "rdp"),
+            SimplifyAlgorithm::Visvalingam => write!(f, "visvalingam"),
+        }
+    }
+}
+
+/// How the segments of the reference path are used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReferenceSegments {
+    /// All segments are joined into a single reference path
+    #[default]
+    Join,
+    /// Every segment is a separate candidate (e.g. an alternative route), the track is compared to the segment with the smallest average distance
+    BestMatch,
+}
+
+impl FromStr for ReferenceSegments {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "join" => Ok(ReferenceSegments::Join),
+            "best-match" => Ok(ReferenceSegments::BestMatch),
+            _ => Err(format!("unknown reference segments mode {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for ReferenceSegments {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferenceSegments::Join => write!(f, "join"),
+            ReferenceSegments::BestMatch => write!(f, "best-match"),
+        }
+    }
+}
+
+/// Where the flat projection used for all distance calculations is centered.
+/// The projection is most precise close to its origin, so the origin should be close to all compared points.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ProjectionOrigin {
+    /// The average position of all points of the reference path, see [`reference_origin`]
+    #[default]
+    Reference,
+    /// The average position of all points of the reference path and the compared track
+    Both,
+    /// A fixed position in LatLon coordinates (x = longitude, y = latitude)
+    Fixed(Point),
+}
+
+impl FromStr for ProjectionOrigin {
+    type Err = String;
+
+    /// Accepts `reference`, `auto-both` or a position given as `lat,lon`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "reference" => return Ok(ProjectionOrigin::Reference),
+            "auto-both" => return Ok(ProjectionOrigin::Both),
+            _ => {}
+        }
+
+        let invalid = || {
+            format!(
+                "invalid projection origin {:?}, expected \"reference\", \"auto-both\" or a position given as lat,lon",
+                s
+            )
+        };
+        let (lat, lon) = s.split_once(',').ok_or_else(invalid)?;
+        let lat: f64 = lat.trim().parse().map_err(|_| invalid())?;
+        let lon: f64 = lon.trim().parse().map_err(|_| invalid())?;
+        if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+            return Err(format!(
+                "the projection origin {}, {} (lat, lon) is out of range",
+                lat, lon
+            ));
+        }
+        Ok(ProjectionOrigin::Fixed(Point::new(lon, lat)))
+    }
+}
+
+impl fmt::Display for ProjectionOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectionOrigin::Reference => write!(f, "reference"),
+            ProjectionOrigin::Both => write!(f, "auto-both"),
+            ProjectionOrigin::Fixed(point) => write!(f, "{},{}", point.y(), point.x()),
+        }
+    }
+}
+
+/// Coordinate system of the distance calculations in the flat distance mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Projection {
+    /// The flat projection around the projection origin (see [`ProjectionOrigin`]), which is very precise for distances up to about 500 km
+    #[default]
+    Flat,
+    /// The UTM zone of the average position of the reference path (see [`UtmProjection`]), which stays precise for large areas
+    /// and at high latitudes, with a scale error below 0.1% within the zone
+    Utm,
+}
+
+impl FromStr for Projection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flat" => Ok(Projection::Flat),
+            "utm" => Ok(Projection::Utm),
+            _ => Err(format!("unknown projection {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Projection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Projection::Flat => write!(f, "flat"),
+            Projection::Utm => write!(f, "utm"),
+        }
+    }
+}
+
+/// Function to calculate the average position of points.
+/// The longitudes are averaged the shorter way around the globe from the first point, so points on both sides of the antimeridian
+/// average to a point next to them instead of one on the other side of the globe.
+/// - `waypoints`: The points.
+/// - Returns: The average position in LatLon coordinates, NaN if there are no points.
+fn centroid<'a>(mut waypoints: impl Iterator<Item = &'a Waypoint>) -> Point {
+    let Some(first) = waypoints.next() else {
+        return Point::new(f64::NAN, f64::NAN);
+    };
+    let first_longitude = first.point().x();
+    let (count, sum_positions) = waypoints.fold((1.0, first.point()), |(count, acc), waypoint| {
+        (
+            count + 1.0,
+            Point::new(
+                acc.x() + longitude_near(waypoint.point().x(), first_longitude),
+                acc.y() + waypoint.point().y(),
+            ),
+        )
+    });
+    Point::new(
+        wrap_longitude(sum_positions.x() / count),
+        sum_positions.y() / count,
+    )
+}
+
+/// Error of a reference path without any points, which can not be compared against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmptyReferenceError;
+
+impl fmt::Display for EmptyReferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the reference path does not contain any points")
+    }
+}
+
+impl std::error::Error for EmptyReferenceError {}
+
+/// Function to check that a track can be used as reference path, before the projection is created around it.
+/// The origin of the projection is the average position of the points of the reference path, which is NaN without any points
+/// and would silently turn all projected coordinates and distances into NaN.
+/// - `reference`: The reference track.
+/// - Returns: An error if the reference track has no points.
+pub fn check_reference(reference: &Track) -> Result<(), EmptyReferenceError> {
+    if reference
+        .segments
+        .iter()
+        .all(|segment| segment.points.is_empty())
+    {
+        Err(EmptyReferenceError)
+    } else {
+        Ok(())
+    }
+}
+
+/// Function to calculate the origin of the flat projection used for all distance calculations.
+/// - `reference`: The reference track around which the coordinates are projected.
+/// - Returns: The average position of all the points in the reference track, in LatLon coordinates.
+pub fn reference_origin(reference: &Track) -> Point {
+    // Find the average position of all the points in the reference track, around which we can project the coordinates
+    centroid(
+        reference
+            .segments
+            .iter()
+            .flat_map(|segment| &segment.points),
+    )
+}
+
+/// Function to calculate the origin of the flat projection used to compare a track to the reference path.
+/// - `reference`: The reference track.
+/// - `track`: The compared track.
+/// - `origin`: Where the projection is centered.
+/// - Returns: The origin of the projection, in LatLon coordinates.
+pub fn projection_origin(reference: &Track, track: &Track, origin: ProjectionOrigin) -> Point {
+    match origin {
+        ProjectionOrigin::Reference => reference_origin(reference),
+        ProjectionOrigin::Both => centroid(
+            reference
+                .segments
+                .iter()
+                .chain(&track.segments)
+                .flat_map(|segment| &segment.points),
+        ),
+        ProjectionOrigin::Fixed(point) => point,
+    }
+}
+
+/// Function to create the flat projection used to compare a track to the reference path.
+/// - `reference`: The reference track.
+/// - `track`: The compared track.
+/// - `origin`: Where the projection is centered, see [`projection_origin`].
+/// - Returns: The flat coordinate system used for projection, around NaN if the origin depends on a reference track without points
+///   (see [`check_reference`], such a reference track is rejected by [`compare_tracks_with_projection`]).
+pub fn track_projection(
+    reference: &Track,
+    track: &Track,
+    origin: ProjectionOrigin,
+) -> FlatProjection<f64> {
+    let origin = projection_origin(reference, track, origin);
+    FlatProjection::new(origin.x(), origin.y())
+}
+
+/// Function to create the flat projection used for all distance calculations.
+/// The projection is centered around the average position of all the points in the reference track (see [`reference_origin`]).
+/// - `reference`: The reference track around which the coordinates are projected.
+/// - Returns: The flat coordinate system used for projection, around NaN if the reference track has no points
+///   (see [`check_reference`], such a reference track is rejected by [`compare_tracks_with_projection`]).
+pub fn reference_projection(reference: &Track) -> FlatProjection<f64> {
+    let origin = reference_origin(reference);
+    FlatProjection::new(origin.x(), origin.y())
+}
+
+/// Function to find the point of a track which is furthest away from the origin of the flat projection.
+/// The flat projection gets less precise the further away a point is from its origin, so this shows whether the projection is still suitable.
+/// - `track`: The track.
+/// - `projector`: The flat coordinate system used for projection.
+/// - Returns: The largest distance of a point to the origin of the projection in meters, 0 for tracks without points.
+pub fn max_distance_from_origin(track: &Track, projector: &FlatProjection<f64>) -> f64 {
+    track
+        .segments
+        .iter()
+        .flat_map(|segment| &segment.points)
+        .map(|waypoint| {
+            let projected = project_point(projector, waypoint.point());
+            // The projected coordinates are given in kilometers relative to the origin
+            projected.x.hypot(projected.y) * 1000.0
+        })
+        .fold(0.0, f64::max)
+}
+
+/// Function to join segments and project them into a flat coordinate system.
+/// This function takes a list of segments, projects their points, and returns a single LineString.
+/// - `segments`: The GPS track segments to be joined and projected.
+/// - `projector`: The flat coordinate system used for projection.
+/// - Returns: A LineString containing all the projected points.
+pub fn join_and_project_segments(
+    segments: &[TrackSegment],
+    projector: &FlatProjection<f64>,
+) -> LineString {
+    let mut joined_segment = TrackSegment::new();
+    segments.iter().for_each(|segment| {
+        joined_segment
+            .points
+            .extend(segment.points.iter().map(|point| {
+                let projected_point = project_point(projector, point.point());
+                Waypoint::new(Point::new(projected_point.x, projected_point.y))
+            }));
+    });
+    joined_segment.linestring()
+}
+
+/// Function to calculate the weight of every point of the given segments from its horizontal dilution of precision (HDOP).
+/// Points with a poor accuracy (large HDOP) get a smaller weight.
+/// - `segments`: The GPS track segments whose points are used.
+/// - Returns: The weight `1 / HDOP` of every point, 1 for points without a (positive) HDOP.
+pub fn hdop_weights(segments: &[TrackSegment]) -> Vec<f64> {
+    segments
+        .iter()
+        .flat_map(|segment| &segment.points)
+        .map(|point| match point.hdop {
+            Some(hdop) if hdop > 0.0 && hdop.is_finite() => 1.0 / hdop,
+            _ => 1.0,
+        })
+        .collect()
+}
+
+/// Function to collect the elevations of all points of the given segments.
+/// - `segments`: The GPS track segments whose points are used.
+/// - Returns: The elevation of every point in meters, `None` for points without an elevation.
+pub fn join_elevations(segments: &[TrackSegment]) -> Vec<Option<f64>> {
+    segments
+        .iter()
+        .flat_map(|segment| &segment.points)
+        .map(|point| point.elevation)
+        .collect()
+}
+
+/// Function to join segments into a single LineString without projecting them.
+/// - `segments`: The GPS track segments to be joined.
+/// - Returns: A LineString containing all the points in LatLon coordinates.
+pub fn join_segments(segments: &[TrackSegment]) -> LineString {
+    segments
+        .iter()
+        .flat_map(|segment| &segment.points)
+        .map(|point| point.point().0)
+        .collect()
+}
+
+/// Function to find the lines of joined segments which connect the end of one segment to the start of the next one.
+/// These lines were never travelled, e.g. they bridge a pause of the recording. Empty segments are skipped like when joining the segments.
+/// - `segments`: The GPS track segments which are joined.
+/// - Returns: For every line of the joined LineString whether it connects two segments.
+pub fn segment_gaps(segments: &[TrackSegment]) -> Vec<bool> {
+    let mut gaps = Vec::new();
+    let mut is_first = true;
+    for segment in segments.iter().filter(|segment| !segment.points.is_empty()) {
+        if !is_first {
+            gaps.push(true);
+        }
+        is_first = false;
+        gaps.extend(std::iter::repeat_n(false, segment.points.len() - 1));
+    }
+    gaps
+}
+
+/// Function to convert a route, e.g. exported by a planning tool, into a track with a single segment.
+/// - `route`: The route.
+/// - Returns: A track with the name and the points of the route.
+pub fn route_track(route: &Route) -> Track {
+    let mut track = Track::new();
+    track.name = route.name.clone();
+    track.segments.push(TrackSegment {
+        points: route.points.clone(),
+    });
+    track
+}
+
+/// Function to get the first track of a file, falling back to its first route if the file has no tracks.
+/// - `gpx`: The content of the file.
+/// - Returns: A copy of the first track or the converted first route, `None` if the file has neither.
+pub fn first_track_or_route(gpx: &Gpx) -> Option<Track> {
+    gpx.tracks
+        .first()
+        .cloned()
+        .or_else(|| gpx.routes.first().map(route_track))
+}
+
+/// Function to split a track into one track per segment, so the segments can be compared separately.
+/// Comparing the segments separately avoids the connecting line between the end of one segment and the start of the next one,
+/// which is created when all segments are joined (e.g. for tracks with multiple laps or pauses).
+/// - `track`: The track to split.
+/// - Returns: One copy of the track for every segment, each containing only this segment.
+pub fn split_segments(track: &Track) -> Vec<Track> {
+    track
+        .segments
+        .iter()
+        .map(|segment| {
+            let mut segment_track = track.clone();
+            segment_track.segments = vec![segment.clone()];
+            segment_track
+        })
+        .collect()
+}
+
+/// Function to reverse the direction of a track, e.g. if it was recorded in the opposite direction of the reference path.
+/// - `track`: The track to reverse.
+/// - Returns: A copy of the track with the order of its segments and of the points within them reversed.
+pub fn reverse_track(track: &Track) -> Track {
+    let mut reversed = track.clone();
+    reversed.segments.reverse();
+    for segment in &mut reversed.segments {
+        segment.points.reverse();
+    }
+    reversed
+}
+
+/// Function to move a waypoint to another position, keeping all its other data (e.g. elevation, time and name).
+/// - `waypoint`: The waypoint to move.
+/// - `point`: The new position, in LatLon coordinates.
+/// - Returns: A copy of the waypoint at the new position.
+pub fn move_waypoint(waypoint: &Waypoint, point: Point) -> Waypoint {
+    // The position of a waypoint can only be set when creating it, so all other fields are copied over
+    let mut moved = Waypoint::new(point);
+    moved.elevation = waypoint.elevation;
+    moved.speed = waypoint.speed;
+    moved.time = waypoint.time;
+    moved.name = waypoint.name.clone();
+    moved.comment = waypoint.comment.clone();
+    moved.description = waypoint.description.clone();
+    moved.source = waypoint.source.clone();
+    moved.links = waypoint.links.clone();
+    moved.symbol = waypoint.symbol.clone();
+    moved.type_ = waypoint.type_.clone();
+    moved.geoidheight = waypoint.geoidheight;
+    moved.fix = waypoint.fix.clone();
+    moved.sat = waypoint.sat;
+    moved.hdop = waypoint.hdop;
+    moved.vdop = waypoint.vdop;
+    moved.pdop = waypoint.pdop;
+    moved.dgps_age = waypoint.dgps_age;
+    moved.dgpsid = waypoint.dgpsid;
+    moved
+}
+
+/// Function to unproject a LineString from a flat coordinate system back to LatLon coordinates.
+/// This function takes a LineString in a flat coordinate system and unprojects the points back to LatLon coordinates.
+/// - `linestring`: The LineString to be unprojected.
+/// - `projector`: The flat coordinate system used for projection.
+/// - Returns: A LineString containing all the unprojected points.
+pub fn unproject_linestring(
+    linestring: &LineString,
+    projector: &FlatProjection<f64>,
+) -> LineString {
+    linestring
+        .points()
+        .map(|point| {
+            unproject_point(
+                projector,
+                &FlatPoint {
+                    x: point.x(),
+                    y: point.y(),
+                },
+            )
+        })
+        .collect()
+}
+
+/// Function to calculate the total length of a LineString.
+/// The length is computed as the sum of distances between consecutive points in the LineString.
+/// - `linestring`: The LineString whose total length is to be calculated.
+/// - Returns: The total length of the LineString in kilometers.
+pub fn calculate_total_length(linestring: &LineString) -> f64 {
+    linestring
+        .points()
+        .zip(linestring.points().skip(1))
+        .map(|(p1, p2)| p1.euclidean_distance(&p2))
+        .sum()
+}
+
+/// Accumulated statistics of the distances between points and their closest points on the reference path.
+/// All distances are given in the units of the compared LineStrings.
+#[derive(Debug, Clone, Default)]
+pub struct DistanceAccumulator {
+    /// Sum of the distances of all compared points
+    pub total_distance: f64,
+    /// Sum of the squared distances of all compared points
+    pub total_squared_distance: f64,
+    /// Number of compared points
+    pub points: usize,
+    /// Largest distance of a single point
+    pub max_distance: f64,
+    /// 0-based index of the point with the largest distance, `None` if no point was compared
+    pub max_distance_index: Option<usize>,
+    /// Closest point on the reference path of the point with the largest distance, `None` if no point was compared
+    pub max_distance_reference_point: Option<Point>,
+    /// Distances of all compared points, in the order of the points
+    pub distances: Vec<f64>,
+    /// Sum of the signed horizontal offsets (positive right of the reference path) of all compared points
+    pub total_signed_offset: f64,
+    /// Number of points for which a signed offset was added
+    pub offset_points: usize,
+    /// Number of points right of the reference path
+    pub right_points: usize,
+    /// Sum of the components of the horizontal distances perpendicular to the matched reference segment, of all compared points
+    pub total_cross_track: f64,
+    /// Sum of the signed components of the horizontal distances along the matched reference segment, positive ahead of it, of all compared points
+    pub total_along_track: f64,
+    /// Number of points for which the cross-track and along-track components were added
+    pub track_error_points: usize,
+    /// Number of points without a closest point (e.g. if the reference path has no segments), which are not included in any other statistic
+    pub indeterminate_points: usize,
+}
+
+impl DistanceAccumulator {
+    /// Function to add the distance of a single point to the statistics.
+    /// - `index`: The 0-based index of the point.
+    /// - `distance`: The distance of the point to its closest point.
+    pub fn add(&mut self, index: usize, distance: f64) {
+        if self.max_distance_index.is_none() || distance > self.max_distance {
+            self.max_distance = distance;
+            self.max_distance_index = Some(index);
+        }
+
+        self.total_distance += distance;
+        self.total_squared_distance += distance * distance;
+        self.points += 1;
+        self.distances.push(distance);
+    }
+
+    /// Function to add the signed horizontal offset of a single point to the statistics.
+    /// - `signed_offset`: The offset of the point, positive right and negative left of the reference path.
+    pub fn add_offset(&mut self, signed_offset: f64) {
+        self.total_signed_offset += signed_offset;
+        self.offset_points += 1;
+        if signed_offset > 0.0 {
+            self.right_points += 1;
+        }
+    }
+
+    /// Function to add the cross-track and along-track components of the horizontal distance of a single point to the statistics.
+    /// - `cross_track`: The component perpendicular to the reference segment.
+    /// - `along_track`: The signed component along the reference segment.
+    pub fn add_track_error(&mut self, cross_track: f64, along_track: f64) {
+        self.total_cross_track += cross_track;
+        self.total_along_track += along_track;
+        self.track_error_points += 1;
+    }
+
+    /// Function to get the average cross-track and along-track components of all compared points.
+    /// - Returns: The average cross-track and signed along-track component, NaN if no component was added.
+    pub fn mean_track_error(&self) -> (f64, f64) {
+        (
+            self.total_cross_track / self.track_error_points as f64,
+            self.total_along_track / self.track_error_points as f64,
+        )
+    }
+
+    /// Function to get the average signed offset of all compared points.
+    /// - Returns: The average signed offset, NaN if no offset was added.
+    pub fn mean_signed_offset(&self) -> f64 {
+        self.total_signed_offset / self.offset_points as f64
+    }
+
+    /// Function to get the fraction of the compared points which are right of the reference path.
+    /// - Returns: The fraction between 0 and 1, NaN if no offset was added.
+    pub fn fraction_right(&self) -> f64 {
+        self.right_points as f64 / self.offset_points as f64
+    }
+
+    /// Function to get the distances of all compared points sorted in ascending order.
+    /// - Returns: The sorted distances.
+    pub fn sorted_distances(&self) -> Vec<f64> {
+        let mut sorted = self.distances.clone();
+        sorted.sort_by(f64::total_cmp);
+        sorted
+    }
+
+    /// Function to get the average distance of all compared points.
+    /// - Returns: The average distance, NaN if no point was compared.
+    pub fn average(&self) -> f64 {
+        self.total_distance / self.points as f64
+    }
+
+    /// Function to get the root mean square of the distances of all compared points.
+    /// Compared to the average, large distances have a bigger influence.
+    /// - Returns: The root mean square distance, NaN if no point was compared.
+    pub fn rms(&self) -> f64 {
+        (self.total_squared_distance / self.points as f64).sqrt()
+    }
+
+    /// Function to get the weighted average distance of all compared points.
+    /// - `weights`: The weight of every compared point, in the order of the points.
+    /// - Returns: The weighted average distance, NaN if no point was compared.
+    pub fn weighted_average(&self, weights: &[f64]) -> f64 {
+        let (weighted_sum, total_weight) = self
+            .distances
+            .iter()
+            .zip(weights)
+            .fold((0.0, 0.0), |(sum, total), (distance, weight)| {
+                (sum + distance * weight, total + weight)
+            });
+        weighted_sum / total_weight
+    }
+}
+
+/// Function to calculate a percentile of a sorted list of values.
+/// The percentile is linearly interpolated between the two closest ranks, where the rank of the
+/// `p`-th percentile is `p / 100 * (n - 1)` (the same definition as the default of numpy).
+/// - `sorted_values`: The values, sorted in ascending order.
+/// - `percentile`: The percentile to calculate, between 0 and 100.
+/// - Returns: The interpolated value, NaN if there are no values.
+pub fn percentile(sorted_values: &[f64], percentile: f64) -> f64 {
+    if sorted_values.is_empty() {
+        return f64::NAN;
+    }
+
+    let rank = (percentile / 100.0).clamp(0.0, 1.0) * (sorted_values.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let fraction = rank - lower as f64;
+
+    sorted_values[lower] + (sorted_values[upper] - sorted_values[lower]) * fraction
+}
+
+/// Function to average the distances of the points in consecutive windows along a track, e.g. to see where a track deviates from the reference path.
+/// Windows without any points (e.g. because of a gap in the recording) are skipped.
+/// - `positions`: The position of every point along the track, in ascending order.
+/// - `distances`: The distance of every point to its closest point, in the order of the points.
+/// - `window`: The length of the windows, in the units of the positions.
+/// - Returns: All windows containing at least one point, in the order along the track.
+pub fn distance_windows(positions: &[f64], distances: &[f64], window: f64) -> Vec<DistanceWindow> {
+    let track_length = positions.last().copied().unwrap_or(0.0);
+    let window_index = |position: f64| (position / window).floor() as usize;
+
+    let mut windows = Vec::new();
+    let mut start = 0;
+    while start < positions.len().min(distances.len()) {
+        // All points up to the first point of the next window belong to this window
+        let index = window_index(positions[start]);
+        let end = positions[start..]
+            .iter()
+            .position(|&position| window_index(position) != index)
+            .map_or(positions.len(), |offset| start + offset)
+            .min(distances.len());
+        let window_distances = &distances[start..end];
+
+        windows.push(DistanceWindow {
+            start_m: index as f64 * window,
+            end_m: ((index + 1) as f64 * window).min(track_length),
+            average_m: window_distances.iter().sum::<f64>() / window_distances.len() as f64,
+            max_m: window_distances.iter().copied().fold(0.0, f64::max),
+        });
+        start = end;
+    }
+    windows
+}
+
+/// Function to sign a horizontal distance by the side of a reference segment a point lies on.
+/// The side is determined by the cross product of the direction of the segment and the vector from its start to the point,
+/// which works for projected as well as LatLon coordinates, as longitude and latitude point east and north as well.
+/// - `segment`: The segment of the reference path, in the direction of travel.
+/// - `point`: The compared point.
+/// - `distance`: The (unsigned) horizontal distance of the point to the segment.
+/// - Returns: The distance, positive if the point is right of the direction of travel, negative if it is left and 0 if it is on the segment.
+pub fn signed_offset(segment: &Line, point: &Point, distance: f64) -> f64 {
+    let cross = segment
+        .start_point()
+        .cross_prod(segment.end_point(), *point);
+    if cross < 0.0 {
+        distance
+    } else if cross > 0.0 {
+        -distance
+    } else {
+        0.0
+    }
+}
+
+/// Function to split the horizontal distance of a point to its closest point into components perpendicular to and along a reference segment.
+/// The components are calculated from the angle between the segment and the vector from the closest point to the point.
+/// In the geodesic mode the longitudes are scaled by the cosine of the latitude, so both axes have about the same scale.
+/// - `segment`: The segment of the reference path, in the direction of travel.
+/// - `point`: The compared point.
+/// - `closest`: The closest point to `point` on the segment.
+/// - `distance`: The (unsigned) horizontal distance between both points.
+/// - `distance_mode`: How the coordinates are given.
+/// - Returns: The cross-track component (never negative) and the along-track component (positive if the point is ahead of the segment).
+pub fn track_error_components(
+    segment: &Line,
+    point: &Point,
+    closest: &Point,
+    distance: f64,
+    distance_mode: DistanceMode,
+) -> (f64, f64) {
+    let x_scale = match distance_mode {
+        DistanceMode::Flat => 1.0,
+        DistanceMode::Geodesic => closest.y().to_radians().cos(),
+    };
+    let direction = (segment.dx() * x_scale, segment.dy());
+    let offset = ((point.x() - closest.x()) * x_scale, point.y() - closest.y());
+    let lengths = direction.0.hypot(direction.1) * offset.0.hypot(offset.1);
+    if lengths == 0.0 {
+        return (distance, 0.0);
+    }
+    let cos = ((direction.0 * offset.0 + direction.1 * offset.1) / lengths).clamp(-1.0, 1.0);
+    (distance * (1.0 - cos * cos).sqrt(), distance * cos)
+}
+
+/// Function to calculate the summed distance between two LineStrings.
+/// For every point of the current LineString the closest point on the reference LineString is searched,
+/// and the distance between them (measured according to the distance mode of the index) is added to the `accumulator`.
+/// Points without a closest point (only if the reference LineString has no segments) are only counted as indeterminate.
+/// If elevations are given for the current points and the index has elevations as well, the elevation difference is included in the distance.
+/// - `current_linestring`: The LineString whose points are compared.
+/// - `current_elevations`: Optional elevation of every point of the current LineString, in the units of the distance mode.
+/// - `reference_index`: The spatial index of the LineString on which the closest points are searched.
+/// - `accumulator`: Accumulator for the distance statistics.
+/// - `nearest_points`: Optional collector for the lines from every compared point to its closest point, together with their distance.
+pub fn calculate_average_distance(
+    current_linestring: &LineString,
+    current_elevations: Option<&[Option<f64>]>,
+    reference_index: &ReferenceIndex,
+    accumulator: &mut DistanceAccumulator,
+    mut nearest_points: Option<&mut Vec<(Line, f64)>>,
+) {
+    current_linestring
+        .points()
+        .enumerate()
+        .for_each(|(index, point)| {
+            // Find the distance to the closest point on the reference path
+            let closest = reference_index.closest_match(&point);
+            let closest_point = closest.point;
+
+            let distance_mode = reference_index.distance_mode();
+            let current_distance = match closest_point {
+                Closest::Intersection(p) | Closest::SinglePoint(p) => {
+                    distance_mode.distance(&p, &point)
+                }
+                // Without a closest point there is no distance, counting it as infinitely far away would turn all averages into infinity
+                Closest::Indeterminate => {
+                    accumulator.indeterminate_points += 1;
+                    return;
+                }
+            };
+
+            // The horizontal distance is signed by the side of the reference segment the point lies on
+            if let Some(segment) = closest.segment {
+                accumulator.add_offset(signed_offset(&segment, &point, current_distance));
+                if let Closest::Intersection(p) | Closest::SinglePoint(p) = closest_point {
+                    let (cross_track, along_track) = track_error_components(
+                        &segment,
+                        &point,
+                        &p,
+                        current_distance,
+                        distance_mode,
+                    );
+                    accumulator.add_track_error(cross_track, along_track);
+                }
+            }
+
+            // Include the elevation difference if both points have an elevation
+            let current_elevation = current_elevations.and_then(|elevations| elevations[index]);
+            let current_distance = match (current_elevation, closest.elevation) {
+                (Some(current), Some(closest)) => current_distance.hypot(current - closest),
+                _ => current_distance,
+            };
+
+            if let (Some(nearest_points), Closest::Intersection(p) | Closest::SinglePoint(p)) =
+                (nearest_points.as_mut(), closest_point)
+            {
+                nearest_points.push((Line::new(point, p), current_distance));
+            }
+
+            // Add the distance to the statistics, remembering the closest point of the largest distance
+            accumulator.add(index, current_distance);
+            if let (Some(max_index), Closest::Intersection(p) | Closest::SinglePoint(p)) =
+                (accumulator.max_distance_index, closest_point)
+            {
+                if max_index == index {
+                    accumulator.max_distance_reference_point = Some(p);
+                }
+            }
+        });
+}
+
+/// Function to compare a track to the reference path.
+/// - `reference`: The reference track.
+/// - `track`: The track to compare to the reference track.
+/// - `opts`: The options used for the comparison.
+/// - Returns: The computed distances and lengths, or an error if the reference track has no points.
+pub fn compare_tracks(
+    reference: &Track,
+    track: &Track,
+    opts: &CompareOptions,
+) -> Result<TrackComparison, EmptyReferenceError> {
+    check_reference(reference)?;
+    let projector = track_projection(reference, track, opts.projection_origin);
+    compare_tracks_with_projection(reference, track, opts, &projector)
+}
+
+/// Factor by which the Fréchet distance of the reversed track has to be smaller than the one of the track, so the track counts as reversed.
+/// The margin keeps tracks whose direction can not be told apart (e.g. a short out-and-back) in their recorded direction.
+pub const AUTO_ORIENT_THRESHOLD: f64 = 0.9;
+
+/// Function to compare a track to the reference path using an already created projection.
+/// When comparing many tracks to the same reference path, the projection only has to be created once,
+/// which also guarantees that all tracks are projected around the same origin.
+/// If [`CompareOptions::reference_segments`] is [`ReferenceSegments::BestMatch`], the track is compared to every non-empty segment
+/// of the reference path and the comparison with the smallest average distance is returned.
+/// If [`CompareOptions::auto_orient`] is set, the track is additionally compared in reverse, and the reversed comparison is used
+/// if its Fréchet distance is smaller than [`AUTO_ORIENT_THRESHOLD`] times the one of the track.
+/// - `reference`: The reference track.
+/// - `track`: The track to compare to the reference track.
+/// - `opts`: The options used for the comparison.
+/// - `projector`: The flat coordinate system used for projection, usually created with [`reference_projection`].
+/// - Returns: The computed distances and lengths, or an error if the reference track has no points (the projection around it is not usable then).
+pub fn compare_tracks_with_projection(
+    reference: &Track,
+    track: &Track,
+    opts: &CompareOptions,
+    projector: &FlatProjection<f64>,
+) -> Result<TrackComparison, EmptyReferenceError> {
+    check_reference(reference)?;
+    if opts.reference_segments == ReferenceSegments::Join {
+        return Ok(compare_track_orientations(
+            reference, track, opts, projector,
+        ));
+    }
+
+    // Averages which are not available (NaN) never match best
+    let average = |comparison: &TrackComparison| {
+        Some(comparison.average_distance_m).filter(|average| !average.is_nan())
+    };
+    Ok(split_segments(reference)
+        .iter()
+        .enumerate()
+        .filter(|(_, segment)| !segment.segments[0].points.is_empty())
+        .map(|(index, segment)| TrackComparison {
+            reference_segment_index: Some(index + 1),
+            ..compare_track_orientations(segment, track, opts, projector)
+        })
+        .inspect(|comparison| {
+            log::debug!(
+                "Reference segment {:?}: average distance {}",
+                comparison.reference_segment_index,
+                comparison.average_distance_m
+            )
+        })
+        .reduce(
+            |best, comparison| match (average(&best), average(&comparison)) {
+                (None, Some(_)) => comparison,
+                (Some(best_average), Some(average)) if average < best_average => comparison,
+                _ => best,
+            },
+        )
+        .expect("The reference path has points, so it has a non-empty segment"))
+}
+
+/// Function to compare a track to the reference path in the direction which matches it best, see [`compare_tracks_with_projection`].
+/// - `reference`: The reference track.
+/// - `track`: The track to compare to the reference track.
+/// - `opts`: The options used for the comparison.
+/// - `projector`: The flat coordinate system used for projection.
+/// - Returns: The computed distances and lengths.
+fn compare_track_orientations(
+    reference: &Track,
+    track: &Track,
+    opts: &CompareOptions,
+    projector: &FlatProjection<f64>,
+) -> TrackComparison {
+    let comparison = compare_oriented_track(reference, track, opts, projector, false);
+    if !opts.auto_orient {
+        return comparison;
+    }
+
+    let reversed = compare_oriented_track(reference, track, opts, projector, true);
+    log::debug!(
+        "Fréchet distance forward: {:?}, reversed: {:?}",
+        comparison.frechet_distance_m,
+        reversed.frechet_distance_m
+    );
+    match (comparison.frechet_distance_m, reversed.frechet_distance_m) {
+        (Some(frechet), Some(reversed_frechet))
+            if reversed_frechet < frechet * AUTO_ORIENT_THRESHOLD =>
+        {
+            // The point indices and geometries are mapped back to the original order of the points
+            let point_count = reversed.point_count + reversed.indeterminate_points;
+            TrackComparison {
+                reversed: true,
+                max_distance_point_index: reversed
+                    .max_distance_point_index
+                    .map(|index| point_count - 1 - index),
+                simplified_track: reversed.simplified_track.0.iter().rev().copied().collect(),
+                nearest_points: reversed
+                    .nearest_points
+                    .iter()
+                    .rev()
+                    .map(|pair| NearestPoint {
+                        cumulative_arc_m: reversed.current_track_length_m - pair.cumulative_arc_m,
+                        ..*pair
+                    })
+                    .collect(),
+                ..reversed
+            }
+        }
+        _ => comparison,
+    }
+}
+
+/// Function to compare a track in its given direction to the reference path, see [`compare_tracks_with_projection`].
+/// - `reference`: The reference track.
+/// - `track`: The track to compare to the reference track.
+/// - `opts`: The options used for the comparison.
+/// - `projector`: The flat coordinate system used for projection.
+/// - `reversed`: Whether the track is compared in reverse. It is reversed after the trimming, and the speeds, the ascent and descent,
+///   the distance windows and the start and end offsets still refer to the recorded order of its points.
+/// - Returns: The computed distances and lengths.
+fn compare_oriented_track(
+    reference: &Track,
+    track: &Track,
+    opts: &CompareOptions,
+    projector: &FlatProjection<f64>,
+    reversed: bool,
+) -> TrackComparison {
+    // Tracks may contain multiple segments, we however assume that there is only one segment, thus in
+    // Files with multiple segments, we combine them into a single LineString which is then used for the calculations
+
+    // Additionally, GPS tracks are stored in LatLon coordinates, which are not suitable for distance calculations
+    // as the distance in meter varies depending on the latitude (1° latitude ranges from ~111 km at the equator to 0 km at the poles)
+    // To solve this, we project the coordinates to a flat coordinate system
+    // This is "very precise" for distances of up to about 500km
+    // In the geodesic mode the projection is only used to select the points of the simplified track, all distances are instead computed on the sphere
+    let distance_mode = opts.distance_mode;
+    let point_distance = |a: &Point, b: &Point| distance_mode.distance(a, b);
+
+    let mut timings = ComparisonTimings::default();
+    let preprocessing_start = Instant::now();
+
+    // If requested, the start and end of both tracks are dropped, e.g. to ignore the points recorded while waiting for a GPS fix
+    let trimmed_tracks;
+    let (reference, track) = match (opts.start_trim, opts.end_trim) {
+        (None, None) => (reference, track),
+        (start, end) => {
+            trimmed_tracks = (
+                trim_track(reference, start, end),
+                trim_track(track, start, end),
+            );
+            (&trimmed_tracks.0, &trimmed_tracks.1)
+        }
+    };
+
+    // The speeds are calculated from the recorded points, as the smoothed and resampled points may have no timestamps
+    let time_statistics = opts
+        .time_stats
+        .then(|| time_statistics(track, projector))
+        .flatten();
+    // The timestamps are also only known for the recorded points
+    let time_matched_distance = opts
+        .match_by_time
+        .then(|| time_matched_distance(reference, track, projector))
+        .flatten();
+
+    // The speeds need increasing timestamps, so the track is only reversed after they were calculated
+    let reversed_track;
+    let track = if reversed {
+        reversed_track = reverse_track(track);
+        &reversed_track
+    } else {
+        track
+    };
+
+    // Like the speeds, the elevations are compared on the recorded points, as the smoothing and resampling may drop them
+    // Reversing the track swaps its ascent and descent, so they are swapped back to the recorded order
+    let elevation_profile = opts
+        .elevation_profile
+        .then(|| elevation_profile(reference, track))
+        .flatten()
+        .map(|profile| match reversed {
+            true => ElevationProfile {
+                track_ascent_m: profile.track_descent_m,
+                track_descent_m: profile.track_ascent_m,
+                ..profile
+            },
+            false => profile,
+        });
+
+    // If requested, the systematic offset of the compared track is removed, so only the random error remains
+    let removed_offset = match (opts.track_offset, opts.auto_offset) {
+        (None, false) => None,
+        (offset, false) => offset,
+        (offset, true) => Some(estimate_offset(
+            reference,
+            track,
+            -offset.unwrap_or_default(),
+            projector,
+        ))
+        .map(|translation| -translation),
+    };
+    let offset_corrected_track;
+    let track = match removed_offset {
+        Some(offset) => {
+            offset_corrected_track = offset_track(track, -offset, projector);
+            &offset_corrected_track
+        }
+        None => track,
+    };
+
+    // If requested, the jitter of the compared track is smoothed out first, the reference path is assumed to be accurate
+    let smoothed_track;
+    let track = match opts.smoothing {
+        SmoothingMethod::None => track,
+        method => {
+            smoothed_track = smooth_track(track, method, opts.smooth_window, projector);
+            &smoothed_track
+        }
+    };
+
+    // If requested, both tracks are resampled to evenly spaced points, so the point-wise metrics do not depend on the sampling rate
+    let resampled_tracks;
+    let (reference, track) = match opts.resample {
+        Some(spacing) => {
+            resampled_tracks = (
+                resample_track(reference, spacing, projector),
+                resample_track(track, spacing, projector),
+            );
+            (&resampled_tracks.0, &resampled_tracks.1)
+        }
+        None => (reference, track),
+    };
+    timings.preprocessing = preprocessing_start.elapsed();
+    let projection_start = Instant::now();
+
+    // All distances are converted to meters
+    // Computing in kilometers in the flat mode does not lose precision for small tracks, as floating point numbers keep their relative precision at any scale
+    let unit = distance_mode.unit_in_meters();
+
+    // Use the function to join and project the current track, which is needed for the simplification in both modes
+    let projected_current_linestring = join_and_project_segments(&track.segments, projector);
+
+    // In the flat mode, the distances are computed either in the flat projection or in the UTM zone of the reference path, both in kilometers
+    let utm = (distance_mode == DistanceMode::Flat && opts.projection == Projection::Utm)
+        .then(|| UtmProjection::for_point(reference_origin(reference)));
+    let project_utm = |utm: &UtmProjection, segments: &[TrackSegment]| -> LineString {
+        join_segments(segments)
+            .points()
+            .map(|point| {
+                let projected = utm.project(point);
+                Coord {
+                    x: projected.x() / 1000.0,
+                    y: projected.y() / 1000.0,
+                }
+            })
+            .collect()
+    };
+
+    // Get the LineStrings on which the distances are computed, projected for the flat mode and in LatLon coordinates for the geodesic mode
+    let (joined_current_linestring, joined_reference_linestring) = match (distance_mode, utm) {
+        (DistanceMode::Flat, Some(utm)) => {
+            log::debug!("Distances computed in UTM zone {}", utm.zone);
+            (
+                project_utm(&utm, &track.segments),
+                project_utm(&utm, &reference.segments),
+            )
+        }
+        (DistanceMode::Flat, None) => (
+            projected_current_linestring.clone(),
+            join_and_project_segments(&reference.segments, projector),
+        ),
+        (DistanceMode::Geodesic, _) => (
+            join_segments(&track.segments),
+            join_segments(&reference.segments),
+        ),
+    };
+
+    // The lines connecting two segments have no length if the gaps are excluded
+    let line_lengths = |linestring: &LineString, segments: &[TrackSegment]| -> Vec<f64> {
+        let gaps = if opts.exclude_segment_gaps {
+            segment_gaps(segments)
+        } else {
+            Vec::new()
+        };
+        linestring
+            .lines()
+            .enumerate()
+            .map(|(index, line)| {
+                if gaps.get(index).copied().unwrap_or(false) {
+                    0.0
+                } else {
+                    point_distance(&line.start_point(), &line.end_point())
+                }
+            })
+            .collect()
+    };
+    let current_line_lengths = line_lengths(&joined_current_linestring, &track.segments);
+
+    // Calculate the total length of the joined LineStrings for both the current and reference tracks.
+    // Folding from 0.0 instead of using `sum` avoids reporting tracks without lines as -0.0
+    let total_length = |lengths: &[f64]| lengths.iter().fold(0.0, |length, line| length + line);
+    let current_track_length = total_length(&current_line_lengths);
+    // Length of the compared track up to every one of its points
+    let cumulative_arcs: Vec<f64> = std::iter::once(0.0)
+        .chain(current_line_lengths.iter().scan(0.0, |length, line| {
+            *length += line;
+            Some(*length)
+        }))
+        .collect();
+    let reference_track_length = total_length(&line_lengths(
+        &joined_reference_linestring,
+        &reference.segments,
+    ));
+    timings.projection = projection_start.elapsed();
+
+    // If requested, the elevations (converted to the units of the distance mode) are included in the point distances
+    // The track lengths are always measured horizontally
+    let elevations = |segments: &[TrackSegment]| -> Vec<Option<f64>> {
+        if opts.use_elevation {
+            join_elevations(segments)
+                .into_iter()
+                .map(|elevation| elevation.map(|elevation| elevation / unit))
+                .collect()
+        } else {
+            vec![None; segments.iter().map(|segment| segment.points.len()).sum()]
+        }
+    };
+    let current_elevations = elevations(&track.segments);
+    let reference_elevations = elevations(&reference.segments);
+
+    let with_elevations = |linestring: &LineString, elevations: &[Option<f64>]| {
+        linestring
+            .points()
+            .zip(elevations.iter().copied())
+            .collect::<Vec<_>>()
+    };
+    let current_points = with_elevations(&joined_current_linestring, &current_elevations);
+    let reference_points = with_elevations(&joined_reference_linestring, &reference_elevations);
+
+    // Distance between two points, falling back to the horizontal distance if one of them has no elevation
+    let point_distance_3d = |(a, a_elevation): &(Point, Option<f64>),
+                             (b, b_elevation): &(Point, Option<f64>)| {
+        let horizontal = point_distance(a, b);
+        match (a_elevation, b_elevation) {
+            (Some(a_elevation), Some(b_elevation)) => horizontal.hypot(a_elevation - b_elevation),
+            _ => horizontal,
+        }
+    };
+
+    // Horizontal distance between the ends of both tracks in meters, e.g. to check the start and finish of a loop
+    // The first and last recorded points are the last and first points of a reversed track
+    // As a reversed track runs the reference path backwards, it starts at the end of the reference path
+    let (recorded_points, reference_ends) = match reversed {
+        false => (
+            (current_points.first(), current_points.last()),
+            (reference_points.first(), reference_points.last()),
+        ),
+        true => (
+            (current_points.last(), current_points.first()),
+            (reference_points.last(), reference_points.first()),
+        ),
+    };
+    let endpoint_distance = |current: Option<&(Point, Option<f64>)>,
+                             reference: Option<&(Point, Option<f64>)>| {
+        Some(point_distance(&current?.0, &reference?.0) * unit)
+    };
+
+    // The curve metrics compare the shape of both tracks, which requires both of them to consist of at least one line
+    let is_curve = current_points.len() >= 2 && reference_points.len() >= 2;
+
+    // The runtime of the curve metrics grows with the product of the number of points of both tracks, so very long tracks are downsampled for them
+    let downsampled_points;
+    let (curve_current_points, curve_reference_points) = match opts.max_points {
+        Some(max_points) => {
+            downsampled_points = (
+                metrics::downsample(&current_points, max_points),
+                metrics::downsample(&reference_points, max_points),
+            );
+            log::debug!(
+                "Downsampled for the curve metrics: {} of {} points, reference path: {} of {} points",
+                downsampled_points.0.len(),
+                current_points.len(),
+                downsampled_points.1.len(),
+                reference_points.len()
+            );
+            (&downsampled_points.0, &downsampled_points.1)
+        }
+        None => (&current_points, &reference_points),
+    };
+
+    // Calculate the frechet distance, together with the pair of points with the longest leash of the optimal coupling
+    let (frechet_coupling, frechet_time) = timed(|| {
+        is_curve
+            .then(|| {
+                metrics::frechet_distance_with_pair(
+                    curve_current_points,
+                    curve_reference_points,
+                    point_distance_3d,
+                )
+            })
+            .flatten()
+    });
+    let frechet_distance = frechet_coupling.map(|(distance, _)| distance);
+    timings.frechet = frechet_time;
+
+    // Calculate the hausdorff distance, as the larger of both directed distances so it is known which track has the outlier
+    let (directed_hausdorff, hausdorff_time) = timed(|| {
+        is_curve.then(|| {
+            (
+                metrics::directed_hausdorff_distance(
+                    curve_current_points,
+                    curve_reference_points,
+                    point_distance_3d,
+                ),
+                metrics::directed_hausdorff_distance(
+                    curve_reference_points,
+                    curve_current_points,
+                    point_distance_3d,
+                ),
+            )
+        })
+    });
+    timings.hausdorff = hausdorff_time;
+
+    // Calculate the dynamic time warping distance
+    let (dtw_distance, dtw_time) = timed(|| {
+        is_curve.then(|| {
+            metrics::dtw_distance_with(
+                curve_current_points,
+                curve_reference_points,
+                opts.dtw_window,
+                point_distance_3d,
+            )
+        })
+    });
+    timings.dtw = dtw_time;
+
+    let mut distances = DistanceAccumulator::default();
+
+    // Next we want to compute the "average" and the "simplified average" distance between the reference path and the current track
+    // The average distance is computed by taking every point of the current track and finding the closest point on the reference path, then calculating the distance between them and summing them up divided by the total number of points
+    // For the simplified average distance, we first simplify the current track using the Ramer-Douglas-Peucker algorithm and then do the same as for the average distance
+    // Simplifying the current track (and not the reference path) is intentional: points recorded while standing still collapse into a single point,
+    // so the simplified average only depends on the shape of the current track and not on how long was spent at each location
+
+    // The closest points are searched using an index over the reference segments, which is built only once for all averages
+    let (reference_index, index_time) = timed(|| {
+        ReferenceIndex::with_elevations(
+            &joined_reference_linestring,
+            distance_mode,
+            reference_elevations,
+        )
+    });
+    timings.index = index_time;
+
+    // Function to simplify the current track with the given epsilon (in meters, or square meters for Visvalingam-Whyatt) and calculate the distances of the retained points
+    // The simplification always happens in the projected space where the epsilon is given in kilometers (or square kilometers), the retained points are then taken from the LineString used for the distances
+    let simplified_average = |epsilon: f64| -> (Vec<usize>, DistanceAccumulator) {
+        // Tracks with less than three points can not be simplified any further (and the simplification panics on a single point)
+        let simplified_indices = if projected_current_linestring.0.len() < 3 {
+            (0..projected_current_linestring.0.len()).collect()
+        } else {
+            match opts.simplify_algorithm {
+                SimplifyAlgorithm::Rdp => {
+                    projected_current_linestring.simplify_idx(&(epsilon / 1000.0))
+                }
+                SimplifyAlgorithm::Visvalingam => {
+                    projected_current_linestring.simplify_vw_idx(&(epsilon / 1_000_000.0))
+                }
+            }
+        };
+        let simplified_linestring: LineString = simplified_indices
+            .iter()
+            .map(|&index| joined_current_linestring[index])
+            .collect();
+        let simplified_elevations: Vec<Option<f64>> = simplified_indices
+            .iter()
+            .map(|&index| current_elevations[index])
+            .collect();
+
+        let mut distances_simplified = DistanceAccumulator::default();
+        calculate_average_distance(
+            &simplified_linestring,
+            Some(&simplified_elevations),
+            &reference_index,
+            &mut distances_simplified,
+            None,
+        );
+        (simplified_indices, distances_simplified)
+    };
+
+    // Calculate the average distance between the current and reference tracks
+    let average_start = Instant::now();
+    let mut nearest_points: Vec<(Line, f64)> = Vec::new();
+    calculate_average_distance(
+        &joined_current_linestring,
+        Some(&current_elevations),
+        &reference_index,
+        &mut distances,
+        Some(&mut nearest_points),
+    );
+    timings.average = average_start.elapsed();
+
+    // Calculate the average distance between the simplified current and reference tracks
+    let simplification_start = Instant::now();
+    let (simplified_indices, distances_simplified) = simplified_average(opts.simplify_epsilon);
+
+    // Repeat the simplified average for every epsilon of the sweep
+    let epsilon_sweep = opts
+        .epsilon_sweep
+        .iter()
+        .map(|&epsilon| {
+            let (_, distances) = simplified_average(epsilon);
+            EpsilonSweepEntry {
+                epsilon_m: epsilon,
+                simplified_average_distance_m: distances.average() * unit,
+                simplified_point_count: distances.points,
+            }
+        })
+        .collect();
+    timings.simplification = simplification_start.elapsed();
+
+    // The area between both tracks is computed in the flat projection in both modes, from the pairs of points and their closest points
+    // If not every point has a closest point (the reference path has no segments), there is no area
+    let area_start = Instant::now();
+    let area_between = if is_curve && nearest_points.len() == joined_current_linestring.0.len() {
+        let project = |point: Point| match distance_mode {
+            DistanceMode::Flat => point,
+            DistanceMode::Geodesic => {
+                let projected = project_point(projector, point);
+                Point::new(projected.x, projected.y)
+            }
+        };
+        let (points, closest_points): (Vec<Point>, Vec<Point>) = nearest_points
+            .iter()
+            .map(|(line, _)| (project(line.start_point()), project(line.end_point())))
+            .unzip();
+        // The flat projection is in kilometers, so the area is in square kilometers
+        Some(metrics::area_between(&points, &closest_points) * 1_000_000.0)
+    } else {
+        None
+    };
+    timings.area = area_start.elapsed();
+
+    // For the symmetric average, the closest points of the reference points are searched on the compared track
+    let average_reverse = (opts.symmetric && current_points.len() >= 2).then(|| {
+        let current_index = ReferenceIndex::with_elevations(
+            &joined_current_linestring,
+            distance_mode,
+            current_elevations.clone(),
+        );
+        let reference_elevations: Vec<Option<f64>> = reference_points
+            .iter()
+            .map(|(_, elevation)| *elevation)
+            .collect();
+        let mut distances_reverse = DistanceAccumulator::default();
+        calculate_average_distance(
+            &joined_reference_linestring,
+            Some(&reference_elevations),
+            &current_index,
+            &mut distances_reverse,
+            None,
+        );
+        distances_reverse.average()
+    });
+
+    // Percentiles of the point distances are less sensitive to outliers than the average
+    let sorted_distances = distances.sorted_distances();
+
+    // Check which points are within the tolerance, and find the longest run of points outside of it
+    let tolerance = opts.tolerance.map(|tolerance| {
+        let within = distances
+            .distances
+            .iter()
+            .filter(|&&distance| distance * unit <= tolerance)
+            .count();
+
+        // Runs are tracked by the index of their first point, the length of a run is measured along the lines between its points
+        let (mut longest_points, mut longest_length) = (0, 0.0);
+        let mut run_start = None;
+        for (index, &distance) in distances.distances.iter().enumerate() {
+            if distance * unit <= tolerance {
+                run_start = None;
+                continue;
+            }
+            let start = *run_start.get_or_insert(index);
+            let points = index - start + 1;
+            if points > longest_points {
+                longest_points = points;
+                longest_length = current_line_lengths[start..index]
+                    .iter()
+                    .fold(0.0, |length, line_length| length + line_length);
+            }
+        }
+
+        ToleranceStatistics {
+            tolerance_m: tolerance,
+            within_tolerance_fraction: within as f64 / distances.points as f64,
+            longest_out_of_tolerance_points: longest_points,
+            longest_out_of_tolerance_m: longest_length * unit,
+        }
+    });
+
+    // The lines of the compared track are sampled in short steps, to measure which part of their length lies within half the corridor width of the reference path
+    // The distances are horizontal, the corridor does not have a height
+    let corridor = opts.corridor_width.map(|width| {
+        let half_width = width / 2.0 / unit;
+        let step = half_width / 4.0;
+        let (mut inside, mut outside, mut outside_area, mut indeterminate) = (0.0, 0.0, 0.0, 0.0);
+        for (line, &length) in joined_current_linestring
+            .lines()
+            .zip(&current_line_lengths)
+            .filter(|(_, &length)| length > 0.0)
+        {
+            let samples = ((length / step).ceil() as usize).clamp(1, MAX_CORRIDOR_SAMPLES_PER_LINE);
+            let sample_length = length / samples as f64;
+            for sample in 0..samples {
+                // Every sample represents the part of the line around its center
+                let fraction = (sample as f64 + 0.5) / samples as f64;
+                let point = Point::from(line.start + line.delta() * fraction);
+                let distance = match reference_index.closest_point(&point) {
+                    Closest::Intersection(p) | Closest::SinglePoint(p) => {
+                        distance_mode.distance(&p, &point)
+                    }
+                    Closest::Indeterminate => {
+                        indeterminate += sample_length;
+                        continue;
+                    }
+                };
+                if distance <= half_width {
+                    inside += sample_length;
+                } else {
+                    outside += sample_length;
+                    outside_area += (distance - half_width) * sample_length;
+                }
+            }
+        }
+        CorridorStatistics {
+            width_m: width,
+            inside_length_fraction: inside / (inside + outside),
+            outside_length_m: outside * unit,
+            outside_area_m2: outside_area * unit * unit,
+            indeterminate_length_m: indeterminate * unit,
+        }
+    });
+
+    // Profile of the distances along the compared track, in consecutive windows of its length
+    let distance_windows = opts.window_report.map_or_else(Vec::new, |window| {
+        let mut position = 0.0;
+        let positions: Vec<f64> = std::iter::once(0.0)
+            .chain(current_line_lengths.iter().map(|line_length| {
+                position += line_length * unit;
+                position
+            }))
+            .collect();
+        let point_distances: Vec<f64> = distances
+            .distances
+            .iter()
+            .map(|distance| distance * unit)
+            .collect();
+        if !reversed {
+            return crate::distance_windows(&positions, &point_distances, window);
+        }
+        // The windows of a reversed track start at the first recorded point, like for all other tracks
+        let track_length = positions.last().copied().unwrap_or(0.0);
+        crate::distance_windows(
+            &positions
+                .iter()
+                .rev()
+                .map(|position| track_length - position)
+                .collect::<Vec<f64>>(),
+            &point_distances.into_iter().rev().collect::<Vec<f64>>(),
+            window,
+        )
+    });
+
+    // Geometries are converted back to LatLon coordinates so they can be reexported
+    let joined_current_latlon = join_segments(&track.segments);
+    let point_to_latlon = |point: Point| match (distance_mode, utm) {
+        (DistanceMode::Flat, Some(utm)) => {
+            utm.unproject(Point::new(point.x() * 1000.0, point.y() * 1000.0))
+        }
+        (DistanceMode::Flat, None) => unproject_point(
+            projector,
+            &FlatPoint {
+                x: point.x(),
+                y: point.y(),
+            },
+        ),
+        (DistanceMode::Geodesic, _) => point,
+    };
+    let to_latlon = |linestring: &LineString| match (distance_mode, utm) {
+        (DistanceMode::Flat, None) => unproject_linestring(linestring, projector),
+        (DistanceMode::Flat, Some(_)) => linestring.points().map(point_to_latlon).collect(),
+        (DistanceMode::Geodesic, _) => linestring.clone(),
+    };
+
+    TrackComparison {
+        current_track_length_m: current_track_length * unit,
+        reference_track_length_m: reference_track_length * unit,
+        average_distance_m: distances.average() * unit,
+        simplified_average_distance_m: distances_simplified.average() * unit,
+        rms_distance_m: distances.rms() * unit,
+        average_distance_reverse_m: average_reverse.map(|average| average * unit),
+        average_distance_symmetric_m: average_reverse
+            .map(|average| (distances.average() + average) / 2.0 * unit),
+        weighted_average_distance_m: opts
+            .weight_by_hdop
+            .then(|| distances.weighted_average(&hdop_weights(&track.segments)) * unit),
+        median_distance_m: percentile(&sorted_distances, 50.0) * unit,
+        p90_distance_m: percentile(&sorted_distances, 90.0) * unit,
+        p95_distance_m: percentile(&sorted_distances, 95.0) * unit,
+        max_distance_m: distances.max_distance * unit,
+        max_distance_point_index: distances.max_distance_index,
+        max_distance_reference_point: distances.max_distance_reference_point.map(point_to_latlon),
+        start_offset_m: endpoint_distance(recorded_points.0, reference_ends.0),
+        end_offset_m: endpoint_distance(recorded_points.1, reference_ends.1),
+        start_to_end_offset_m: endpoint_distance(recorded_points.0, reference_ends.1),
+        indeterminate_points: distances.indeterminate_points,
+        mean_signed_offset_m: distances.mean_signed_offset() * unit,
+        fraction_right: distances.fraction_right(),
+        mean_cross_track_m: distances.mean_track_error().0 * unit,
+        mean_along_track_m: distances.mean_track_error().1 * unit,
+        frechet_distance_m: frechet_distance.map(|distance| distance * unit),
+        frechet_normalized: frechet_distance
+            .filter(|_| reference_track_length > 0.0)
+            .map(|distance| distance / reference_track_length),
+        frechet_track_point: frechet_coupling
+            .map(|(_, (track_index, _))| point_to_latlon(curve_current_points[track_index].0)),
+        frechet_reference_point: frechet_coupling.map(|(_, (_, reference_index))| {
+            point_to_latlon(curve_reference_points[reference_index].0)
+        }),
+        hausdorff_distance_m: directed_hausdorff.map(|(track_to_reference, reference_to_track)| {
+            track_to_reference.max(reference_to_track) * unit
+        }),
+        hausdorff_track_to_reference_m: directed_hausdorff
+            .map(|(track_to_reference, _)| track_to_reference * unit),
+        hausdorff_reference_to_track_m: directed_hausdorff
+            .map(|(_, reference_to_track)| reference_to_track * unit),
+        dtw_distance_m: dtw_distance.map(|distance| distance * unit),
+        match_score: match_score(
+            frechet_distance.map(|distance| distance * unit),
+            directed_hausdorff.map(|(track_to_reference, reference_to_track)| {
+                track_to_reference.max(reference_to_track) * unit
+            }),
+            Some(distances.average() * unit),
+            &opts.score_weights,
+        ),
+        area_between_m2: area_between,
+        tolerance,
+        corridor,
+        reversed: false,
+        reference_segment_index: None,
+        track_offset: removed_offset,
+        time_statistics,
+        elevation_profile,
+        time_matched_distance,
+        distance_windows,
+        point_count: distances.points,
+        reference_point_count: reference_points.len(),
+        simplified_point_count: distances_simplified.points,
+        epsilon_sweep,
+        // The retained points are taken from the original coordinates instead of unprojecting them,
+        // so they are identical to the coordinates of their waypoints, see [`export::simplified_waypoints`]
+        simplified_track: simplified_indices
+            .iter()
+            .map(|&index| joined_current_latlon[index])
+            .collect(),
+        // Without a closest point for every point there are no pairs at all, so the pairs and the points of the track line up
+        nearest_points: nearest_points
+            .iter()
+            .zip(join_elevations(&track.segments))
+            .zip(cumulative_arcs)
+            .filter(|_| opts.collect_nearest_points)
+            .map(|(((line, distance), elevation), cumulative_arc)| {
+                let latlon = to_latlon(&LineString::from(*line));
+                NearestPoint {
+                    line: Line::new(latlon[0], latlon[1]),
+                    distance_m: distance * unit,
+                    elevation,
+                    cumulative_arc_m: cumulative_arc * unit,
+                }
+            })
+            .collect(),
+        timings,
+    }
+}
@@ -1,441 +1,4551 @@
-use std::fs::File;
-use std::io::BufReader;
-use std::path::PathBuf;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
-use clap::{arg, Parser};
-use colored::Colorize;
-use flat_projection::{FlatPoint, FlatProjection};
-use geo::{Closest, ClosestPoint, Coord, LineString, Point, Simplify};
-use geo::{EuclideanDistance, FrechetDistance, HausdorffDistance};
-use gpx::{read, write, Waypoint};
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgMatches, Command, CommandFactory, Parser};
+use colored::{Color, ColoredString, Colorize};
+use flat_projection::FlatProjection;
+use geo::{HaversineLength, LineString};
+use gps_path_average_distance::config::{parse_config, ConfigValue};
+use gps_path_average_distance::export::{
+    add_metrics_extensions, geojson_feature_collection, geojson_linestring_feature,
+    simplified_waypoints, wkt_linestring, ExportedMetrics,
+};
+use gps_path_average_distance::input::{
+    input_file_stem, is_gzip_path, read_file, read_inline, read_reader, stream_file, stream_reader,
+};
+use gps_path_average_distance::manifest::{parse_manifest, run_job, ManifestJob};
+use gps_path_average_distance::output::{
+    round_to_precision, CheckpointOutput, CheckpointsOutput, ComparisonOutput, DirectoryPairOutput,
+    FileListingOutput, LatLonOutput, MultiReferenceOutput, ReferenceHeaderOutput,
+    ReportSummaryOutput, StreamingOutput, TrackListingOutput, ValidationOutput,
+};
+use gps_path_average_distance::serve::serve;
+use gps_path_average_distance::{
+    bounding_boxes_overlap, check_reference, checkpoint_distances, clip_track,
+    compare_tracks_with_projection, exclude_reference_track, join_segments, load_reference_index,
+    locate_section, max_distance_from_origin, projection_origin, reference_origin,
+    reference_projection, route_track, save_reference_index, select_track, smooth_track,
+    split_segments, track_projection, validate_file, validate_track, CompareOptions,
+    ComparisonTimings, DistanceMode, HausdorffDirection, Projection, ProjectionOrigin,
+    ReferenceIndexError, ReferenceSegments, ScoreWeights, Section, SimplifyAlgorithm,
+    SmoothingMethod, StreamingComparison, TrackComparison, TrackOffset, TrackSelector, Trim, Units,
+    UtmProjection, HALF_SCORE_DISTANCE_M, UTM_HALF_ZONE_WIDTH,
+};
+use gpx::write;
 use gpx::{Gpx, Track, TrackSegment};
+use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
+use serde::Serialize;
+
+/// Function to create a value parser which only accepts the given values and parses them into `T`.
+/// - `values`: The accepted values, each of them has to be parsable into `T`.
+/// - Returns: The value parser, which also lists the accepted values in the help text.
+fn choice<T>(values: &'static [&'static str]) -> impl TypedValueParser<Value = T>
+where
+    T: FromStr + Clone + Send + Sync + 'static,
+    T::Err: fmt::Debug,
+{
+    PossibleValuesParser::new(values.iter().copied()).map(|value| {
+        value
+            .parse::<T>()
+            .expect("All possible values have to be parsable")
+    })
+}
+
+/// Function to parse a distance which has to be positive, e.g. a spacing between points.
+/// - `value`: The value given on the command line.
+/// - Returns: The parsed distance or an error message.
+fn positive_distance(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(distance) if distance > 0.0 && distance.is_finite() => Ok(distance),
+        Ok(_) => Err("the distance has to be a positive number".to_string()),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+/// Function to parse a distance which may also be zero, e.g. a margin.
+/// - `value`: The value given on the command line.
+/// - Returns: The parsed distance or an error message.
+fn non_negative_distance(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(distance) if distance >= 0.0 && distance.is_finite() => Ok(distance),
+        Ok(_) => Err("the distance can not be negative".to_string()),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+/// Function to parse the window of the smoothing, which has to be odd so it is centered on the smoothed point.
+/// - `value`: The value given on the command line.
+/// - Returns: The parsed window or an error message.
+fn smooth_window(value: &str) -> Result<usize, String> {
+    match value.trim().parse::<usize>() {
+        Ok(window) if window >= 3 && window % 2 == 1 => Ok(window),
+        Ok(_) => Err("the window has to be an odd number of at least 3".to_string()),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+/// Function to parse the maximum number of points used for the curve metrics.
+/// - `value`: The value given on the command line.
+/// - Returns: The parsed number of points or an error message.
+fn max_points(value: &str) -> Result<usize, String> {
+    match value.trim().parse::<usize>() {
+        Ok(points) if points >= 2 => Ok(points),
+        Ok(_) => {
+            Err("at least 2 points are needed to keep the first and the last point".to_string())
+        }
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+/// Function to parse the maximum ratio between the lengths of a track and the reference path.
+/// - `value`: The value given on the command line.
+/// - Returns: The parsed ratio or an error message.
+fn length_ratio(value: &str) -> Result<f64, String> {
+    match value.trim().trim_end_matches('x').parse::<f64>() {
+        Ok(ratio) if ratio >= 1.0 && ratio.is_finite() => Ok(ratio),
+        Ok(_) => Err("the ratio has to be a number of at least 1".to_string()),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+/// What to do if elevations should be used, but some points have no elevation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ElevationMissing {
+    /// Exit with an error
+    Error,
+    /// Use the horizontal distance for the affected points
+    Ignore,
+}
+
+impl FromStr for ElevationMissing {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "error" => Ok(ElevationMissing::Error),
+            "ignore" => Ok(ElevationMissing::Ignore),
+            _ => Err(format!("unknown elevation handling {:?}", value)),
+        }
+    }
+}
+
+/// How tracks (and reference paths) with multiple segments are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SegmentMode {
+    /// Join all segments into a single line
+    Join,
+    /// Only use the first segment which contains points
+    First,
+    /// Exit with an error
+    Error,
+}
+
+impl FromStr for SegmentMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "join" => Ok(SegmentMode::Join),
+            "first" => Ok(SegmentMode::First),
+            "error" => Ok(SegmentMode::Error),
+            _ => Err(format!("unknown segment mode {:?}", value)),
+        }
+    }
+}
+
+/// Metric by which the compared tracks are ordered in the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortKey {
+    Frechet,
+    Hausdorff,
+    Average,
+    /// The length of the compared track
+    Length,
+    /// The name of the track, unnamed tracks are sorted last
+    Name,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "frechet" => Ok(SortKey::Frechet),
+            "hausdorff" => Ok(SortKey::Hausdorff),
+            "average" => Ok(SortKey::Average),
+            "length" => Ok(SortKey::Length),
+            "name" => Ok(SortKey::Name),
+            _ => Err(format!("unknown sort key {:?}", value)),
+        }
+    }
+}
+
+impl SortKey {
+    /// Function to get the metric of a comparison which is sorted by.
+    /// - `comparison`: The comparison of a track.
+    /// - Returns: The metric, `None` if it is not available or the key is the name.
+    fn value(&self, comparison: &TrackComparison) -> Option<f64> {
+        match self {
+            SortKey::Frechet => comparison.frechet_distance_m,
+            SortKey::Hausdorff => comparison.hausdorff_distance_m,
+            SortKey::Average => Some(comparison.average_distance_m),
+            SortKey::Length => Some(comparison.current_track_length_m),
+            SortKey::Name => None,
+        }
+        .filter(|value| !value.is_nan())
+    }
+
+    /// Function to compare two tracks by the key, in ascending order.
+    /// Tracks without a value (e.g. the Fréchet distance of a single point track or an unnamed track) are always sorted last, also in reverse.
+    /// - `a`, `b`: The compared tracks and their comparisons.
+    /// - `reverse`: Whether the tracks are sorted in descending order.
+    /// - Returns: The ordering of both tracks.
+    fn compare(
+        &self,
+        (a, a_comparison): (&Track, &TrackComparison),
+        (b, b_comparison): (&Track, &TrackComparison),
+        reverse: bool,
+    ) -> Ordering {
+        fn missing_last<T>(
+            a: Option<T>,
+            b: Option<T>,
+            reverse: bool,
+            compare: impl Fn(&T, &T) -> Ordering,
+        ) -> Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) if reverse => compare(&b, &a),
+                (Some(a), Some(b)) => compare(&a, &b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        }
+        match self {
+            SortKey::Name => {
+                missing_last(a.name.as_ref(), b.name.as_ref(), reverse, |a, b| a.cmp(b))
+            }
+            _ => missing_last(
+                self.value(a_comparison),
+                self.value(b_comparison),
+                reverse,
+                f64::total_cmp,
+            ),
+        }
+    }
+}
+
+/// Range of epsilons for the epsilon sweep, given as `start,end,step` in meters
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct EpsilonSweep {
+    start: f64,
+    end: f64,
+    step: f64,
+}
+
+impl EpsilonSweep {
+    /// Function to list all epsilons of the sweep.
+    /// - Returns: The epsilons from start to end (inclusive) in increments of step.
+    fn epsilons(&self) -> Vec<f64> {
+        // Counting the steps avoids accumulating rounding errors, the small tolerance keeps the end if it is hit exactly
+        let steps = ((self.end - self.start) / self.step + 1e-9).floor() as usize;
+        (0..=steps)
+            .map(|step| self.start + step as f64 * self.step)
+            .collect()
+    }
+}
+
+impl FromStr for EpsilonSweep {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let parts = value
+            .split(',')
+            .map(|part| part.trim().parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()
+            .map_err(|error| format!("invalid number: {}", error))?;
+
+        match parts[..] {
+            [start, end, step] => {
+                if !(start.is_finite() && end.is_finite() && step.is_finite()) {
+                    Err("all values have to be finite".to_string())
+                } else if start < 0.0 {
+                    Err("the start has to be at least 0".to_string())
+                } else if end < start {
+                    Err("the end has to be at least the start".to_string())
+                } else if step <= 0.0 {
+                    Err("the step has to be greater than 0".to_string())
+                } else {
+                    Ok(EpsilonSweep { start, end, step })
+                }
+            }
+            _ => Err("expected three values: start,end,step".to_string()),
+        }
+    }
+}
+
+/// Distances in meters up to which a distance of the human readable output is colored as good (green) or as warning (yellow), given as `good,warn`.
+/// Larger distances are colored red.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ColorThresholds {
+    good: f64,
+    warn: f64,
+}
+
+impl ColorThresholds {
+    /// Function to get the color of a distance.
+    /// - `distance`: The distance in meters.
+    /// - Returns: Green, yellow or red depending on the severity of the distance.
+    fn color(&self, distance: f64) -> Color {
+        if distance <= self.good {
+            Color::Green
+        } else if distance <= self.warn {
+            Color::Yellow
+        } else {
+            Color::Red
+        }
+    }
+}
+
+impl FromStr for ColorThresholds {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let parts = value
+            .split(',')
+            .map(|part| part.trim().parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()
+            .map_err(|error| format!("invalid number: {}", error))?;
+
+        match parts[..] {
+            [good, warn] => {
+                if !(good.is_finite() && warn.is_finite()) || good < 0.0 {
+                    Err("both distances have to be finite and at least 0".to_string())
+                } else if warn < good {
+                    Err("the warning distance has to be at least the good distance".to_string())
+                } else {
+                    Ok(ColorThresholds { good, warn })
+                }
+            }
+            _ => Err("expected two distances: good,warn".to_string()),
+        }
+    }
+}
+
+/// Function to check whether a track path refers to the standard input.
+/// - `path`: The track path given on the command line.
+/// - Returns: Whether the path is the `-` sentinel.
+fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+/// Function to count the points of a track which have no elevation.
+/// - `track`: The track to check.
+/// - Returns: The number of points without an elevation.
+fn count_missing_elevations(track: &Track) -> usize {
+    track
+        .segments
+        .iter()
+        .flat_map(|segment| &segment.points)
+        .filter(|point| point.elevation.is_none())
+        .count()
+}
 
 #[derive(Parser)]
 #[command(
     name = "gps-path-average-distance",
     version = "0.1.3",
     author = "NetroScript",
-    about = "This application compares a reference GPS path to other tracks by calculating four distances: point-wise average distance, simplified point-wise average distance, Fréchet distance, and Hausdorff distance."
+    about = "This application compares a reference GPS path to other tracks by calculating five distances: point-wise average distance, simplified point-wise average distance, Fréchet distance, Hausdorff distance, and Dynamic Time Warping distance."
 )]
 struct Cli {
-    /// File path to a .gpx file containing the reference path
-    #[arg(short, long, required = true)]
-    reference: PathBuf,
+    /// File path to a .gpx, .tcx, .kml, .fit or .geojson file containing the reference path. With --multi-reference, multiple paths can be given separated with a comma.
+    #[arg(
+        short,
+        long,
+        required_unless_present_any = ["serve", "reference_dir", "reference_inline", "reference_from_track", "manifest"],
+        value_delimiter = ',',
+        num_args = 1
+    )]
+    reference: Vec<PathBuf>,
+
+    /// Reference path given inline as semicolon separated list of lat,lon points (e.g. "48.1,11.5;48.2,11.6") instead of a file, e.g. for quick tests or straight-line references
+    #[arg(long, value_name = "LAT,LON;...", conflicts_with_all = ["reference", "multi_reference", "reference_track", "section", "list_tracks", "reference_dir", "serve"])]
+    reference_inline: Option<String>,
+
+    /// File path to save the parsed reference file to, so later runs against the same reference file can skip parsing it with --load-reference-index
+    #[arg(long, value_name = "PATH", requires = "reference", conflicts_with_all = ["multi_reference", "load_reference_index"])]
+    save_reference_index: Option<PathBuf>,
 
-    /// One to multiple file paths to a .gpx file containing a track to compare to the reference path. Separate multiple paths with a comma.
-    #[arg(short, long, required = true, value_delimiter = ',', num_args = 1)]
+    /// File path of an index saved with --save-reference-index, which is used instead of parsing the reference file. The reference file is still needed: if it changed since the index was saved, the index is rejected.
+    #[arg(long, value_name = "PATH", requires = "reference", conflicts_with_all = ["multi_reference", "list_tracks"])]
+    load_reference_index: Option<PathBuf>,
+
+    /// Toggle to compare every track to multiple reference paths (given with --reference) and report which of them matches best, i.e. has the smallest Fréchet distance
+    #[arg(long, conflicts_with_all = ["csv", "export_track", "export_smoothed", "export_geojson", "export_wkt", "report_dir", "max_average", "max_frechet"])]
+    multi_reference: bool,
+
+    /// Track of the reference file to use as the reference path, given either as 1-based index or as (case-insensitive) name. Defaults to the first track.
+    #[arg(long)]
+    reference_track: Option<TrackSelector>,
+
+    /// Track of the (first) track file to use as the reference path instead of a separate reference file, given either as 1-based index or as (case-insensitive) name, e.g. to compare the other tracks of a group recording to one of them. The selected track is not compared to itself, unless --keep-reference-track is given.
+    #[arg(long, value_name = "INDEX_OR_NAME", requires = "track", conflicts_with_all = ["reference", "reference_inline", "reference_track", "multi_reference", "reference_dir", "streaming", "serve", "manifest", "list_tracks"])]
+    reference_from_track: Option<TrackSelector>,
+
+    /// Toggle to also compare the track selected with --reference-from-track to itself, e.g. as a sanity check where all distances are zero
+    #[arg(long, requires = "reference_from_track")]
+    keep_reference_track: bool,
+
+    /// Named waypoints of the reference file (given as start:end) marking the section of the reference path to compare, e.g. a single leg of a long route. The reference path is clipped between the closest points to both waypoints, the compared track(s) are clipped to the same fractions of their length.
+    #[arg(long, value_name = "START:END", conflicts_with = "multi_reference")]
+    section: Option<Section>,
+
+    /// One to multiple file paths to a .gpx, .tcx, .kml, .fit or .geojson file containing a track to compare to the reference path. Separate multiple paths with a comma. Use - to read a track from the standard input.
+    #[arg(
+        short,
+        long,
+        required_unless_present_any = ["serve", "list_tracks", "track_dir", "manifest"],
+        value_delimiter = ',',
+        num_args = 1
+    )]
     track: Vec<PathBuf>,
 
+    /// Directory of reference files, which are compared to the files with the same name (without the extension) in --track-dir. A summary of all pairs is reported, files without a counterpart are listed as warnings.
+    #[arg(long, requires = "track_dir", conflicts_with_all = ["reference", "track", "multi_reference", "section", "serve", "list_tracks", "export_track", "export_smoothed", "export_geojson", "export_wkt", "report_dir", "debug_pairs", "export_point_distances", "max_average", "max_frechet", "per_segment"])]
+    reference_dir: Option<PathBuf>,
+
+    /// Directory of track files, which are compared to the files with the same name in --reference-dir
+    #[arg(long, requires = "reference_dir")]
+    track_dir: Option<PathBuf>,
+
     /// Turn debugging information on
     #[arg(short, long)]
     debug: bool,
 
-    /// Custom epsilon value to use for simplifying the reference path. This is the maximum distance between two points before they are simplified. The value is given in meters. Default is 1m.
+    /// Custom epsilon value to use for simplifying the compared track(s) before calculating the location dependent average distance. This is the maximum distance a point may deviate from the simplified path before it is kept. The value is given in meters (in square meters with --simplify-algo visvalingam). Default is 1m.
     #[arg(short, long, default_value = "1.0")]
     simplify_epsilon: f64,
 
+    /// Algorithm used for simplifying the compared track(s). "rdp" (Ramer-Douglas-Peucker) interprets --simplify-epsilon as distance in meters, "visvalingam" (Visvalingam-Whyatt) as area in square meters: points forming a triangle with their neighbours smaller than this area are removed. Visvalingam-Whyatt often simplifies curvy tracks more naturally.
+    #[arg(long, default_value = "rdp", value_parser = choice::<SimplifyAlgorithm>(&["rdp", "visvalingam"]))]
+    simplify_algo: SimplifyAlgorithm,
+
+    /// Range of epsilons given as start,end,step in meters, for which the simplified average distance is additionally calculated. This shows how the simplified average depends on the epsilon, e.g. to pick a suitable --simplify-epsilon.
+    #[arg(long, conflicts_with = "csv")]
+    epsilon_sweep: Option<EpsilonSweep>,
+
+    /// Spacing in meters to which the reference path and the compared track(s) are resampled before comparing them. The points are linearly interpolated along the tracks, keeping their first and last point. This makes the point-wise averages comparable between devices with different logging rates.
+    #[arg(long, value_parser = positive_distance)]
+    resample: Option<f64>,
+
+    /// How much of the start of the reference path and the compared track(s) is dropped before comparing them, e.g. to ignore the points recorded while waiting for a GPS fix. Either a distance along the track in meters (e.g. "100" or "100m") or a number of points (e.g. "10pts").
+    #[arg(long, value_name = "TRIM")]
+    start_offset: Option<Trim>,
+
+    /// How much of the end of the reference path and the compared track(s) is dropped before comparing them, in the same format as --start-offset
+    #[arg(long, value_name = "TRIM")]
+    end_trim: Option<Trim>,
+
+    /// Method to smooth the jitter of the compared track(s) before comparing them. The points are smoothed in the flat projection, the first and last point of every segment are kept.
+    #[arg(long, default_value = "none", value_parser = choice::<SmoothingMethod>(&["none", "moving-average", "savitzky-golay"]))]
+    smooth: SmoothingMethod,
+
+    /// Number of points within the window of the smoothing, an odd number of at least 3
+    #[arg(long, default_value = "5", value_parser = smooth_window)]
+    smooth_window: usize,
+
+    /// Toggle to also export the smoothed tracks as GPX files
+    #[arg(long)]
+    export_smoothed: bool,
+
     /// Toggle to also reexport the parsed GPX files as simplified GPX files
     #[arg(short, long)]
     export_track: bool,
 
-    /// Toggle to only output JSON data in the console
-    #[arg(short, long)]
-    json: bool,
-}
+    /// How distances are computed. "flat" projects all points onto a plane around the reference path, which is fast and very precise for tracks up to about 500km. "geodesic" computes all distances on the sphere (Haversine) without any projection, which is slower (no spatial index can be used for the closest points) but does not lose precision for long tracks.
+    #[arg(long, default_value = "flat", value_parser = choice::<DistanceMode>(&["flat", "geodesic"]))]
+    distance_mode: DistanceMode,
+
+    /// Projection of the flat distance mode. "flat" uses a plane around the reference path, whose precision degrades with the distance from it. "utm" projects all points into the UTM zone of the center of the reference path, which stays precise over a whole zone (about 670km wide) and allows comparing against external tools working in UTM coordinates.
+    #[arg(long, default_value = "flat", value_parser = choice::<Projection>(&["flat", "utm"]))]
+    projection: Projection,
+
+    /// Unit system of the reported distances and lengths. "metric" reports meters (and square meters), "imperial" feet (and square feet). The field names of the JSON and CSV output end in _ft (and _ft2) instead of _m (and _m2) for imperial units. All calculations, thresholds and other options stay in meters.
+    #[arg(long, default_value = "metric", value_parser = choice::<Units>(&["metric", "imperial"]))]
+    units: Units,
+
+    /// Distance in kilometers from the origin of the flat projection up to which the flat distance mode is considered precise. If a point of a track or the reference path is further away, a warning is printed. Does not apply to --distance-mode geodesic.
+    #[arg(long, default_value = "500", value_parser = positive_distance, value_name = "KM")]
+    precision_warn_km: f64,
+
+    /// Origin of the flat projection. By default the projection is centered on the average position of the reference path. Either a position given as lat,lon or "auto-both" to center it on the average position of the reference path and the compared track.
+    #[arg(long, default_value = "reference", value_name = "ORIGIN")]
+    projection_origin: ProjectionOrigin,
+
+    /// Width of the Sakoe-Chiba band (in points) used to constrain the Dynamic Time Warping distance. Smaller values are faster on large tracks. By default the DTW distance is unconstrained.
+    #[arg(long)]
+    dtw_window: Option<usize>,
+
+    /// Maximum number of points of either track used for the Fréchet, Hausdorff and DTW distance, whose runtime grows quadratically with the number of points. Longer tracks are uniformly downsampled (keeping the first and last point) for these metrics, which is reported as a warning. All other metrics still use every point. By default all points are used.
+    #[arg(long, value_name = "N", value_parser = max_points)]
+    max_points: Option<usize>,
+
+    /// Toggle to include the elevation of the points in all point distances, e.g. to compare tracks on different levels of a stacked road. Lengths are still measured horizontally.
+    #[arg(long)]
+    use_elevation: bool,
+
+    /// What to do if --use-elevation is set, but points of the reference path or a track have no elevation. "error" exits with an error, "ignore" uses the horizontal distance for those points.
+    #[arg(long, default_value = "error", requires = "use_elevation", value_parser = choice::<ElevationMissing>(&["error", "ignore"]))]
+    elevation_missing: ElevationMissing,
+
+    /// File path to write a GeoJSON FeatureCollection with the reference path and all compared tracks to. The computed distances are attached as properties to every track.
+    #[arg(long)]
+    export_geojson: Option<PathBuf>,
+
+    /// File path to write every point of the compared track(s) together with its closest point on the reference path and their distance to, as CSV. Without a path, the pairs are printed with --debug.
+    #[arg(long, conflicts_with = "multi_reference")]
+    debug_pairs: Option<PathBuf>,
+
+    /// File path to write every point of the compared track(s) with its position, elevation, distance to the reference path and distance along the track to, as CSV, e.g. to render a heatmap of the distances in another tool. The points of all tracks are written one after the other.
+    #[arg(long, conflicts_with = "multi_reference")]
+    export_point_distances: Option<PathBuf>,
+
+    /// Distance in meters up to which a point of a track counts as matching the reference path. Reports the fraction of the points within the tolerance and the longest run of points outside of it.
+    #[arg(long, value_parser = positive_distance)]
+    tolerance: Option<f64>,
+
+    /// Toggle to treat every point of the reference path as a checkpoint instead of comparing to the reference path as a line. Reports the smallest distance of every track to every checkpoint and, with --tolerance, how many checkpoints were passed within the tolerance. The tracks are compared as recorded, without trimming or smoothing.
+    #[arg(long, conflicts_with_all = ["multi_reference", "csv", "resample", "export_track", "export_smoothed", "export_geojson", "export_wkt", "report_dir", "debug_pairs", "export_point_distances", "max_average", "max_frechet", "serve", "manifest", "reference_dir"])]
+    reference_as_checkpoints: bool,
+
+    /// Full width in meters of a corridor around the reference path, e.g. for compliance checks. Reports the fraction of the length of every track inside the corridor, and the length and area outside of it.
+    #[arg(long, value_name = "METERS", value_parser = positive_distance)]
+    corridor_width: Option<f64>,
+
+    /// Length in meters of the consecutive windows along each track in which the average and maximum distance are reported, e.g. every 100m, to see where a track deviates from the reference path
+    #[arg(long, value_name = "METERS", value_parser = positive_distance)]
+    window_report: Option<f64>,
+
+    /// Toggle to also calculate the average distance from the points of the reference path to the track, and the mean of both directions. Unlike the average distance, this symmetric average does not depend on which track is the reference path.
+    #[arg(long)]
+    symmetric: bool,
+
+    /// Toggle to also calculate the average distance with every point weighted by its accuracy, i.e. inversely by its HDOP (horizontal dilution of precision). Points with a poor accuracy count less, points without HDOP get the weight 1.
+    #[arg(long)]
+    weight_by_hdop: bool,
+
+    /// How the segments of the reference path are used. "join" joins them into a single path (see --segment-mode), "best-match" treats every segment as a separate candidate (e.g. alternative routes) and compares each track to the segment with the smallest average distance. With "best-match", --segment-mode only applies to the compared tracks.
+    #[arg(long, default_value = "join", value_parser = choice::<ReferenceSegments>(&["join", "best-match"]))]
+    reference_segments: ReferenceSegments,
+
+    /// Toggle to abort with an error if a track file does not exist or can not be read. By default such files are skipped with a warning, and listed again after all other tracks were compared.
+    #[arg(long)]
+    fail_fast: bool,
+
+    /// Maximum number of tracks compared per track file, e.g. to quickly check the first tracks of a file bundling hundreds of them. The remaining tracks of every file are dropped right after reading it, before any preprocessing or comparison. Track indices in the output still refer to the file.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..), conflicts_with_all = ["streaming", "serve", "manifest", "reference_dir", "list_tracks", "validate"])]
+    max_tracks: Option<u32>,
+
+    /// Margin in meters of a cheap pre-filter which skips tracks far away from the reference path. Tracks whose bounding box does not overlap the bounding box of the reference path (of any reference path with --multi-reference), enlarged by the margin, are reported as "no overlap" and not compared at all. This saves most of the time when only a few of many candidate tracks follow the reference path. Track indices in the output still refer to the file.
+    #[arg(long, value_name = "MARGIN_M", value_parser = non_negative_distance, conflicts_with_all = ["streaming", "serve", "manifest", "reference_dir", "list_tracks", "validate"])]
+    bbox_prefilter: Option<f64>,
+
+    /// Toggle to detect tracks which run the reference path backwards (e.g. a round trip recorded in the opposite direction). Every track is also compared in reverse, and the reversed direction is used if it reduces the Fréchet distance significantly.
+    #[arg(long)]
+    auto_orient: bool,
+
+    /// Relative weights of the Fréchet, Hausdorff and average distance in the match score, a single match quality from 0 to 100. Every distance is mapped to a score of 100 * 0.5^(distance / 10m), i.e. 100 for a perfect overlay and half the score for every 10m, and the match score is the weighted mean of these scores.
+    #[arg(
+        long,
+        value_name = "FRECHET,HAUSDORFF,AVERAGE",
+        default_value = "1,1,1"
+    )]
+    score_weights: ScoreWeights,
+
+    /// Known bias of the compared track(s) as "north,east" in meters (e.g. "-1.5,2" for a device recording 1.5m too far south and 2m too far east), which is removed before comparing them. The remaining distances show the error after correcting the systematic part.
+    #[arg(long, value_name = "NORTH,EAST", allow_hyphen_values = true)]
+    track_offset: Option<TrackOffset>,
+
+    /// Toggle to estimate the bias of every compared track as the shift which moves it closest onto the reference path, and remove it before comparing. The estimated bias is reported, which separates the systematic from the random error. Starts from --track-offset if given.
+    #[arg(long)]
+    auto_offset: bool,
+
+    /// Toggle to also report the duration of every track and its minimum, average and maximum speed, calculated from the timestamps of its points. Tracks without timestamps are skipped with a warning.
+    #[arg(long)]
+    time_stats: bool,
+
+    /// Toggle to also compare the elevation profiles: the mean absolute difference between the elevation of every point of the track and the elevation of the reference path at the same fraction of its length, and the total ascent and descent of both. Tracks without elevations are skipped with a warning.
+    #[arg(long)]
+    elevation_profile: bool,
+
+    /// Toggle to also match every point of the track to the position of the reference path at the same time, interpolated between the timestamps of its points, instead of only to its geometrically closest point. Reports the average and maximum distance to these positions, which also includes lagging behind or running ahead on the same line. Requires timestamps in both files, points outside of the time span of the reference path are not matched and tracks without such points are skipped with a warning.
+    #[arg(long)]
+    match_by_time: bool,
+
+    /// Metric by which the compared tracks are ordered in the output instead of the input order, in ascending order (best match or shortest track first). Tracks without the metric, e.g. the Fréchet distance of single point tracks or the name of unnamed tracks, are always listed last.
+    #[arg(long, value_parser = choice::<SortKey>(&["frechet", "hausdorff", "average", "length", "name"]), conflicts_with_all = ["multi_reference", "reference_as_checkpoints", "streaming", "serve", "manifest", "reference_dir"])]
+    sort: Option<SortKey>,
+
+    /// Toggle to sort in descending order, e.g. the worst match first.
+    #[arg(long, requires = "sort")]
+    reverse: bool,
+
+    /// How tracks and reference paths with multiple segments are handled. "join" joins all segments into a single line, "first" only uses the first segment and "error" exits with an error if any track has more than one segment. Use --per-segment to compare every segment separately instead.
+    #[arg(long, default_value = "join", value_parser = choice::<SegmentMode>(&["join", "first", "error"]), conflicts_with_all = ["per_segment", "reference_dir", "serve", "manifest"])]
+    segment_mode: SegmentMode,
+
+    /// Toggle to exclude the lines connecting the end of a segment to the start of the next one from the track lengths (and all other lengths along the tracks), as they were never travelled. The segments are still joined for all distances.
+    #[arg(long)]
+    exclude_segment_gaps: bool,
+
+    /// Toggle to compare every segment of a track separately to the reference path instead of joining all segments of the track. This avoids the connecting line between two segments, e.g. of multiple laps or pauses.
+    #[arg(long)]
+    per_segment: bool,
+
+    /// Toggle to reject the input if a track or the reference path contains suspicious points (coordinates at exactly 0, 0, invalid coordinates, jumps larger than --max-jump or timestamps which are out of order or duplicated), instead of only printing a warning.
+    #[arg(long)]
+    strict: bool,
+
+    /// Maximum ratio between the lengths of a track and the reference path (e.g. 3 if one may be up to 3 times as long as the other). Larger ratios usually mean mismatched files and are reported as a warning, or rejected with --strict.
+    #[arg(long, default_value = "3", value_parser = length_ratio)]
+    length_ratio_threshold: f64,
+
+    /// Maximum distance in meters between two consecutive points of a track. Larger jumps are reported as suspicious points.
+    #[arg(long, value_parser = positive_distance)]
+    max_jump: Option<f64>,
+
+    /// Toggle to also include the lines from every point of a compared track to its closest point on the reference path in the GeoJSON export
+    #[arg(long, requires = "export_geojson")]
+    export_geojson_nearest: bool,
+
+    /// File path to write the reference path and all compared tracks to as WKT, one LINESTRING in longitude/latitude per line after a label (reference or file_<index>_track_<index>) and a tab
+    #[arg(long)]
+    export_wkt: Option<PathBuf>,
+
+    /// Toggle to write the simplified compared tracks (see --simplify_epsilon) to the WKT export instead of the full tracks
+    #[arg(long, requires = "export_wkt")]
+    export_wkt_simplified: bool,
+
+    /// Directory to write a JSON report per compared track to (named after the track file and the index of the track), together with a summary.json containing the minimum, mean and maximum of every metric over all tracks. The directory is created if it does not exist.
+    #[arg(long, value_name = "DIR")]
+    report_dir: Option<PathBuf>,
+
+    /// Toggle to read the track files incrementally and compare every point as soon as it is read, so the memory needed does not grow with the size of the files, e.g. for GPX files of several hundred megabytes. Only the lengths and the average, RMS and maximum distance are reported. Other formats than GPX are still read completely.
+    #[arg(long, conflicts_with_all = ["multi_reference", "csv", "section", "resample", "start_offset", "end_trim", "export_track", "export_smoothed", "export_geojson", "export_wkt", "report_dir", "debug_pairs", "export_point_distances", "use_elevation", "tolerance", "reference_as_checkpoints", "corridor_width", "window_report", "symmetric", "weight_by_hdop", "auto_orient", "track_offset", "auto_offset", "time_stats", "elevation_profile", "match_by_time", "per_segment", "epsilon_sweep", "max_average", "max_frechet", "serve", "manifest", "reference_dir", "list_tracks", "validate"])]
+    streaming: bool,
+
+    /// Hidden option to repeat the comparison of all tracks the given number of times (after one warm-up run) and print the minimum, median and maximum time of every phase instead of the results, e.g. to check if a change makes the comparison faster on a machine.
+    #[arg(long, hide = true, value_name = "RUNS", value_parser = clap::value_parser!(u32).range(1..), conflicts_with_all = ["json", "csv", "streaming", "serve", "manifest", "reference_dir", "list_tracks", "validate"])]
+    benchmark: Option<u32>,
+
+    /// Number of tracks to compare in parallel. Defaults to the number of logical cores, use 1 to compare the tracks sequentially.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    jobs: Option<u32>,
+
+    /// Maximum allowed average distance (in time) of a track in meters. If any track exceeds it, the application exits with status code 3 after printing all failing tracks.
+    #[arg(long)]
+    max_average: Option<f64>,
+
+    /// Maximum allowed Fréchet distance of a track in meters. If any track exceeds it, the application exits with status code 3 after printing all failing tracks.
+    #[arg(long)]
+    max_frechet: Option<f64>,
+
+    /// Distances in meters given as good,warn (e.g. 5,15) by which the distances of the human readable output are colored: green up to the good distance, yellow up to the warning distance and red above it. Without this option every metric has its own color. Colors are disabled automatically for the JSON and CSV output and if the output is no terminal.
+    #[arg(long, value_name = "GOOD,WARN")]
+    color_thresholds: Option<ColorThresholds>,
+
+    /// Toggle to never show the progress bar. It is already hidden automatically if the output is no terminal or machine readable.
+    #[arg(long)]
+    no_progress: bool,
+
+    /// Number of decimal places of all distances and lengths in the output. In the JSON output the values are rounded accordingly.
+    #[arg(long, default_value = "3", value_parser = clap::value_parser!(u8).range(0..=15))]
+    precision: u8,
+
+    /// File path to write the results (human readable, JSON or CSV) to instead of the console
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// Toggle to append to the output file instead of overwriting it. In CSV mode the header is only written if the file is empty.
+    #[arg(long, requires = "output")]
+    append: bool,
+
+    /// Toggle to run as a long-lived server, which reads one JSON request per line from the standard input and writes one JSON response per line to the standard output. Every request gives the reference and track file either as path or as base64 encoded content, all other options apply to every request.
+    #[arg(long, conflicts_with_all = ["reference", "track", "multi_reference", "section", "csv", "json", "output", "export_track", "export_smoothed", "export_geojson", "export_wkt", "report_dir", "debug_pairs", "export_point_distances"])]
+    serve: bool,
+
+    /// JSON manifest file describing a whole batch of comparisons instead of --reference and --track: an array of jobs like {"name": "commute", "reference": "ref.gpx", "tracks": ["a.gpx", "b.fit"], "simplify_epsilon": 2.0, "max_average": 10.0}. Every job may set simplify_epsilon, dtw_window, distance_mode, resample, tolerance, use_elevation, symmetric, max_points, max_average and max_frechet, all other options are taken from the command line. Relative paths are resolved relative to the manifest. The results of all jobs are written as JSON array (pretty-printed with --json --pretty), the application exits with status code 3 if any job failed.
+    #[arg(long, conflicts_with_all = ["reference", "reference_inline", "track", "multi_reference", "reference_track", "section", "serve", "list_tracks", "validate", "reference_dir", "csv", "debug", "export_track", "export_smoothed", "export_geojson", "export_wkt", "report_dir", "debug_pairs", "export_point_distances", "per_segment", "max_average", "max_frechet"])]
+    manifest: Option<PathBuf>,
+
+    /// YAML config file with defaults for any other option, e.g. to share a standardized set of options within a team. Every entry sets the option of the same name, e.g. "simplify-epsilon: 2.5", "units: imperial", "symmetric: true" or "track: [a.gpx, b.gpx]". Options given on the command line take precedence, the entries of the file for them (and for options conflicting with them) are ignored.
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Toggle to only list the tracks of the reference file(s) with their index, name, segments, number of points and length, and then exit without comparing anything. The named waypoints are listed as well.
+    #[arg(long, conflicts_with_all = ["track", "serve", "csv"])]
+    list_tracks: bool,
+
+    /// Toggle to only check that every --reference and --track file can be parsed and contains usable tracks (no missing tracks or waypoints, empty segments or suspicious points like with --strict), and then exit without comparing anything. Exits with an error if any file is invalid.
+    #[arg(long, conflicts_with_all = ["serve", "list_tracks", "reference_dir", "csv"])]
+    validate: bool,
+
+    /// Toggle to suppress all informational output and warnings. Errors and the results are still printed.
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Toggle to only output JSON data in the console
+    #[arg(short, long)]
+    json: bool,
+
+    /// Toggle to print a JSON record describing every reference path (name, length, number of points and projection origin) before the comparisons of the tracks, so a consumer of the JSON lines has the full context. The record has "record": "reference" to tell it apart from the tracks.
+    #[arg(long, requires = "json", conflicts_with_all = ["manifest", "reference_dir", "serve", "list_tracks", "validate"])]
+    json_header: bool,
+
+    /// Toggle to print a short explanation below every metric of the human readable output, describing what it measures and when to trust it based on the computed values (e.g. when the simplified average diverges a lot from the average)
+    #[arg(long, conflicts_with_all = ["json", "csv"])]
+    explain: bool,
+
+    /// Toggle to pretty-print the JSON output over multiple lines instead of one line per track
+    #[arg(long, requires = "json")]
+    pretty: bool,
+
+    /// Toggle to only output CSV data in the console, one row per compared track
+    #[arg(long)]
+    csv: bool,
+}
+
+/// Logger of the command line, printing the diagnostics of the binary and the library.
+/// Debug and trace records are printed to stdout with a green `[DEBUG]` or `[TRACE]` prefix, all other records to stderr.
+struct CliLogger {
+    /// Whether debug and trace records are printed to stderr as well, so they do not mix with machine readable output
+    stderr_only: bool,
+    /// Level of all records whose target is not matched by a directive
+    default_level: log::LevelFilter,
+    /// Levels of the records whose target starts with the given module path, e.g. `gps_path_average_distance=trace`
+    directives: Vec<(String, log::LevelFilter)>,
+}
+
+impl CliLogger {
+    /// Function to create the logger from the `RUST_LOG` environment variable and the debug flag.
+    /// `RUST_LOG` is a comma separated list of levels (`error`, `warn`, `info`, `debug`, `trace` or `off`), each optionally prefixed with `<module path>=`.
+    /// Invalid entries are ignored.
+    /// - `rust_log`: The value of `RUST_LOG`, if set.
+    /// - `debug`: Whether the debug flag is set, which enables at least the debug level for all records.
+    /// - `stderr_only`: Whether all records are printed to stderr.
+    /// - Returns: The logger.
+    fn new(rust_log: Option<&str>, debug: bool, stderr_only: bool) -> Self {
+        let mut logger = CliLogger {
+            stderr_only,
+            default_level: log::LevelFilter::Warn,
+            directives: Vec::new(),
+        };
+        for directive in rust_log.unwrap_or_default().split(',').map(str::trim) {
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    if let Ok(level) = level.trim().parse() {
+                        logger.directives.push((target.trim().to_string(), level));
+                    }
+                }
+                None => {
+                    if let Ok(level) = directive.parse() {
+                        logger.default_level = level;
+                    }
+                }
+            }
+        }
+        if debug {
+            logger.default_level = logger.default_level.max(log::LevelFilter::Debug);
+            for (_, level) in &mut logger.directives {
+                *level = (*level).max(log::LevelFilter::Debug);
+            }
+        }
+        // The most specific directive wins, so the longest module paths are checked first
+        logger
+            .directives
+            .sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+        logger
+    }
+
+    /// Function to get the most verbose level of any record this logger prints.
+    /// - Returns: The level.
+    fn max_level(&self) -> log::LevelFilter {
+        self.directives
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default_level, Ord::max)
+    }
+}
+
+impl log::Log for CliLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        let level = self
+            .directives
+            .iter()
+            .find(|(target, _)| metadata.target().starts_with(target.as_str()))
+            .map_or(self.default_level, |(_, level)| *level);
+        metadata.level() <= level
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match record.level() {
+            log::Level::Debug | log::Level::Trace if self.stderr_only => eprintln!(
+                "{} {}",
+                format!("[{}]", record.level()).green(),
+                record.args()
+            ),
+            log::Level::Debug => println!("{} {}", "[DEBUG]".green(), record.args()),
+            log::Level::Trace => println!("{} {}", "[TRACE]".green(), record.args()),
+            log::Level::Info => eprintln!("{} {}", "[INFO]".blue(), record.args()),
+            log::Level::Warn => eprintln!("{} {}", "[WARN]".yellow(), record.args()),
+            log::Level::Error => eprintln!("{} {}", "[ERROR]".red(), record.args()),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+// Macro to write a line of the results to the output, exiting with an error if that fails
+macro_rules! write_output {
+    ($output:expr, $($arg:tt)*) => {
+        if let Err(error) = writeln!($output, $($arg)*) {
+            eprintln!("Failed to write the output: {}", error);
+            process::exit(1);
+        }
+    }
+}
+
+// Macro to print a warning to stderr, unless the quiet flag is set
+macro_rules! print_warning {
+    ($quiet:expr, $($arg:tt)*) => {
+        if !$quiet {
+            eprintln!($($arg)*);
+        }
+    }
+}
+
+// Macro to print the explanation of a metric below it, if the explain flag is set
+macro_rules! explain {
+    ($output:expr, $matches:expr, $comparison:expr, $metric:expr) => {
+        if $matches.explain {
+            write_output!(
+                $output,
+                "  {}",
+                explanation($matches, $comparison, $metric).dimmed()
+            );
+        }
+    };
+}
+
+// Macro to only print informational output if neither the json, the csv nor the quiet flag is set
+macro_rules! print_info {
+    ($suppress_info:expr, $($arg:tt)*) => {
+        if !$suppress_info {
+            println!($($arg)*);
+        }
+    }
+}
+
+/// Function to describe what a metric of the human readable output measures and when to trust it, see --explain.
+/// The description takes the computed values into account, e.g. to point out when the simplified average diverges from the average.
+/// - `matches`: The parsed command line arguments.
+/// - `comparison`: The comparison of the track, already converted to the reported units.
+/// - `metric`: The name of the metric.
+/// - Returns: The description.
+fn explanation(matches: &Cli, comparison: &TrackComparison, metric: &str) -> String {
+    // Relative difference of two values, 0 if both are 0
+    let relative_difference = |a: f64, b: f64| {
+        let larger = a.abs().max(b.abs());
+        if larger > 0.0 {
+            (a - b).abs() / larger
+        } else {
+            0.0
+        }
+    };
+
+    match metric {
+        "lengths" => {
+            let mut text = "Lengths along both tracks. Very different lengths usually mean the track covers only a part of the reference path (or the wrong files are compared), which makes all other metrics less meaningful.".to_string();
+            let precise_length = matches.units.length(matches.precision_warn_km * 1000.0);
+            if matches.distance_mode == DistanceMode::Flat
+                && matches.projection == Projection::Flat
+                && comparison.current_track_length_m.max(comparison.reference_track_length_m)
+                    > precise_length
+            {
+                text.push_str(&format!(" These tracks are long: the flat projection is only precise up to about {} km from the reference path, consider --distance-mode geodesic or --projection utm.", matches.precision_warn_km));
+            }
+            // Half the width of a zone at the equator, tracks shorter than this can not leave the zone of their center
+            let zone_length = matches.units.length(UTM_HALF_ZONE_WIDTH * 111_320.0);
+            if matches.distance_mode == DistanceMode::Flat
+                && matches.projection == Projection::Utm
+                && comparison.current_track_length_m.max(comparison.reference_track_length_m)
+                    > zone_length
+            {
+                text.push_str(&format!(" These tracks are long: the utm projection is only precise within its zone ({}° in longitude from its central meridian), a warning is printed if they leave it.", UTM_HALF_ZONE_WIDTH));
+            }
+            text
+        }
+        "average" => "Mean distance of every recorded point to the closest point on the reference path. Points recorded while standing still or moving slowly count more often, so this average depends on the speed and the logging rate.".to_string(),
+        "simplified" => {
+            let mut text = "Mean distance of only the points kept after simplifying the track (see --simplify_epsilon), so every location counts about once regardless of the time spent there. Use it to compare the shapes of the paths.".to_string();
+            if relative_difference(
+                comparison.average_distance_m,
+                comparison.simplified_average_distance_m,
+            ) > 0.2
+            {
+                text.push_str(" It differs by more than 20% from the average in time here: the track was recorded unevenly (e.g. stops or speed changes) close to or far from the reference path, so trust this one for the shape.");
+            } else {
+                text.push_str(" It is close to the average in time here, so the recording speed does not distort the average.");
+            }
+            text
+        }
+        "rms" => {
+            let mut text = "Root mean square of the point distances, which weighs large deviations more than the average.".to_string();
+            if comparison.rms_distance_m > 1.5 * comparison.average_distance_m {
+                text.push_str(" It is much larger than the average here, so a few points deviate a lot (e.g. a detour or bad GPS fixes).");
+            }
+            text
+        }
+        "percentiles" => {
+            let mut text = "Typical (median) and near-worst (p90, p95) point distances, which are not affected by single outliers like the average.".to_string();
+            if comparison.max_distance_m > 3.0 * comparison.p95_distance_m && comparison.p95_distance_m > 0.0 {
+                text.push_str(" The maximum is far above the p95 here, so the worst deviations are isolated points.");
+            }
+            text
+        }
+        "max" => "Distance of the single point furthest away from the reference path, counted from 0 over all segments, to find the worst excursion in the GPS log. A single bad GPS fix is enough to make it large.".to_string(),
+        "offset" => "Average signed distance, positive if the track runs right of the reference path (in its direction of travel). A value close to the average distance means the track is consistently on one side, e.g. the other side of a road.".to_string(),
+        "track-error" => "Splits the distance of every point into the part perpendicular to the reference path (lateral drift) and the part along it. The along-track part is only non-zero where the closest point is a corner or an end of the reference path, e.g. if the track starts before or ends after it.".to_string(),
+        "frechet" => {
+            let mut text = "Shortest leash needed to walk both paths from start to end without going back. Unlike the averages it respects the order of the points, so it grows if the track runs parts of the route backwards or in another order.".to_string();
+            if comparison
+                .frechet_distance_m
+                .zip(comparison.hausdorff_distance_m)
+                .is_some_and(|(frechet, hausdorff)| frechet > 2.0 * hausdorff)
+            {
+                text.push_str(" It is much larger than the Hausdorff distance here, so the paths have a similar shape but are travelled in a different order or direction (see --auto-orient).");
+            }
+            text
+        }
+        "hausdorff" => "Largest distance of any point of either path to the other path, ignoring the order of the points. It is a worst case, so a single detour or skipped part determines it.".to_string(),
+        "dtw" => "Sum of the distances of the best alignment of all points of both tracks. It grows with the number of points, so it is only comparable between tracks with a similar number of points.".to_string(),
+        "area" => "Area enclosed between the track and the reference path, adding up both sides where the track crosses the reference path. Divided by the track length it gives another average offset.".to_string(),
+        "endpoints" => {
+            let mut text = "Distances from the first point of the track to the first point of the reference path and between both last points, e.g. to check that the right start and finish were recorded. If the start is much closer to the end of the reference path than to its start, the track runs it backwards (see --auto-orient).".to_string();
+            if comparison.reversed {
+                text.push_str(" The track was compared in reverse, so its first point is compared to the last point of the reference path and its last point to the first one.");
+            }
+            text
+        }
+        "score" => format!("Single match quality from 0 to 100 (a perfect overlay), the weighted mean ({}) of the scores of the Fréchet, Hausdorff and average distance. Every distance scores 100 * 0.5^(distance / {}m), so the score halves with every {}m of distance.", matches.score_weights, HALF_SCORE_DISTANCE_M, HALF_SCORE_DISTANCE_M),
+        "elevation" => "Mean absolute difference between the elevation of every point of the track and the elevation of the reference path at the same fraction of its length, independent of the horizontal distance. The ascent and descent add up all elevation changes between consecutive points, so noisy elevations (e.g. from a barometer) increase both.".to_string(),
+        "time-matched" => {
+            let mut text = "Distance between every point of the track and the position of the reference path at the same time, interpolated between the timestamps of the reference path. Unlike the average distance, which matches every point to its closest point wherever the reference path was at that time, it grows when the track lags behind or runs ahead on the same line.".to_string();
+            if let Some(time_matched) = &comparison.time_matched_distance {
+                if time_matched.average_distance_m > 2.0 * comparison.average_distance_m {
+                    text.push_str(" It is much larger than the average distance here, so the track is mostly offset in time rather than in space (or the clocks of both recordings differ).");
+                }
+            }
+            text
+        }
+        _ => String::new(),
+    }
+}
+
+/// Function to format a value which may not be available.
+/// - `value`: The value.
+/// - `precision`: The number of decimal places.
+/// - `unit`: The unit appended to the value.
+/// - Returns: The formatted value with its unit, or "n/a" if the value is not available.
+fn format_optional(value: Option<f64>, precision: usize, unit: &str) -> String {
+    value.map_or("n/a".to_string(), |value| {
+        format!("{:.*}{}", precision, value, unit)
+    })
+}
+
+/// Function to format a duration as hours, minutes and seconds.
+/// - `seconds`: The duration in seconds.
+/// - Returns: The formatted duration, e.g. "1:02:03".
+fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Function to color a formatted distance of the human readable output, by its severity if `--color-thresholds` is given.
+/// - `matches`: The parsed command line arguments.
+/// - `text`: The formatted distance.
+/// - `distance`: The distance in the reported units, `None` if it is not available.
+/// - `color`: The color of the distance without severity thresholds, `None` to leave it uncolored.
+/// - Returns: The colored distance.
+fn color_distance(
+    matches: &Cli,
+    text: String,
+    distance: Option<f64>,
+    color: Option<Color>,
+) -> ColoredString {
+    // The thresholds are given in meters, while the distance is already converted to the reported units
+    let severity = match (matches.color_thresholds, distance) {
+        (Some(thresholds), Some(distance)) if !distance.is_nan() => Some(
+            ColorThresholds {
+                good: matches.units.length(thresholds.good),
+                warn: matches.units.length(thresholds.warn),
+            }
+            .color(distance),
+        ),
+        _ => None,
+    };
+    match severity.or(color) {
+        Some(color) => text.color(color),
+        None => text.normal(),
+    }
+}
+
+/// Function to select the track of a reference file which is used as the reference path.
+/// - `reference_gpx`: The content of the reference file.
+/// - `reference_path`: The path of the reference file.
+/// - `selector`: The explicitly selected track, if any. Otherwise the first track is used.
+/// - `suppress_info`: Whether informational output is suppressed.
+/// - Returns: The reference track, or the error message if no usable track can be selected.
+fn select_reference_track(
+    reference_gpx: &Gpx,
+    reference_path: &Path,
+    selector: Option<&TrackSelector>,
+    suppress_info: bool,
+) -> Result<Track, String> {
+    // Check that the reference path has at least one track
+    let reference_track = if let Some(selector) = selector {
+        // Use the explicitly selected track
+        match select_track(&reference_gpx.tracks, selector) {
+            Ok(index) => reference_gpx.tracks[index].clone(),
+            Err(error) => {
+                return Err(format!(
+                    "Failed to select the reference track {} of {:?}: {}",
+                    selector, reference_path, error
+                ));
+            }
+        }
+    } else if !reference_gpx.tracks.is_empty() {
+        // If there are more than 1 track, print a warning that only the first track will be used and that it potentially should be manually checked if this is correct
+        if reference_gpx.tracks.len() > 1 {
+            print_info!(suppress_info, "The reference path {:?} contains more than one track. Only the first track will be used. Please verify that this is the correct track or select another one with --reference-track.", reference_path);
+        }
+
+        // Get the first track of the reference path
+        log::debug!("Reference path {:?}: using its first track", reference_path);
+        reference_gpx.tracks[0].clone()
+    }
+    // Planning tools often export the reference path as route instead of a track, which is preferred over the waypoints
+    else if let Some(route) = reference_gpx.routes.first() {
+        if reference_gpx.routes.len() > 1 {
+            print_info!(suppress_info, "The reference path {:?} does not contain any tracks, but more than one route. Only the first route will be used.", reference_path);
+        }
+        log::debug!(
+            "Reference path {:?}: no tracks, using its first route ({} points)",
+            reference_path,
+            route.points.len()
+        );
+        route_track(route)
+    }
+    // Check if the reference path has any waypoints
+    // If so create a Track with a single TrackSegment containing all the waypoints
+    else if !reference_gpx.waypoints.is_empty() {
+        print_info!(suppress_info, "The reference path {:?} does not contain any tracks, but it does contain waypoints. Creating a track from the waypoints", reference_path);
+        log::debug!(
+            "Reference path {:?}: no tracks or routes, using its {} waypoints",
+            reference_path,
+            reference_gpx.waypoints.len()
+        );
+        let mut track: Track = Track::default();
+        let track_segment = TrackSegment {
+            points: reference_gpx.waypoints.clone(),
+        };
+        track.segments.push(track_segment);
+        track
+    } else {
+        // No waypoints or tracks, so there is nothing to compare to
+        return Err(format!(
+            "The reference path {:?} does not contain any tracks, routes or waypoints",
+            reference_path
+        ));
+    };
+
+    // Without any points there is no origin for the projection, which would silently turn all distances into NaN
+    check_reference(&reference_track).map_err(|error| {
+        format!(
+            "The reference track {} of {:?} can not be used: {}",
+            reference_track.name.as_deref().unwrap_or("-- Unnamed --"),
+            reference_path,
+            error
+        )
+    })?;
+    Ok(reference_track)
+}
+
+/// Function to serialize a value of the JSON output whose fields are named in metric units, see [`Units::field_name`].
+/// - `value`: The value to serialize, whose values are already converted to the units.
+/// - `units`: The units of the values.
+/// - `pretty`: Whether the JSON should be pretty-printed instead of printed on a single line.
+fn json_string_in_units<T: Serialize>(value: &T, units: Units, pretty: bool) -> String {
+    let mut value = serde_json::to_value(value).expect("The JSON output can always be serialized");
+    units.rename_json_fields(&mut value);
+    json_string(&value, pretty)
+}
+
+/// Function to serialize a value of the JSON output.
+/// - `value`: The value to serialize.
+/// - `pretty`: Whether the JSON should be pretty-printed instead of printed on a single line.
+/// - Returns: The JSON string.
+fn json_string<T: Serialize>(value: &T, pretty: bool) -> String {
+    let result = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+    result.expect("The JSON output can always be serialized")
+}
+
+/// The compared track files, after they were read and preprocessed
+struct TrackFiles<'a> {
+    /// Path of every track file
+    paths: &'a [PathBuf],
+    /// Content of every track file, only containing the compared tracks
+    gpxs: &'a [Gpx],
+    /// For every compared track of a file, the 0-based index of the track in its file and of its segment with --per-segment
+    origins: &'a [Vec<(usize, Option<usize>)>],
+}
+
+/// A metric of a track which exceeds the threshold given on the command line
+struct ThresholdFailure {
+    /// Path of the file containing the track
+    track_path: PathBuf,
+    /// 1-based index of the track in its file
+    track_index: usize,
+    /// Name of the track
+    track_name: String,
+    /// Name of the metric, matching the key in the JSON output
+    metric: &'static str,
+    /// Value of the metric in meters
+    value: f64,
+    /// Threshold of the metric in meters
+    threshold: f64,
+}
+
+impl ThresholdFailure {
+    /// Function to build the JSON object describing the failure.
+    /// - `precision`: The number of decimal places all values are rounded to.
+    /// - Returns: The JSON object.
+    fn json(&self, precision: usize) -> serde_json::Value {
+        let round = |value: f64| round_to_precision(value, precision);
+        serde_json::json!({
+            "track_path": self.track_path,
+            "track_index": self.track_index,
+            "track_name": self.track_name,
+            "metric": self.metric,
+            "value_m": round(self.value),
+            "threshold_m": round(self.threshold),
+            "exceeded_by_m": round(self.value - self.threshold),
+        })
+    }
+}
+
+/// Maximum number of suspicious points listed per track
+const MAX_LISTED_ISSUES: usize = 10;
+
+/// Header of the CSV file written with --debug-pairs, followed by one row per point of every compared track
+const DEBUG_PAIRS_CSV_HEADER: &str =
+    "track_index,current_lat,current_lon,nearest_lat,nearest_lon,distance_m";
+
+/// Header of the CSV file written with --export-point-distances, followed by one row per point of every compared track
+const POINT_DISTANCES_CSV_HEADER: &str = "lon,lat,elevation,distance_m,cumulative_arc_m";
+
+/// Header of the CSV output, matching the columns of [`comparison_csv`]
+const CSV_HEADER: &str = "track_index,track_name,current_track_length_m,reference_track_length_m,average_distance_m,simplified_average_distance_m,frechet_distance_m,hausdorff_distance_m,dtw_distance_m";
+
+/// Function to build the CSV row describing the comparison of a single track.
+/// - `track_index`: The 1-based index of the track in its file.
+/// - `track_name`: The name of the track.
+/// - `comparison`: The result of the comparison.
+/// - `precision`: The number of decimal places of all values.
+/// - Returns: The CSV row, without a trailing line break.
+fn comparison_csv(
+    track_index: usize,
+    track_name: &str,
+    comparison: &TrackComparison,
+    precision: usize,
+) -> String {
+    // Values which are not available (e.g. curve metrics of single point tracks) are left empty
+    let values = [
+        Some(comparison.current_track_length_m),
+        Some(comparison.reference_track_length_m),
+        Some(comparison.average_distance_m),
+        Some(comparison.simplified_average_distance_m),
+        comparison.frechet_distance_m,
+        comparison.hausdorff_distance_m,
+        comparison.dtw_distance_m,
+    ]
+    .map(|value| value.map_or(String::new(), |value| format!("{:.*}", precision, value)));
+
+    format!(
+        "{},{},{}",
+        track_index,
+        csv_field(track_name),
+        values.join(",")
+    )
+}
+
+/// Function to get the header of a CSV file, with the names of the columns in the units.
+/// - `header`: The header with the names of the columns in metric units, e.g. [`CSV_HEADER`].
+/// - `units`: The units of the values.
+/// - Returns: The header row.
+fn csv_header(header: &str, units: Units) -> String {
+    header
+        .split(',')
+        .map(|column| units.field_name(column))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Function to quote a text value of a CSV row if it contains characters with a special meaning in CSV.
+/// - `value`: The text value.
+/// - Returns: The value as CSV field.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Function to check that every reference and track file can be parsed and contains usable tracks, without comparing anything.
+/// - `matches`: The parsed command line arguments.
+/// - `output`: The output the report of every file is written to.
+/// - Returns: `true` if all files are valid.
+fn validate_files(matches: &Cli, output: &mut dyn Write) -> bool {
+    let files = matches
+        .reference
+        .iter()
+        .map(|path| ("reference", path))
+        .chain(matches.track.iter().map(|path| ("track", path)));
+
+    let mut all_valid = true;
+    for (role, path) in files {
+        let result = if is_stdin(path) {
+            read_reader(BufReader::new(std::io::stdin()))
+        } else {
+            read_file(path)
+        };
+        let mut report = match result {
+            Ok(gpx) => ValidationOutput {
+                path: path.display().to_string(),
+                role: role.to_string(),
+                valid: false,
+                error: None,
+                track_count: gpx.tracks.len(),
+                point_count: gpx
+                    .tracks
+                    .iter()
+                    .flat_map(|track| &track.segments)
+                    .map(|segment| segment.points.len())
+                    .sum(),
+                issues: validate_file(&gpx, matches.max_jump)
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+            },
+            Err(error) => ValidationOutput {
+                path: path.display().to_string(),
+                role: role.to_string(),
+                valid: false,
+                error: Some(error.to_string()),
+                track_count: 0,
+                point_count: 0,
+                issues: Vec::new(),
+            },
+        };
+        report.valid = report.error.is_none() && report.issues.is_empty();
+        all_valid &= report.valid;
+
+        if matches.json {
+            write_output!(output, "{}", json_string(&report, matches.pretty));
+        } else if let Some(error) = &report.error {
+            write_output!(
+                output,
+                "{} {} {:?}: {}",
+                "INVALID".red().bold(),
+                role,
+                path,
+                error
+            );
+        } else if report.valid {
+            write_output!(
+                output,
+                "{} {} {:?} ({} track(s), {} point(s))",
+                "OK".green().bold(),
+                role,
+                path,
+                report.track_count,
+                report.point_count
+            );
+        } else {
+            write_output!(
+                output,
+                "{} {} {:?} ({} track(s), {} point(s)) has {} issue(s):",
+                "INVALID".red().bold(),
+                role,
+                path,
+                report.track_count,
+                report.point_count,
+                report.issues.len()
+            );
+            // Like for --strict, only the first issues are listed
+            for issue in report.issues.iter().take(MAX_LISTED_ISSUES) {
+                write_output!(output, "  - {}", issue);
+            }
+            if report.issues.len() > MAX_LISTED_ISSUES {
+                write_output!(
+                    output,
+                    "  - ... and {} more",
+                    report.issues.len() - MAX_LISTED_ISSUES
+                );
+            }
+        }
+    }
+    all_valid
+}
+
+/// Function to warn if the curve metrics of a comparison were calculated on downsampled tracks, see --max-points.
+/// - `matches`: The parsed command line arguments.
+/// - `comparison`: The comparison of the track.
+/// - `track_name`: The name of the track.
+/// - `track_path`: The path of the file containing the track.
+fn warn_if_downsampled(
+    matches: &Cli,
+    comparison: &TrackComparison,
+    track_name: &str,
+    track_path: &Path,
+) {
+    let Some(max_points) = matches.max_points else {
+        return;
+    };
+    if comparison.point_count > max_points || comparison.reference_point_count > max_points {
+        print_warning!(
+            matches.quiet,
+            "Warning: The track {} in {:?} ({} points) and/or the reference path ({} points) have more than {} points, they were downsampled to {} points for the Fréchet, Hausdorff and DTW distance",
+            track_name,
+            track_path,
+            comparison.point_count,
+            comparison.reference_point_count,
+            max_points,
+            max_points
+        );
+    }
+}
+
+/// Function to print a warning if points or lines of a track have no closest point on the reference path, as they are excluded from all point statistics and the corridor statistics.
+/// - `matches`: The parsed command line arguments.
+/// - `comparison`: The comparison of the track.
+/// - `track_name`: The name of the track.
+/// - `track_path`: The path of the file containing the track.
+fn warn_if_indeterminate(
+    matches: &Cli,
+    comparison: &TrackComparison,
+    track_name: &str,
+    track_path: &Path,
+) {
+    if comparison.indeterminate_points > 0 {
+        print_warning!(
+            matches.quiet,
+            "Warning: {} of {} points of the track {} in {:?} have no closest point on the reference path (it has less than two points), they are excluded from the average and all other point statistics",
+            comparison.indeterminate_points,
+            comparison.point_count + comparison.indeterminate_points,
+            track_name,
+            track_path
+        );
+    }
+    if let Some(corridor) = comparison
+        .corridor
+        .as_ref()
+        .filter(|corridor| corridor.indeterminate_length_m > 0.0)
+    {
+        print_warning!(
+            matches.quiet,
+            "Warning: {:.1}m of the track {} in {:?} have no closest point on the reference path, they are neither counted inside nor outside of the corridor",
+            corridor.indeterminate_length_m,
+            track_name,
+            track_path
+        );
+    }
+}
+
+/// Function to print a warning if the time statistics or the elevation profile of a track were requested, but it has no timestamps or elevations.
+/// - `matches`: The parsed command line arguments.
+/// - `comparison`: The comparison of the track.
+/// - `track_name`: The name of the track.
+/// - `track_path`: The path of the file containing the track.
+fn warn_if_statistics_skipped(
+    matches: &Cli,
+    comparison: &TrackComparison,
+    track_name: &str,
+    track_path: &Path,
+) {
+    if matches.time_stats && comparison.time_statistics.is_none() {
+        print_warning!(
+            matches.quiet,
+            "Warning: The track {} in {:?} has no consecutive points with timestamps, its time statistics are skipped",
+            track_name,
+            track_path
+        );
+    }
+    if matches.elevation_profile && comparison.elevation_profile.is_none() {
+        print_warning!(
+            matches.quiet,
+            "Warning: The track {} in {:?} or the reference path has no elevations, the elevation profile is skipped",
+            track_name,
+            track_path
+        );
+    }
+    if matches.match_by_time && comparison.time_matched_distance.is_none() {
+        print_warning!(
+            matches.quiet,
+            "Warning: The track {} in {:?} has no timestamps within the time span of the reference path, the time matched distance is skipped",
+            track_name,
+            track_path
+        );
+    }
+}
+
+/// Function to run all jobs of a manifest file and write their results as JSON array.
+/// Exits the application with an error if the manifest can not be read or parsed.
+/// - `matches`: The parsed command line arguments.
+/// - `manifest_path`: The path of the manifest file.
+/// - `compare_options`: The options given on the command line, which are used for everything the jobs do not set.
+/// - `output`: The output to write the results to.
+/// - `precision`: The number of decimal places of all values.
+/// - Returns: `true` if every job passed, i.e. all its files were compared and no track exceeded a threshold.
+fn run_manifest(
+    matches: &Cli,
+    manifest_path: &Path,
+    compare_options: &CompareOptions,
+    output: &mut dyn Write,
+    precision: usize,
+) -> bool {
+    let jobs = match fs::read_to_string(manifest_path)
+        .map_err(|error| error.to_string())
+        .and_then(|content| parse_manifest(&content))
+    {
+        Ok(jobs) => jobs,
+        Err(error) => {
+            eprintln!("Failed to read the manifest {:?}: {}", manifest_path, error);
+            process::exit(1);
+        }
+    };
+    let base_directory = manifest_path.parent().unwrap_or(Path::new(""));
+
+    let run = |job: &ManifestJob| {
+        run_job(
+            job,
+            base_directory,
+            compare_options,
+            precision,
+            matches.units,
+        )
+    };
+    let results = run_parallel(thread_pool(matches.jobs).as_ref(), &jobs, run);
+
+    for (index, result) in results.iter().enumerate() {
+        let name = result.name.clone().unwrap_or(format!("#{}", index + 1));
+        if let Some(error) = &result.error {
+            print_warning!(matches.quiet, "Warning: Job {} failed: {}", name, error);
+        }
+        for file in &result.files {
+            if let Some(error) = &file.error {
+                print_warning!(
+                    matches.quiet,
+                    "Warning: Job {} could not compare {:?}: {}",
+                    name,
+                    file.track_path,
+                    error
+                );
+            }
+        }
+    }
+
+    write_output!(
+        output,
+        "{}",
+        json_string_in_units(&results, matches.units, matches.pretty)
+    );
+    results.iter().all(|result| result.passed)
+}
+
+/// Function to load a reference file from an index saved with `--save-reference-index`.
+/// Exits the application with an error if the index can not be loaded, e.g. because the reference file changed since it was saved.
+/// - `reference_path`: The path of the reference file, whose content has to match the index.
+/// - `index_path`: The path of the index file.
+/// - Returns: The reference file as GPX structure.
+fn load_index(reference_path: &Path, index_path: &Path) -> Gpx {
+    let result = fs::read(reference_path)
+        .map_err(|error| format!("failed to read the reference path: {}", error))
+        .and_then(|data| {
+            load_reference_index(index_path, &data).map_err(|error| match error {
+                ReferenceIndexError::Stale => format!(
+                    "{} {:?}, rebuild it with --save-reference-index",
+                    error, reference_path
+                ),
+                error => error.to_string(),
+            })
+        });
+    match result {
+        Ok(gpx) => gpx,
+        Err(error) => {
+            eprintln!(
+                "Failed to load the reference index {:?}: {}",
+                index_path, error
+            );
+            process::exit(1);
+        }
+    }
+}
+
+/// Comparison of a track of a file, together with the index and name of the track.
+type NamedComparison = (usize, String, TrackComparison);
+
+/// Function to collect the files of a directory by their name without extensions, see [`input_file_stem`].
+/// Files of unsupported formats are ignored. Exits the application with an error if the directory can not be read.
+/// - `directory`: The directory.
+/// - `quiet`: Whether warnings are suppressed.
+/// - Returns: The paths of all files by their name, sorted by name.
+fn input_files_by_stem(directory: &Path, quiet: bool) -> BTreeMap<String, PathBuf> {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(error) => {
+            eprintln!("Failed to read the directory {:?}: {}", directory, error);
+            process::exit(1);
+        }
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    // Sorting the paths first keeps the choice between files with the same name deterministic
+    paths.sort();
+
+    let mut files = BTreeMap::new();
+    for path in paths {
+        let Some(stem) = input_file_stem(&path) else {
+            continue;
+        };
+        if let Some(existing) = files.get(&stem) {
+            print_warning!(
+                quiet,
+                "Warning: Ignoring {:?}, as {:?} has the same name",
+                path,
+                existing
+            );
+            continue;
+        }
+        files.insert(stem, path);
+    }
+    files
+}
+
+/// Function to compare every file of the track directory to the file with the same name in the reference directory.
+/// Files without a counterpart in the other directory are reported as warnings.
+/// Exits the application with an error if a file can not be read.
+/// - `matches`: The parsed command line arguments.
+/// - `compare_options`: The options used for all comparisons.
+/// - `output`: The output the results are written to.
+/// - `precision`: The number of decimal places of all values.
+/// - `output_has_content`: Whether the output file already has content, so the CSV header is not repeated.
+fn compare_directories(
+    matches: &Cli,
+    compare_options: &CompareOptions,
+    output: &mut dyn Write,
+    precision: usize,
+    output_has_content: bool,
+) {
+    let (Some(reference_dir), Some(track_dir)) = (&matches.reference_dir, &matches.track_dir)
+    else {
+        return;
+    };
+    let suppress_info = matches.json || matches.csv || matches.quiet;
+
+    let reference_files = input_files_by_stem(reference_dir, matches.quiet);
+    let track_files = input_files_by_stem(track_dir, matches.quiet);
+    for (stem, path) in &reference_files {
+        if !track_files.contains_key(stem) {
+            print_warning!(
+                matches.quiet,
+                "Warning: The reference path {:?} has no track file named {:?} in {:?}",
+                path,
+                stem,
+                track_dir
+            );
+        }
+    }
+    for (stem, path) in &track_files {
+        if !reference_files.contains_key(stem) {
+            print_warning!(
+                matches.quiet,
+                "Warning: The track file {:?} has no reference path named {:?} in {:?}",
+                path,
+                stem,
+                reference_dir
+            );
+        }
+    }
+    let pairs: Vec<(&String, &PathBuf, &PathBuf)> = reference_files
+        .iter()
+        .filter_map(|(stem, reference_path)| {
+            track_files
+                .get(stem)
+                .map(|track_path| (stem, reference_path, track_path))
+        })
+        .collect();
+    print_info!(
+        suppress_info,
+        "Comparing {} pair(s) of files with the same name in {:?} and {:?}... ",
+        pairs.len(),
+        reference_dir,
+        track_dir
+    );
+
+    // Every pair is read and compared independently, so the pairs can be compared in parallel
+    let compare_pair = |&(_, reference_path, track_path): &(&String, &PathBuf, &PathBuf)| {
+        let reference_gpx = read_file(reference_path).map_err(|error| {
+            format!(
+                "Failed to read the reference path {:?}: {}",
+                reference_path, error
+            )
+        })?;
+        let track_gpx = read_file(track_path).map_err(|error| {
+            format!("Failed to read the track path {:?}: {}", track_path, error)
+        })?;
+        let reference_track = select_reference_track(
+            &reference_gpx,
+            reference_path,
+            matches.reference_track.as_ref(),
+            true,
+        )?;
+        let comparisons: Vec<NamedComparison> = track_gpx
+            .tracks
+            .iter()
+            .enumerate()
+            .map(|(index, track)| {
+                let projector =
+                    track_projection(&reference_track, track, compare_options.projection_origin);
+                let comparison = compare_tracks_with_projection(
+                    &reference_track,
+                    track,
+                    compare_options,
+                    &projector,
+                )
+                .map_err(|error| {
+                    format!(
+                        "Can not compare the reference path {:?}: {}",
+                        reference_path, error
+                    )
+                })?;
+                Ok((
+                    index,
+                    track.name.clone().unwrap_or("-- Unnamed --".to_string()),
+                    comparison,
+                ))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok::<_, String>(comparisons)
+    };
+    let results = run_parallel(thread_pool(matches.jobs).as_ref(), &pairs, compare_pair);
+
+    if matches.csv && !output_has_content {
+        write_output!(output, "name,{}", csv_header(CSV_HEADER, matches.units));
+    }
+    if !(matches.json || matches.csv) {
+        write_output!(
+            output,
+            "{}",
+            format!(
+                "{:<24} {:<24} {:>12} {:>12} {:>12} {:>12}",
+                "Name", "Track", "Average", "Simplified", "Fréchet", "Hausdorff"
+            )
+            .bold()
+        );
+    }
+    for ((stem, reference_path, track_path), result) in pairs.iter().zip(results) {
+        let comparisons = match result {
+            Ok(comparisons) => comparisons,
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        };
+
+        let mut outputs = Vec::new();
+        for (index, track_name, comparison) in &comparisons {
+            // Tracks without any points can not be compared, so they are skipped
+            if comparison.point_count == 0 && comparison.indeterminate_points == 0 {
+                print_warning!(
+                    matches.quiet,
+                    "Warning: Skipping track {} ({}) in {:?}, as it does not contain any points",
+                    index + 1,
+                    track_name,
+                    track_path
+                );
+                continue;
+            }
+            warn_if_downsampled(matches, comparison, track_name, track_path);
+            warn_if_indeterminate(matches, comparison, track_name, track_path);
+            warn_if_statistics_skipped(matches, comparison, track_name, track_path);
+
+            // Only the reported values are converted, the warnings above are based on the values in meters
+            let comparison = &matches.units.convert_comparison(comparison);
+            let length_label = matches.units.length_label();
+            if matches.json {
+                outputs.push(ComparisonOutput::new(
+                    index + 1,
+                    track_name,
+                    comparison,
+                    precision,
+                ));
+            } else if matches.csv {
+                write_output!(
+                    output,
+                    "{},{}",
+                    csv_field(stem),
+                    comparison_csv(index + 1, track_name, comparison, precision)
+                );
+            } else {
+                write_output!(
+                    output,
+                    "{:<24} {:<24} {:>12} {:>12} {:>12} {:>12}",
+                    stem,
+                    track_name,
+                    color_distance(
+                        matches,
+                        format!(
+                            "{:.*}{}",
+                            precision, comparison.average_distance_m, length_label
+                        ),
+                        Some(comparison.average_distance_m),
+                        None
+                    ),
+                    color_distance(
+                        matches,
+                        format!(
+                            "{:.*}{}",
+                            precision, comparison.simplified_average_distance_m, length_label
+                        ),
+                        Some(comparison.simplified_average_distance_m),
+                        None
+                    ),
+                    color_distance(
+                        matches,
+                        format_optional(comparison.frechet_distance_m, precision, length_label),
+                        comparison.frechet_distance_m,
+                        None
+                    ),
+                    color_distance(
+                        matches,
+                        format_optional(comparison.hausdorff_distance_m, precision, length_label),
+                        comparison.hausdorff_distance_m,
+                        None
+                    )
+                );
+            }
+        }
+
+        if matches.json {
+            let pair_output = DirectoryPairOutput {
+                name: stem.to_string(),
+                reference_path: reference_path.display().to_string(),
+                track_path: track_path.display().to_string(),
+                tracks: outputs,
+            };
+            write_output!(
+                output,
+                "{}",
+                json_string_in_units(&pair_output, matches.units, matches.pretty)
+            );
+        }
+    }
+}
+
+/// Function to flush the output, so all results are written before the application exits.
+/// Exits the application with an error if the output can not be written.
+/// - `output`: The output the results are written to.
+fn flush_output(output: &mut dyn Write) {
+    if let Err(error) = output.flush() {
+        eprintln!("Failed to write the output: {}", error);
+        process::exit(1);
+    }
+}
+
+/// Function to create the thread pool for comparisons which are independent of each other, see --jobs.
+/// - `jobs`: The number of threads, one thread per logical core if not given.
+/// - Returns: The thread pool, `None` for a single job, where the items are processed on the current thread.
+fn thread_pool(jobs: Option<u32>) -> Option<rayon::ThreadPool> {
+    match jobs {
+        Some(1) => None,
+        // Without an explicit number of jobs rayon uses one thread per logical core
+        jobs => Some(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(jobs.unwrap_or(0) as usize)
+                .build()
+                .expect("Failed to create thread pool"),
+        ),
+    }
+}
+
+/// Function to process items which are independent of each other, in parallel if a thread pool is given.
+/// - `pool`: The thread pool, see [`thread_pool`]. Without a pool the items are processed on the current thread.
+/// - `items`: The items to process.
+/// - `f`: The function processing a single item.
+/// - Returns: The results in the order of the items.
+fn run_parallel<T, R, F>(pool: Option<&rayon::ThreadPool>, items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync + Send,
+{
+    match pool {
+        Some(pool) => pool.install(|| items.par_iter().map(f).collect()),
+        None => items.iter().map(f).collect(),
+    }
+}
+
+/// Function to list the tracks, segments and waypoints of the reference files, e.g. to find the index of a track for --reference-track.
+/// Exits the application with an error if a file can not be read.
+/// - `matches`: The parsed command line arguments.
+/// - `reference_paths`: The paths of the reference files.
+/// - `output`: The output the listing is written to.
+/// - `precision`: The number of decimal places of all values.
+fn list_tracks(
+    matches: &Cli,
+    reference_paths: &[PathBuf],
+    output: &mut dyn Write,
+    precision: usize,
+) {
+    for reference_path in reference_paths {
+        let reference_gpx = match read_file(reference_path) {
+            Ok(gpx) => gpx,
+            Err(error) => {
+                eprintln!("Failed to read the file {:?}: {}", reference_path, error);
+                process::exit(1);
+            }
+        };
+        let listing = FileListingOutput {
+            path: reference_path.display().to_string(),
+            waypoint_names: reference_gpx
+                .waypoints
+                .iter()
+                .map(|waypoint| waypoint.name.clone())
+                .collect(),
+            tracks: reference_gpx
+                .tracks
+                .iter()
+                .enumerate()
+                .map(|(index, track)| TrackListingOutput {
+                    track_index: index + 1,
+                    track_name: track.name.clone(),
+                    segment_point_counts: track
+                        .segments
+                        .iter()
+                        .map(|segment| segment.points.len())
+                        .collect(),
+                    point_count: track
+                        .segments
+                        .iter()
+                        .map(|segment| segment.points.len())
+                        .sum(),
+                    length_m: round_to_precision(
+                        join_segments(&track.segments).haversine_length(),
+                        precision,
+                    ),
+                })
+                .collect(),
+        };
+
+        if matches.json {
+            write_output!(output, "{}", json_string(&listing, matches.pretty));
+            continue;
+        }
+        write_output!(
+            output,
+            "{}: {} track(s), {} waypoint(s)",
+            reference_path.display().to_string().bold(),
+            listing.tracks.len(),
+            listing.waypoint_names.len()
+        );
+        for track in &listing.tracks {
+            write_output!(
+                output,
+                "  Track {}: {} ({} segment(s), {} point(s), {:.*}m)",
+                track.track_index,
+                track.track_name.as_deref().unwrap_or("-- Unnamed --"),
+                track.segment_point_counts.len(),
+                track.point_count,
+                precision,
+                track.length_m
+            );
+            for (segment_index, point_count) in track.segment_point_counts.iter().enumerate() {
+                write_output!(
+                    output,
+                    "    Segment {}: {} point(s)",
+                    segment_index + 1,
+                    point_count
+                );
+            }
+        }
+        for name in &listing.waypoint_names {
+            write_output!(
+                output,
+                "  Waypoint: {}",
+                name.as_deref().unwrap_or("-- Unnamed --")
+            );
+        }
+    }
+}
+
+/// Function to compare the tracks while they are read, without ever loading a whole track file into memory, see --streaming.
+/// Exits the application with an error if none of the track files can be read.
+/// - `matches`: The parsed command line arguments.
+/// - `reference_track`: The selected track of the reference path.
+/// - `track_paths`: The paths of the track files.
+/// - `compare_options`: The options used for all comparisons.
+/// - `output`: The output the results are written to.
+/// - `precision`: The number of decimal places of all values.
+/// - `skip_track_file`: Called with the path and the reason for every track file which can not be read.
+fn compare_streaming(
+    matches: &Cli,
+    reference_track: &Track,
+    track_paths: &[PathBuf],
+    compare_options: &CompareOptions,
+    output: &mut dyn Write,
+    precision: usize,
+    skip_track_file: &mut dyn FnMut(&Path, String),
+) {
+    let origin = projection_origin(reference_track, reference_track, matches.projection_origin);
+    let projector = FlatProjection::new(origin.x(), origin.y());
+    let length_label = matches.units.length_label();
+    let length = |meters: f64| matches.units.length(meters);
+
+    let mut compared_files = 0;
+    for track_path in track_paths {
+        let mut comparison = StreamingComparison::new(reference_track, compare_options, &projector);
+        let result = if is_stdin(track_path) {
+            stream_reader(BufReader::new(std::io::stdin()), |event| {
+                comparison.handle(event)
+            })
+        } else {
+            stream_file(track_path, |event| comparison.handle(event))
+        };
+        if let Err(error) = result {
+            skip_track_file(track_path, format!("could not be read: {}", error));
+            continue;
+        }
+        compared_files += 1;
+
+        for (index, statistics) in comparison.finish().into_iter().enumerate() {
+            let track_name = statistics.track_name.as_deref().unwrap_or("-- Unnamed --");
+            if matches.json {
+                let rounded = |meters: f64| round_to_precision(length(meters), precision);
+                let streaming_output = StreamingOutput {
+                    track_index: index + 1,
+                    track_name: track_name.to_string(),
+                    track_path: track_path.display().to_string(),
+                    point_count: statistics.point_count,
+                    current_track_length_m: rounded(statistics.current_track_length_m),
+                    reference_track_length_m: rounded(statistics.reference_track_length_m),
+                    average_distance_m: statistics.average_distance_m.map(rounded),
+                    rms_distance_m: statistics.rms_distance_m.map(rounded),
+                    max_distance_m: statistics.max_distance_m.map(rounded),
+                };
+                write_output!(
+                    output,
+                    "{}",
+                    json_string_in_units(&streaming_output, matches.units, matches.pretty)
+                );
+                continue;
+            }
+
+            write_output!(
+                output,
+                "Track {}: {} ({} point(s))",
+                index + 1,
+                track_name,
+                statistics.point_count
+            );
+            write_output!(
+                output,
+                "Total length of current track: {}",
+                format!(
+                    "{:.*}{}",
+                    precision,
+                    length(statistics.current_track_length_m),
+                    length_label
+                )
+                .bold()
+            );
+            write_output!(
+                output,
+                "Total length of reference track: {}",
+                format!(
+                    "{:.*}{}",
+                    precision,
+                    length(statistics.reference_track_length_m),
+                    length_label
+                )
+                .bold()
+            );
+            let average = statistics.average_distance_m.map(length);
+            write_output!(
+                output,
+                "Average distance (in time): {} (counting every point)",
+                color_distance(
+                    matches,
+                    format_optional(average, precision, length_label),
+                    average,
+                    Some(Color::Cyan)
+                )
+                .bold()
+            );
+            write_output!(
+                output,
+                "RMS distance: {}, max distance: {}",
+                format_optional(
+                    statistics.rms_distance_m.map(length),
+                    precision,
+                    length_label
+                )
+                .bold(),
+                format_optional(
+                    statistics.max_distance_m.map(length),
+                    precision,
+                    length_label
+                )
+                .bold()
+            );
+        }
+    }
+
+    if compared_files == 0 {
+        eprintln!("None of the track paths could be read");
+        process::exit(1);
+    }
+}
+
+/// Function to compare every track to the checkpoints of the reference path instead of the reference path as a line, see --reference-as-checkpoints.
+/// - `matches`: The parsed command line arguments.
+/// - `reference_track`: The reference path, whose points are the checkpoints.
+/// - `track_files`: The compared track files.
+/// - `projector_for`: Function to get the projection used to compare a track to one of the reference paths.
+/// - `output`: The output the results are written to.
+/// - `precision`: The number of decimal places of all values.
+fn report_checkpoints(
+    matches: &Cli,
+    reference_track: &Track,
+    track_files: &TrackFiles,
+    projector_for: &dyn Fn(&Track, usize) -> FlatProjection<f64>,
+    output: &mut dyn Write,
+    precision: usize,
+) {
+    let length_label = matches.units.length_label();
+    for (gpx_index, track_gpx) in track_files.gpxs.iter().enumerate() {
+        for (cur_track_index, track) in track_gpx.tracks.iter().enumerate() {
+            let (cur_track_index, segment_index) = track_files.origins[gpx_index][cur_track_index];
+            let track_name = track.name.as_deref().unwrap_or("-- Unnamed --");
+            let statistics = checkpoint_distances(
+                reference_track,
+                track,
+                matches.distance_mode,
+                &projector_for(track, 0),
+                matches.tolerance,
+            );
+            let distance =
+                |distance: Option<f64>| distance.map(|distance| matches.units.length(distance));
+
+            if matches.json {
+                let checkpoints_output = CheckpointsOutput {
+                    track_index: cur_track_index + 1,
+                    track_name: track_name.to_string(),
+                    segment_index: segment_index.map(|index| index + 1),
+                    track_path: track_files.paths[gpx_index].display().to_string(),
+                    checkpoint_count: statistics.checkpoints.len(),
+                    tolerance_m: distance(statistics.tolerance_m)
+                        .map(|tolerance| round_to_precision(tolerance, precision)),
+                    hit_count: statistics.hit_count,
+                    checkpoints: statistics
+                        .checkpoints
+                        .iter()
+                        .enumerate()
+                        .map(|(index, checkpoint)| CheckpointOutput {
+                            checkpoint_index: index + 1,
+                            position: LatLonOutput::from(checkpoint.checkpoint),
+                            distance_m: distance(checkpoint.distance_m)
+                                .map(|distance| round_to_precision(distance, precision)),
+                            hit: statistics.tolerance_m.map(|tolerance| {
+                                checkpoint
+                                    .distance_m
+                                    .is_some_and(|distance| distance <= tolerance)
+                            }),
+                        })
+                        .collect(),
+                };
+                write_output!(
+                    output,
+                    "{}",
+                    json_string_in_units(&checkpoints_output, matches.units, matches.pretty)
+                );
+                continue;
+            }
+
+            write_output!(output, "Track {}: {}", cur_track_index + 1, track_name);
+            for (index, checkpoint) in statistics.checkpoints.iter().enumerate() {
+                let hit = match (statistics.tolerance_m, checkpoint.distance_m) {
+                    (Some(tolerance), Some(distance)) if distance <= tolerance => {
+                        format!(" {}", "(hit)".green())
+                    }
+                    (Some(_), _) => format!(" {}", "(missed)".red()),
+                    (None, _) => String::new(),
+                };
+                write_output!(
+                    output,
+                    "  Checkpoint {} ({:.7}, {:.7}): {}{}",
+                    index + 1,
+                    checkpoint.checkpoint.y(),
+                    checkpoint.checkpoint.x(),
+                    format_optional(distance(checkpoint.distance_m), precision, length_label),
+                    hit
+                );
+            }
+            if let (Some(tolerance), Some(hit_count)) =
+                (statistics.tolerance_m, statistics.hit_count)
+            {
+                write_output!(
+                    output,
+                    "Checkpoints within {:.*}{}: {} of {}",
+                    precision,
+                    matches.units.length(tolerance),
+                    length_label,
+                    format!("{}", hit_count).bold(),
+                    statistics.checkpoints.len()
+                );
+            }
+        }
+    }
+}
+
+/// Function to repeat the comparisons and report how long their phases take, see --benchmark.
+/// - `matches`: The parsed command line arguments.
+/// - `runs`: The number of times all comparisons are repeated.
+/// - `all_pairs`: Every compared track with the index of its reference path.
+/// - `reference_tracks`: The reference paths.
+/// - `compare_options`: The options used for all comparisons.
+/// - `projector_for`: Function to get the projection used to compare a track to one of the reference paths.
+/// - `output`: The output the timings are written to.
+fn run_benchmark(
+    matches: &Cli,
+    runs: u32,
+    all_pairs: &[(&Track, usize)],
+    reference_tracks: &[Track],
+    compare_options: &CompareOptions,
+    projector_for: &(dyn Fn(&Track, usize) -> FlatProjection<f64> + Sync),
+    output: &mut dyn Write,
+) {
+    // The thread pool is created once for all runs, so its setup is not part of the measured wall-clock time
+    let pool = thread_pool(matches.jobs);
+    let compare = |&(track, reference): &(&Track, usize)| {
+        compare_tracks_with_projection(
+            &reference_tracks[reference],
+            track,
+            compare_options,
+            &projector_for(track, reference),
+        )
+        .expect("The comparisons succeeded before the benchmark runs")
+        .timings
+    };
+    let mut run_timings: Vec<ComparisonTimings> = Vec::new();
+    let mut wall_times: Vec<Duration> = Vec::new();
+    for _ in 0..runs {
+        let run_start = Instant::now();
+        let timings = run_parallel(pool.as_ref(), all_pairs, compare);
+        let wall_time = run_start.elapsed();
+        // The phases of all comparisons of a run are added up
+        let total = timings
+            .iter()
+            .fold(ComparisonTimings::default(), |total, timings| {
+                ComparisonTimings {
+                    preprocessing: total.preprocessing + timings.preprocessing,
+                    projection: total.projection + timings.projection,
+                    frechet: total.frechet + timings.frechet,
+                    hausdorff: total.hausdorff + timings.hausdorff,
+                    dtw: total.dtw + timings.dtw,
+                    index: total.index + timings.index,
+                    average: total.average + timings.average,
+                    simplification: total.simplification + timings.simplification,
+                    area: total.area + timings.area,
+                }
+            });
+        run_timings.push(total);
+        wall_times.push(wall_time);
+    }
+
+    write_output!(
+        output,
+        "Benchmark of {} comparison(s) over {} run(s):",
+        all_pairs.len(),
+        runs
+    );
+    write_output!(
+        output,
+        "{:<16} {:>12} {:>12} {:>12}",
+        "phase",
+        "min",
+        "median",
+        "max"
+    );
+    let phase = |duration: fn(&ComparisonTimings) -> Duration| -> Vec<Duration> {
+        run_timings.iter().map(duration).collect()
+    };
+    // The wall-clock time of a run is less than the total of its phases if the tracks are compared in parallel
+    let rows = [
+        ("preprocessing", phase(|timings| timings.preprocessing)),
+        ("projection", phase(|timings| timings.projection)),
+        ("spatial index", phase(|timings| timings.index)),
+        ("average", phase(|timings| timings.average)),
+        ("simplification", phase(|timings| timings.simplification)),
+        ("frechet", phase(|timings| timings.frechet)),
+        ("hausdorff", phase(|timings| timings.hausdorff)),
+        ("dtw", phase(|timings| timings.dtw)),
+        ("area", phase(|timings| timings.area)),
+        ("total", phase(ComparisonTimings::total)),
+        ("wall clock", wall_times),
+    ];
+    for (phase, mut durations) in rows {
+        let (min, median, max) = duration_statistics(&mut durations);
+        write_output!(
+            output,
+            "{:<16} {:>12} {:>12} {:>12}",
+            phase,
+            format!("{:.2?}", min),
+            format!("{:.2?}", median),
+            format!("{:.2?}", max)
+        );
+    }
+}
+
+/// Function to describe the reference paths before the first track, so the JSON lines are self-contained, see --json-header.
+/// - `matches`: The parsed command line arguments.
+/// - `reference_tracks`: The reference paths.
+/// - `reference_paths`: The paths of the reference files.
+/// - `output`: The output the descriptions are written to.
+/// - `precision`: The number of decimal places of all values.
+fn write_reference_headers(
+    matches: &Cli,
+    reference_tracks: &[Track],
+    reference_paths: &[PathBuf],
+    output: &mut dyn Write,
+    precision: usize,
+) {
+    for (index, reference_track) in reference_tracks.iter().enumerate() {
+        let header = ReferenceHeaderOutput {
+            record: "reference".to_string(),
+            reference_index: index + 1,
+            reference_path: reference_paths[index].display().to_string(),
+            reference_name: reference_track.name.clone(),
+            reference_length_m: round_to_precision(
+                matches
+                    .units
+                    .length(join_segments(&reference_track.segments).haversine_length()),
+                precision,
+            ),
+            reference_point_count: reference_track
+                .segments
+                .iter()
+                .map(|segment| segment.points.len())
+                .sum(),
+            projection_origin: matches.projection_origin.to_string(),
+            projection_origin_point: match matches.projection_origin {
+                ProjectionOrigin::Reference => Some(reference_origin(reference_track)),
+                ProjectionOrigin::Both => None,
+                ProjectionOrigin::Fixed(point) => Some(point),
+            }
+            .map(LatLonOutput::from),
+            distance_mode: matches.distance_mode.to_string(),
+        };
+        write_output!(
+            output,
+            "{}",
+            json_string_in_units(&header, matches.units, matches.pretty)
+        );
+    }
+}
+
+/// Function to report the distances of every track to every reference path and the best matching one per track, see --multi-reference.
+/// - `matches`: The parsed command line arguments.
+/// - `reference_paths`: The paths of the reference files.
+/// - `track_files`: The compared track files.
+/// - `comparisons`: The comparison of track t to reference path r at index t * references + r.
+/// - `output`: The output the results are written to.
+/// - `precision`: The number of decimal places of all values.
+fn report_multi_reference(
+    matches: &Cli,
+    reference_paths: &[PathBuf],
+    track_files: &TrackFiles,
+    comparisons: &[TrackComparison],
+    output: &mut dyn Write,
+    precision: usize,
+) {
+    let reference_count = reference_paths.len();
+    // Keep track of the current index of a track
+    let mut track_index: usize = 0;
+    for (gpx_index, track_gpx) in track_files.gpxs.iter().enumerate() {
+        for (cur_track_index, track) in track_gpx.tracks.iter().enumerate() {
+            let track_comparisons =
+                &comparisons[track_index * reference_count..(track_index + 1) * reference_count];
+            track_index += 1;
+            let (cur_track_index, segment_index) = track_files.origins[gpx_index][cur_track_index];
+            let track_name = track.name.as_deref().unwrap_or("-- Unnamed --");
+
+            // Tracks without any points can not be compared, so they are skipped
+            if track_comparisons[0].point_count == 0
+                && track_comparisons[0].indeterminate_points == 0
+            {
+                print_warning!(
+                    matches.quiet,
+                    "Warning: Skipping track {} ({}) in {:?}, as it does not contain any points",
+                    cur_track_index + 1,
+                    track_name,
+                    track_files.paths[gpx_index]
+                );
+                continue;
+            }
+            for comparison in track_comparisons {
+                warn_if_downsampled(
+                    matches,
+                    comparison,
+                    track_name,
+                    &track_files.paths[gpx_index],
+                );
+                warn_if_indeterminate(
+                    matches,
+                    comparison,
+                    track_name,
+                    &track_files.paths[gpx_index],
+                );
+                warn_if_statistics_skipped(
+                    matches,
+                    comparison,
+                    track_name,
+                    &track_files.paths[gpx_index],
+                );
+            }
+
+            // The best matching reference path has the smallest Fréchet distance
+            let best_reference = track_comparisons
+                .iter()
+                .enumerate()
+                .filter_map(|(index, comparison)| {
+                    comparison
+                        .frechet_distance_m
+                        .map(|distance| (index, distance))
+                })
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(index, _)| index);
+
+            // Only the reported values are converted, the best match is found on the values in meters
+            let track_comparisons: Vec<TrackComparison> = track_comparisons
+                .iter()
+                .map(|comparison| matches.units.convert_comparison(comparison))
+                .collect();
+            let length_label = matches.units.length_label();
+
+            if matches.json {
+                let references: Vec<ComparisonOutput> = track_comparisons
+                    .iter()
+                    .enumerate()
+                    .map(|(reference, comparison)| ComparisonOutput {
+                        segment_index: segment_index.map(|index| index + 1),
+                        reference_index: Some(reference + 1),
+                        reference_path: Some(reference_paths[reference].display().to_string()),
+                        ..ComparisonOutput::new(
+                            cur_track_index + 1,
+                            track_name,
+                            comparison,
+                            precision,
+                        )
+                    })
+                    .collect();
+                let multi_reference_output = MultiReferenceOutput {
+                    track_index: cur_track_index + 1,
+                    track_name: track_name.to_string(),
+                    segment_index: segment_index.map(|index| index + 1),
+                    track_path: track_files.paths[gpx_index].display().to_string(),
+                    best_reference_index: best_reference.map(|index| index + 1),
+                    best_reference_path: best_reference
+                        .map(|index| reference_paths[index].display().to_string()),
+                    references,
+                };
+                write_output!(
+                    output,
+                    "{}",
+                    json_string_in_units(&multi_reference_output, matches.units, matches.pretty)
+                );
+            } else {
+                write_output!(output, "Track {}: {}", track_index, track_name);
+                for (reference, comparison) in track_comparisons.iter().enumerate() {
+                    let line = format!(
+                        "  Reference {} ({:?}): average distance {}, Fréchet distance {}",
+                        reference + 1,
+                        reference_paths[reference],
+                        color_distance(
+                            matches,
+                            format!(
+                                "{:.*}{}",
+                                precision, comparison.average_distance_m, length_label
+                            ),
+                            Some(comparison.average_distance_m),
+                            Some(Color::Cyan)
+                        ),
+                        color_distance(
+                            matches,
+                            format_optional(comparison.frechet_distance_m, precision, length_label),
+                            comparison.frechet_distance_m,
+                            Some(Color::Magenta)
+                        )
+                    );
+                    let line = if comparison.reversed {
+                        format!("{} (reversed)", line)
+                    } else {
+                        line
+                    };
+                    if best_reference == Some(reference) {
+                        write_output!(output, "{} {}", line, "(best match)".green().bold());
+                    } else {
+                        write_output!(output, "{}", line);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Function to write the human-readable report of the comparison of a track.
+/// - `matches`: The parsed command line arguments.
+/// - `comparison`: The comparison of the track, converted to the units of the output.
+/// - `output`: The output the report is written to.
+/// - `precision`: The number of decimal places of all values.
+fn write_comparison(
+    matches: &Cli,
+    comparison: &TrackComparison,
+    output: &mut dyn Write,
+    precision: usize,
+) {
+    let length_label = matches.units.length_label();
+    if let Some(segment_index) = comparison.reference_segment_index {
+        write_output!(
+            output,
+            "Best matching reference segment: {}",
+            segment_index.to_string().bold()
+        );
+    }
+    if comparison.reversed {
+        write_output!(
+            output,
+            "{}",
+            "The track runs the reference path backwards, it was compared in reverse".yellow()
+        );
+    }
+    if let Some(offset) = comparison.track_offset {
+        write_output!(
+            output,
+            "Removed offset: {} north, {} east{}",
+            format!("{:.*}{}", precision, offset.north_m, length_label).bold(),
+            format!("{:.*}{}", precision, offset.east_m, length_label).bold(),
+            if matches.auto_offset {
+                " (estimated)"
+            } else {
+                ""
+            }
+        );
+    }
+
+    // Print the lengths of the tracks
+    write_output!(
+        output,
+        "Total length of current track: {}",
+        format!(
+            "{:.*}{}",
+            precision, comparison.current_track_length_m, length_label
+        )
+        .bold()
+    );
+    write_output!(
+        output,
+        "Total length of reference track: {}",
+        format!(
+            "{:.*}{}",
+            precision, comparison.reference_track_length_m, length_label
+        )
+        .bold()
+    );
+    explain!(output, matches, comparison, "lengths");
+    write_output!(
+        output,
+        "Average distance (in time): {} (counting every point)",
+        color_distance(
+            matches,
+            format!(
+                "{:.*}{}",
+                precision, comparison.average_distance_m, length_label
+            ),
+            Some(comparison.average_distance_m),
+            Some(Color::Cyan)
+        )
+        .bold()
+    );
+    explain!(output, matches, comparison, "average");
+    write_output!(
+        output,
+        "Average distance (location dependent): {} (counting only simplified points)",
+        color_distance(
+            matches,
+            format!(
+                "{:.*}{}",
+                precision, comparison.simplified_average_distance_m, length_label
+            ),
+            Some(comparison.simplified_average_distance_m),
+            Some(Color::Yellow)
+        )
+        .bold()
+    );
+    if let Some(fraction) = comparison.simplified_point_fraction() {
+        write_output!(
+            output,
+            "Simplification kept {} of {} points ({:.1}%)",
+            comparison.simplified_point_count,
+            comparison.point_count,
+            fraction * 100.0
+        );
+    }
+    explain!(output, matches, comparison, "simplified");
+    write_output!(
+        output,
+        "RMS distance: {} (root mean square of the distances of every point)",
+        color_distance(
+            matches,
+            format!(
+                "{:.*}{}",
+                precision, comparison.rms_distance_m, length_label
+            ),
+            Some(comparison.rms_distance_m),
+            None
+        )
+        .bold()
+    );
+    explain!(output, matches, comparison, "rms");
+    if matches.symmetric {
+        write_output!(
+                output,
+                "Average distance (reverse): {} (counting every point of the reference path), symmetric average: {}",
+                color_distance(
+                    matches,
+                    format_optional(comparison.average_distance_reverse_m, precision, length_label),
+                    comparison.average_distance_reverse_m,
+                    None
+                )
+                .bold(),
+                color_distance(
+                    matches,
+                    format_optional(comparison.average_distance_symmetric_m, precision, length_label),
+                    comparison.average_distance_symmetric_m,
+                    Some(Color::Yellow)
+                )
+                .bold()
+            );
+    }
+    if matches.weight_by_hdop {
+        write_output!(
+            output,
+            "Average distance (weighted by HDOP): {} (unweighted: {})",
+            color_distance(
+                matches,
+                format_optional(
+                    comparison.weighted_average_distance_m,
+                    precision,
+                    length_label
+                ),
+                comparison.weighted_average_distance_m,
+                Some(Color::Cyan)
+            )
+            .bold(),
+            format!(
+                "{:.*}{}",
+                precision, comparison.average_distance_m, length_label
+            )
+        );
+    }
+    write_output!(
+        output,
+        "Distance percentiles: {} (median), {} (p90), {} (p95)",
+        color_distance(
+            matches,
+            format!(
+                "{:.*}{}",
+                precision, comparison.median_distance_m, length_label
+            ),
+            Some(comparison.median_distance_m),
+            None
+        )
+        .bold(),
+        color_distance(
+            matches,
+            format!(
+                "{:.*}{}",
+                precision, comparison.p90_distance_m, length_label
+            ),
+            Some(comparison.p90_distance_m),
+            None
+        )
+        .bold(),
+        color_distance(
+            matches,
+            format!(
+                "{:.*}{}",
+                precision, comparison.p95_distance_m, length_label
+            ),
+            Some(comparison.p95_distance_m),
+            None
+        )
+        .bold()
+    );
+    explain!(output, matches, comparison, "percentiles");
+    write_output!(
+        output,
+        "Maximum distance: {} (at point {})",
+        color_distance(
+            matches,
+            format!(
+                "{:.*}{}",
+                precision, comparison.max_distance_m, length_label
+            ),
+            Some(comparison.max_distance_m),
+            Some(Color::Red)
+        )
+        .bold(),
+        comparison
+            .max_distance_point_index
+            .map_or("-".to_string(), |index| index.to_string())
+    );
+    explain!(output, matches, comparison, "max");
+    if comparison.start_offset_m.is_some() {
+        // A reversed track starts at the end of the reference path, so its start is also compared to the other end
+        write_output!(
+            output,
+            "Start offset: {}, end offset: {} (start to reference {}: {})",
+            format_optional(comparison.start_offset_m, precision, length_label).bold(),
+            format_optional(comparison.end_offset_m, precision, length_label).bold(),
+            if comparison.reversed { "start" } else { "end" },
+            format_optional(comparison.start_to_end_offset_m, precision, length_label)
+        );
+        explain!(output, matches, comparison, "endpoints");
+    }
+    write_output!(
+            output,
+            "Mean signed offset: {} (positive = right of the reference path), {} of the points right of the reference path",
+            format!("{:.*}{}", precision, comparison.mean_signed_offset_m, length_label).bold(),
+            format!("{:.1}%", comparison.fraction_right * 100.0).bold()
+        );
+    explain!(output, matches, comparison, "offset");
+    write_output!(
+            output,
+            "Mean cross-track error: {}, mean along-track error: {} (positive = ahead of the reference path)",
+            format!("{:.*}{}", precision, comparison.mean_cross_track_m, length_label).bold(),
+            format!("{:.*}{}", precision, comparison.mean_along_track_m, length_label).bold()
+        );
+    explain!(output, matches, comparison, "track-error");
+    write_output!(
+        output,
+        "Fréchet distance: {}{}",
+        color_distance(
+            matches,
+            format_optional(comparison.frechet_distance_m, precision, length_label),
+            comparison.frechet_distance_m,
+            Some(Color::Magenta)
+        )
+        .bold(),
+        comparison
+            .frechet_normalized
+            .map_or(String::new(), |ratio| format!(
+                " ({:.*}% of the reference path length)",
+                precision,
+                ratio * 100.0
+            ))
+    );
+    if let (Some(track_point), Some(reference_point)) = (
+        comparison.frechet_track_point,
+        comparison.frechet_reference_point,
+    ) {
+        write_output!(
+                output,
+                "  Longest Fréchet leash: track point {:.7}, {:.7} -> reference point {:.7}, {:.7} (lat, lon)",
+                track_point.y(),
+                track_point.x(),
+                reference_point.y(),
+                reference_point.x()
+            );
+    }
+    explain!(output, matches, comparison, "frechet");
+    write_output!(
+        output,
+        "Hausdorff distance: {}",
+        color_distance(
+            matches,
+            format_optional(comparison.hausdorff_distance_m, precision, length_label),
+            comparison.hausdorff_distance_m,
+            Some(Color::Green)
+        )
+        .bold()
+    );
+    if let (Some(track_to_reference), Some(reference_to_track), Some(direction)) = (
+        comparison.hausdorff_track_to_reference_m,
+        comparison.hausdorff_reference_to_track_m,
+        comparison.hausdorff_direction(),
+    ) {
+        write_output!(
+                output,
+                "  Directed: track -> reference {:.precision$}{}, reference -> track {:.precision$}{} ({})",
+                track_to_reference,
+                length_label,
+                reference_to_track,
+                length_label,
+                match direction {
+                    HausdorffDirection::TrackToReference => "the track has the outlier",
+                    HausdorffDirection::ReferenceToTrack => "the reference path has the outlier",
+                    HausdorffDirection::Equal => "both directions are equal",
+                }
+            );
+    }
+    explain!(output, matches, comparison, "hausdorff");
+    write_output!(
+        output,
+        "DTW distance: {}",
+        // The DTW distance is a sum over all matched pairs of points, so it is not colored by the severity thresholds
+        format_optional(comparison.dtw_distance_m, precision, length_label)
+            .blue()
+            .bold()
+    );
+    explain!(output, matches, comparison, "dtw");
+    write_output!(
+        output,
+        "Area between tracks: {}",
+        format_optional(
+            comparison.area_between_m2,
+            precision,
+            matches.units.area_label()
+        )
+        .bold()
+    );
+    explain!(output, matches, comparison, "area");
+    if let Some(score) = comparison.match_score {
+        write_output!(
+            output,
+            "Match score: {} of 100",
+            format!("{:.1}", score).bold()
+        );
+    }
+    explain!(output, matches, comparison, "score");
+    if let Some(profile) = &comparison.elevation_profile {
+        write_output!(
+            output,
+            "Elevation profile: {} mean difference, ascent {} (reference: {}), descent {} (reference: {})",
+            format_optional(profile.mean_elevation_diff_m, precision, length_label).bold(),
+            format!("{:.*}{}", precision, profile.track_ascent_m, length_label).bold(),
+            format!("{:.*}{}", precision, profile.reference_ascent_m, length_label),
+            format!("{:.*}{}", precision, profile.track_descent_m, length_label),
+            format!("{:.*}{}", precision, profile.reference_descent_m, length_label)
+        );
+        explain!(output, matches, comparison, "elevation");
+    }
+    if let Some(time_matched) = &comparison.time_matched_distance {
+        write_output!(
+            output,
+            "Time matched distance: {} average, {} maximum ({} points matched by time)",
+            format!(
+                "{:.*}{}",
+                precision, time_matched.average_distance_m, length_label
+            )
+            .bold(),
+            format!(
+                "{:.*}{}",
+                precision, time_matched.max_distance_m, length_label
+            ),
+            time_matched.matched_points
+        );
+        explain!(output, matches, comparison, "time-matched");
+    }
+    if let Some(tolerance) = &comparison.tolerance {
+        write_output!(
+            output,
+            "Within tolerance of {}{}: {} of the points, longest run outside: {} points ({})",
+            round_to_precision(tolerance.tolerance_m, precision),
+            length_label,
+            format!("{:.1}%", tolerance.within_tolerance_fraction * 100.0).bold(),
+            tolerance.longest_out_of_tolerance_points,
+            format!(
+                "{:.*}{}",
+                precision, tolerance.longest_out_of_tolerance_m, length_label
+            )
+            .bold()
+        );
+    }
+    if let Some(corridor) = &comparison.corridor {
+        write_output!(
+            output,
+            "Corridor of {}{}: {} of the track length inside, {} outside (area outside: {})",
+            round_to_precision(corridor.width_m, precision),
+            length_label,
+            format!("{:.1}%", corridor.inside_length_fraction * 100.0).bold(),
+            format!(
+                "{:.*}{}",
+                precision, corridor.outside_length_m, length_label
+            )
+            .bold(),
+            format!(
+                "{:.*}{}",
+                precision,
+                corridor.outside_area_m2,
+                matches.units.area_label()
+            )
+        );
+    }
+    if let Some(statistics) = &comparison.time_statistics {
+        write_output!(
+            output,
+            "Duration: {}, speed: {} average (min: {}, max: {})",
+            format_duration(statistics.duration_s).bold(),
+            format!("{:.*}m/s", precision, statistics.average_speed_mps).bold(),
+            format!("{:.*}m/s", precision, statistics.min_speed_mps),
+            format!("{:.*}m/s", precision, statistics.max_speed_mps)
+        );
+    }
+
+    // Print the table of the epsilon sweep
+    if !comparison.epsilon_sweep.is_empty() {
+        write_output!(output, "Epsilon sweep:");
+        write_output!(
+            output,
+            "{:>12}  {:>20}  {:>8}",
+            "epsilon",
+            "simplified average",
+            "points"
+        );
+        for entry in &comparison.epsilon_sweep {
+            // The epsilons of the Visvalingam-Whyatt algorithm are areas, which were converted like lengths
+            let epsilon = match matches.simplify_algo {
+                SimplifyAlgorithm::Rdp => {
+                    format!("{:.*}{}", precision, entry.epsilon_m, length_label)
+                }
+                SimplifyAlgorithm::Visvalingam => format!(
+                    "{:.*}{}",
+                    precision,
+                    matches
+                        .units
+                        .area(entry.epsilon_m / matches.units.length(1.0)),
+                    matches.units.area_label()
+                ),
+            };
+            write_output!(
+                output,
+                "{:>12}  {:>20}  {:>8}",
+                epsilon,
+                format!(
+                    "{:.*}{}",
+                    precision, entry.simplified_average_distance_m, length_label
+                ),
+                entry.simplified_point_count
+            );
+        }
+    }
+
+    // Print the distance profile along the track
+    if !comparison.distance_windows.is_empty() {
+        write_output!(output, "Distance profile:");
+        write_output!(
+            output,
+            "{:>25}  {:>12}  {:>12}",
+            "window",
+            "average",
+            "maximum"
+        );
+        for window in &comparison.distance_windows {
+            write_output!(
+                output,
+                "{:>25}  {:>12}  {:>12}",
+                format!(
+                    "{:.0}{} - {:.0}{}",
+                    window.start_m, length_label, window.end_m, length_label
+                ),
+                format!("{:.*}{}", precision, window.average_m, length_label),
+                format!("{:.*}{}", precision, window.max_m, length_label)
+            );
+        }
+    }
+}
+
+/// Function to reexport the simplified and the smoothed tracks next to their track files, see --export-track and --export-smoothed.
+/// - `matches`: The parsed command line arguments.
+/// - `track_files`: The compared track files.
+/// - `track_gpx_copies`: The track files with the simplified tracks.
+/// - `exported_metrics`: The distances of every simplified track, which are stored in the extensions of the track.
+/// - `projector_for`: Function to get the projection used to compare a track to one of the reference paths.
+fn export_track_files(
+    matches: &Cli,
+    track_files: &TrackFiles,
+    track_gpx_copies: &[Gpx],
+    exported_metrics: &[Vec<Option<ExportedMetrics>>],
+    projector_for: &dyn Fn(&Track, usize) -> FlatProjection<f64>,
+) {
+    let suppress_info = matches.json || matches.csv || matches.quiet;
+    for (gpx_index, track_gpx) in track_files.gpxs.iter().enumerate() {
+        // If we want to reexport the GPX files, do it now by writing the modified GPX file to the same path, adding .modified before the extension
+        // The smoothed tracks are exported the same way, adding .smoothed before the extension
+        let smoothed_gpx = matches.export_smoothed.then(|| {
+            let mut smoothed_gpx = track_gpx.clone();
+            for track in &mut smoothed_gpx.tracks {
+                *track = smooth_track(
+                    track,
+                    matches.smooth,
+                    matches.smooth_window,
+                    &projector_for(track, 0),
+                );
+            }
+            smoothed_gpx
+        });
+        let exports = [
+            (
+                "modified",
+                matches.export_track.then_some(&track_gpx_copies[gpx_index]),
+            ),
+            ("smoothed", smoothed_gpx.as_ref()),
+        ];
+        for (kind, export_gpx) in exports {
+            let Some(export_gpx) = export_gpx else {
+                continue;
+            };
+            if is_stdin(&track_files.paths[gpx_index]) {
+                print_info!(
+                    suppress_info,
+                    "Skipping the export of the {} track read from the standard input, as there is no path to export it next to",
+                    kind
+                );
+                continue;
+            }
+
+            let track_path = track_files.paths[gpx_index].clone();
+            // For compressed tracks the `.gz` extension is dropped as well, the export is never compressed
+            let mut export_path = if is_gzip_path(&track_path) {
+                track_path.with_extension("")
+            } else {
+                track_path.clone()
+            };
+            export_path.set_extension(format!("{}.gpx", kind));
+            let mut content = Vec::new();
+            write(export_gpx, &mut content).expect("Failed to write exported track file");
+            // The reexported tracks carry their distances in their extensions, so they can be recovered from the file
+            if kind == "modified" {
+                content = add_metrics_extensions(
+                    &String::from_utf8(content).expect("The GPX writer writes UTF-8"),
+                    &exported_metrics[gpx_index],
+                )
+                .into_bytes();
+            }
+            fs::write(&export_path, content).expect("Failed to write exported track file");
+            print_info!(
+                suppress_info,
+                "Exported {} track file to {:?}",
+                kind,
+                &export_path
+            );
+        }
+    }
+}
+
+/// Function to report all tracks which exceeded a threshold, on stderr so the output of the tracks stays intact.
+/// - `matches`: The parsed command line arguments.
+/// - `threshold_failures`: The metrics exceeding their threshold.
+/// - `precision`: The number of decimal places of all values.
+fn report_threshold_failures(
+    matches: &Cli,
+    threshold_failures: &[ThresholdFailure],
+    precision: usize,
+) {
+    if matches.json {
+        eprintln!(
+            "{}",
+            serde_json::json!({
+                "threshold_failures": threshold_failures
+                    .iter()
+                    .map(|failure| failure.json(precision))
+                    .collect::<Vec<_>>(),
+            })
+        );
+    } else {
+        for failure in threshold_failures {
+            eprintln!(
+                "Track {} ({}) in {:?} exceeds the threshold of {}: {:.*}m > {:.*}m (by {:.*}m)",
+                failure.track_index,
+                failure.track_name,
+                failure.track_path,
+                failure.metric,
+                precision,
+                failure.value,
+                precision,
+                failure.threshold,
+                precision,
+                failure.value - failure.threshold
+            );
+        }
+    }
+}
+
+/// Function to get the minimum, median and maximum of durations, e.g. of the runs of a benchmark.
+/// - `durations`: The durations, which are sorted in place. Must not be empty.
+/// - Returns: The minimum, the median (the mean of both middle values for an even count) and the maximum.
+fn duration_statistics(durations: &mut [Duration]) -> (Duration, Duration, Duration) {
+    durations.sort();
+    let middle = durations.len() / 2;
+    let median = if durations.len().is_multiple_of(2) {
+        (durations[middle - 1] + durations[middle]) / 2
+    } else {
+        durations[middle]
+    };
+    (durations[0], median, durations[durations.len() - 1])
+}
+
+/// Function to convert the entries of a config file into command line arguments, leaving out the options given on the command line.
+/// - `command`: The built command with all options.
+/// - `cli_matches`: The options given on the command line.
+/// - `config`: The entries of the config file.
+/// - Returns: The command line arguments of the config file, or an error message.
+fn config_arguments(
+    command: &Command,
+    cli_matches: &ArgMatches,
+    config: &[(String, ConfigValue)],
+) -> Result<Vec<OsString>, String> {
+    let given_arguments: Vec<_> = command
+        .get_arguments()
+        .filter(|argument| {
+            cli_matches.value_source(argument.get_id().as_str()) == Some(ValueSource::CommandLine)
+        })
+        .collect();
+
+    let mut arguments = Vec::new();
+    for (name, value) in config {
+        // Both the name of the option and the name of the field in the JSON output are accepted, e.g. simplify-epsilon and simplify_epsilon
+        let long = name.replace('_', "-");
+        let Some(argument) = command
+            .get_arguments()
+            .find(|argument| argument.get_long() == Some(long.as_str()))
+        else {
+            return Err(format!("unknown option {}", name));
+        };
+        if matches!(long.as_str(), "config" | "help" | "version") {
+            return Err(format!("--{} can not be set in a config file", long));
+        }
+
+        // Options given on the command line take precedence, which also applies to the options conflicting with them
+        let overridden = given_arguments.iter().any(|given| {
+            given.get_id() == argument.get_id()
+                || command
+                    .get_arg_conflicts_with(given)
+                    .iter()
+                    .any(|conflict| conflict.get_id() == argument.get_id())
+                || command
+                    .get_arg_conflicts_with(argument)
+                    .iter()
+                    .any(|conflict| conflict.get_id() == given.get_id())
+        });
+        if overridden {
+            continue;
+        }
+
+        match (argument.get_action(), value) {
+            (ArgAction::SetTrue, ConfigValue::Scalar(toggle)) => match toggle.as_str() {
+                "true" => arguments.push(OsString::from(format!("--{}", long))),
+                "false" => {}
+                _ => return Err(format!("{} has to be true or false", name)),
+            },
+            (ArgAction::SetTrue, ConfigValue::List(_)) => {
+                return Err(format!("{} has to be true or false", name))
+            }
+            (_, ConfigValue::Scalar(value)) => {
+                arguments.push(OsString::from(format!("--{}={}", long, value)))
+            }
+            // Options which can be given multiple times get every item separately, all others get the comma separated list
+            (ArgAction::Append, ConfigValue::List(items)) => arguments.extend(
+                items
+                    .iter()
+                    .map(|item| OsString::from(format!("--{}={}", long, item))),
+            ),
+            (_, ConfigValue::List(items)) => {
+                arguments.push(OsString::from(format!("--{}={}", long, items.join(","))))
+            }
+        }
+    }
+    Ok(arguments)
+}
+
+/// Function to parse the command line arguments, using the entries of the config file given with --config as defaults.
+/// - Returns: The parsed arguments.
+fn parse_cli_with_config() -> Cli {
+    let arguments: Vec<OsString> = std::env::args_os().collect();
+    let mut command = Cli::command();
+    command.build();
+
+    // The command line is parsed leniently first, as required options may only be given in the config file
+    // Errors (and --help or --version) are reported by the final parsing
+    let Ok(cli_matches) = command
+        .clone()
+        .ignore_errors(true)
+        .try_get_matches_from(&arguments)
+    else {
+        return Cli::parse_from(arguments);
+    };
+    let Some(config_path) = cli_matches.get_one::<PathBuf>("config") else {
+        return Cli::parse_from(arguments);
+    };
+
+    let config = match fs::read_to_string(config_path)
+        .map_err(|error| error.to_string())
+        .and_then(|content| parse_config(&content))
+        .and_then(|config| config_arguments(&command, &cli_matches, &config))
+    {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!(
+                "Failed to read the config file {:?}: {}",
+                config_path, error
+            );
+            process::exit(1);
+        }
+    };
+
+    // The options of the config file are inserted right after the name of the application
+    let (application, cli_arguments) = arguments
+        .split_first()
+        .expect("The name of the application is always given");
+    Cli::parse_from(
+        std::iter::once(application.clone())
+            .chain(config)
+            .chain(cli_arguments.iter().cloned()),
+    )
+}
+
+fn main() {
+    #[cfg(windows)]
+    {
+        let _ = colored::control::set_virtual_terminal(true).unwrap_or(());
+    }
+
+    // Parse the command line arguments, together with the defaults of the config file
+    let matches = parse_cli_with_config();
+    let start = Instant::now();
+
+    // The diagnostics of the binary and the library are printed by the logger, see --debug and RUST_LOG
+    let logger = CliLogger::new(
+        std::env::var("RUST_LOG").ok().as_deref(),
+        matches.debug,
+        matches.json || matches.csv,
+    );
+    log::set_max_level(logger.max_level());
+    if log::set_boxed_logger(Box::new(logger)).is_err() {
+        eprintln!("Failed to install the logger");
+        process::exit(1);
+    }
+
+    // Error and exit if both debug and json are enabled
+    if matches.debug && matches.json {
+        eprintln!("Both debug and json flags are enabled. Please only enable one of them.");
+        process::exit(1);
+    }
+
+    // Error and exit if csv is enabled together with debug or json
+    if matches.csv && (matches.debug || matches.json) {
+        eprintln!("The csv flag can not be combined with the debug or json flags. Please only enable one of them.");
+        process::exit(1);
+    }
+
+    // The UTM projection replaces the flat projection, the geodesic mode does not project at all
+    if matches.projection == Projection::Utm && matches.distance_mode == DistanceMode::Geodesic {
+        eprintln!("The utm projection can only be used with --distance-mode flat, the geodesic mode does not project the points.");
+        process::exit(1);
+    }
+
+    // Informational output is suppressed whenever the output should be machine readable
+    let machine_readable = matches.json || matches.csv;
+    // Additionally, informational output and warnings can be suppressed explicitly, e.g. for scripted runs
+    let suppress_info = machine_readable || matches.quiet;
+
+    // Number of decimal places of all values in the output
+    let precision = matches.precision as usize;
+
+    // The results are written either to the console or to the given output file
+    let mut output_has_content = false;
+    let mut output: Box<dyn Write> = match &matches.output {
+        Some(output_path) => {
+            // Remember whether the file already has content, so the CSV header is not repeated when appending
+            output_has_content = matches.append
+                && fs::metadata(output_path).is_ok_and(|metadata| metadata.len() > 0);
+
+            match OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(matches.append)
+                .truncate(!matches.append)
+                .open(output_path)
+            {
+                Ok(file) => {
+                    // Colors would only end up as escape codes in the file
+                    colored::control::set_override(false);
+                    Box::new(BufWriter::new(file))
+                }
+                Err(error) => {
+                    eprintln!(
+                        "Failed to open the output file {:?}: {}",
+                        output_path, error
+                    );
+                    process::exit(1);
+                }
+            }
+        }
+        None => Box::new(std::io::stdout()),
+    };
+
+    let compare_options = CompareOptions::builder()
+        .simplify_epsilon(matches.simplify_epsilon)
+        .simplify_algorithm(matches.simplify_algo)
+        .dtw_window(matches.dtw_window)
+        .max_points(matches.max_points)
+        .exclude_segment_gaps(matches.exclude_segment_gaps)
+        .epsilon_sweep(
+            matches
+                .epsilon_sweep
+                .map(|sweep| sweep.epsilons())
+                .unwrap_or_default(),
+        )
+        // The pairs are printed in debug mode, unless they are written to a file
+        .collect_nearest_points(
+            matches.export_geojson_nearest
+                || matches.debug_pairs.is_some()
+                || matches.export_point_distances.is_some()
+                || log::log_enabled!(log::Level::Debug),
+        )
+        .distance_mode(matches.distance_mode)
+        .use_elevation(matches.use_elevation)
+        .resample(matches.resample)
+        .smoothing(matches.smooth)
+        .smooth_window(matches.smooth_window)
+        .tolerance(matches.tolerance)
+        .corridor_width(matches.corridor_width)
+        .window_report(matches.window_report)
+        .symmetric(matches.symmetric)
+        .weight_by_hdop(matches.weight_by_hdop)
+        .time_stats(matches.time_stats)
+        .elevation_profile(matches.elevation_profile)
+        .match_by_time(matches.match_by_time)
+        .auto_orient(matches.auto_orient)
+        .reference_segments(matches.reference_segments)
+        .projection_origin(matches.projection_origin)
+        .projection(matches.projection)
+        .start_trim(matches.start_offset)
+        .end_trim(matches.end_trim)
+        .track_offset(matches.track_offset)
+        .score_weights(matches.score_weights)
+        .auto_offset(matches.auto_offset)
+        .build();
 
-// Add a macro to print out the debug information
-// Make the color green and prefix it with [DEBUG]
-macro_rules! debug_print {
-    ($debug_enabled:expr, $($arg:tt)*) => {
-        if $debug_enabled {
-            println!("{} {}", "[DEBUG]".green(), format!($($arg)*));
+    // In the server mode, the files are given by the requests on the standard input instead of the command line
+    if matches.serve {
+        let stdin = std::io::stdin();
+        if let Err(error) = serve(stdin.lock(), std::io::stdout(), &compare_options, precision) {
+            eprintln!("Failed to serve the requests: {}", error);
+            process::exit(1);
         }
+        return;
     }
-}
 
-// Macro to only print if the json flag is not set
-macro_rules! print_info {
-    ($json_enabled:expr, $($arg:tt)*) => {
-        if !$json_enabled {
-            println!($($arg)*);
+    // Run all jobs of the manifest instead of comparing the given files
+    if let Some(manifest_path) = &matches.manifest {
+        let success = run_manifest(
+            &matches,
+            manifest_path,
+            &compare_options,
+            &mut output,
+            precision,
+        );
+        flush_output(&mut output);
+        if !success {
+            process::exit(3);
         }
+        return;
     }
-}
 
-fn main() {
-    #[cfg(windows)]
-    {
-        let _ = colored::control::set_virtual_terminal(true).unwrap_or(());
+    // Compare all pairs of files with the same name in both directories instead of the given files
+    if matches.reference_dir.is_some() {
+        compare_directories(
+            &matches,
+            &compare_options,
+            &mut output,
+            precision,
+            output_has_content,
+        );
+        flush_output(&mut output);
+        return;
+    }
+
+    // An inline reference path has no file, its option is shown instead of a path in all messages
+    let reference_paths: Vec<PathBuf> = match (
+        &matches.reference_inline,
+        &matches.reference_from_track,
+    ) {
+        (Some(_), _) => vec![PathBuf::from("--reference-inline")],
+        // The reference path is taken from the first track file, which has to be read a second time
+        (None, Some(_)) => {
+            if is_stdin(&matches.track[0]) {
+                eprintln!("The reference path of --reference-from-track can not be read from the standard input, as it is also read as track file. Please save it to a file first.");
+                process::exit(1);
+            }
+            vec![matches.track[0].clone()]
+        }
+        (None, None) => matches.reference.clone(),
+    };
+
+    // Only list the content of the reference files, e.g. to find the index of a track for --reference-track
+    if matches.list_tracks {
+        list_tracks(&matches, &reference_paths, &mut output, precision);
+        flush_output(&mut output);
+        return;
     }
 
-    // Parse the command line arguments
-    let matches = Cli::parse();
+    // Only check the files, e.g. before starting a long batch of comparisons
+    if matches.validate {
+        let all_valid = validate_files(&matches, &mut output);
+        flush_output(&mut output);
+        if !all_valid {
+            process::exit(1);
+        }
+        return;
+    }
 
-    // Error and exit if both debug and json are enabled
-    if matches.debug && matches.json {
-        eprintln!("Both debug and json flags are enabled. Please only enable one of them.");
+    // Without --multi-reference, exactly one reference path is compared
+    if !matches.multi_reference && reference_paths.len() > 1 {
+        eprintln!("Multiple reference paths are only supported together with --multi-reference");
         process::exit(1);
     }
 
-    let reference_path: PathBuf = matches.reference;
-
     // Generate a path buffer from the input strings
-    let track_paths: Vec<PathBuf> = matches.track.iter().map(|s| PathBuf::from(s)).collect();
+    let mut track_paths: Vec<PathBuf> = matches.track.iter().map(PathBuf::from).collect();
 
-    debug_print!(matches.debug, "Debugging is enabled");
-    debug_print!(matches.debug, "Reference path: {:?}", reference_path);
-    debug_print!(matches.debug, "Track paths: {:?}", track_paths);
-    debug_print!(
-        matches.debug,
-        "Simplify epsilon: {}",
-        matches.simplify_epsilon
+    log::debug!("Debugging is enabled");
+    log::debug!("Reference paths: {:?}", reference_paths);
+    log::debug!("Track paths: {:?}", track_paths);
+    log::debug!(
+        "Simplify epsilon: {} ({})",
+        matches.simplify_epsilon,
+        matches.simplify_algo
     );
 
     // Check that all passed paths exist and are files
-    if !reference_path.exists() {
-        eprintln!("The reference path {:?} does not exist", reference_path);
-        process::exit(1);
+    for reference_path in &matches.reference {
+        if !reference_path.exists() {
+            eprintln!("The reference path {:?} does not exist", reference_path);
+            process::exit(1);
+        }
+        if !reference_path.is_file() {
+            eprintln!("The reference path {:?} is not a file", reference_path);
+            process::exit(1);
+        }
     }
-    if !reference_path.is_file() {
-        eprintln!("The reference path {:?} is not a file", reference_path);
+
+    // The standard input can only be read once
+    if track_paths.iter().filter(|path| is_stdin(path)).count() > 1 {
+        eprintln!("The standard input (-) can only be given once as track path");
         process::exit(1);
     }
 
-    for track_path in &track_paths {
-        if !track_path.exists() {
-            eprintln!("The track path {:?} does not exist", track_path);
+    // Track files which can not be read are skipped (and listed at the end), unless the run should be aborted
+    let track_file_count = track_paths.len();
+    let mut skipped_track_files: Vec<(PathBuf, String)> = Vec::new();
+    let mut skip_track_file = |track_path: &Path, reason: String| {
+        if matches.fail_fast {
+            eprintln!("The track path {:?} {}", track_path, reason);
             process::exit(1);
         }
-        if !track_path.is_file() {
-            eprintln!("The track path {:?} is not a file", track_path);
+        print_warning!(
+            matches.quiet,
+            "Warning: Skipping the track path {:?}, as it {}",
+            track_path,
+            reason
+        );
+        skipped_track_files.push((track_path.to_path_buf(), reason));
+    };
+
+    track_paths.retain(|track_path| {
+        if is_stdin(track_path) {
+            true
+        } else if !track_path.exists() {
+            skip_track_file(track_path, "does not exist".to_string());
+            false
+        } else if !track_path.is_file() {
+            skip_track_file(track_path, "is not a file".to_string());
+            false
+        } else {
+            true
+        }
+    });
+
+    // Read in the reference paths, converting them to GPX if they are in another supported format
+    let parsing_start = Instant::now();
+    let reference_gpxs: Vec<Gpx> = reference_paths
+        .iter()
+        .map(|reference_path| {
+            if let Some(index_path) = &matches.load_reference_index {
+                return load_index(reference_path, index_path);
+            }
+            match matches.reference_inline.as_ref().map_or_else(
+                || read_file(reference_path),
+                |coordinates| read_inline(coordinates),
+            ) {
+                Ok(gpx) => gpx,
+                Err(error) => {
+                    eprintln!(
+                        "Failed to read the reference path {:?}: {}",
+                        reference_path, error
+                    );
+                    process::exit(1);
+                }
+            }
+        })
+        .collect();
+
+    if let Some(index_path) = &matches.save_reference_index {
+        let result = fs::read(&reference_paths[0])
+            .map_err(|error| error.to_string())
+            .and_then(|data| {
+                save_reference_index(index_path, &data, &reference_gpxs[0])
+                    .map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            eprintln!(
+                "Failed to save the reference index {:?}: {}",
+                index_path, error
+            );
             process::exit(1);
         }
+        print_info!(
+            suppress_info,
+            "Saved the reference index of {:?} to {:?}",
+            reference_paths[0],
+            index_path
+        );
     }
 
-    // Read in the reference path as a GPX file
-    let reference_file = File::open(reference_path).expect("Failed to open reference path");
-    let reference_reader = BufReader::new(reference_file);
-    let reference_gpx: Gpx = read(reference_reader).expect("Failed to read reference path as GPX");
+    // Compare the tracks while they are read, without ever loading a whole track file into memory
+    if matches.streaming {
+        if matches.projection_origin == ProjectionOrigin::Both {
+            eprintln!("The projection origin auto-both can not be used with --streaming, as the tracks are not known before they are compared.");
+            process::exit(1);
+        }
+        let reference_track = select_reference_track(
+            &reference_gpxs[0],
+            &reference_paths[0],
+            matches.reference_track.as_ref(),
+            suppress_info,
+        )
+        .unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1)
+        });
+        compare_streaming(
+            &matches,
+            &reference_track,
+            &track_paths,
+            &compare_options,
+            &mut output,
+            precision,
+            &mut skip_track_file,
+        );
+        flush_output(&mut output);
+        return;
+    }
 
-    // Read in the track paths as GPX files
+    // Read in the track paths
     let mut track_gpxs: Vec<Gpx> = Vec::new();
 
-    for track_path in &track_paths {
-        let track_file = File::open(track_path).expect("Failed to open track path");
-        let track_reader = BufReader::new(track_file);
-        let track_gpx: Gpx = read(track_reader).expect("Failed to read track path as GPX");
-        track_gpxs.push(track_gpx);
+    track_paths.retain(|track_path| {
+        let result = if is_stdin(track_path) {
+            read_reader(BufReader::new(std::io::stdin()))
+        } else {
+            read_file(track_path)
+        };
+        match result {
+            Ok(track_gpx) => {
+                track_gpxs.push(track_gpx);
+                true
+            }
+            Err(error) => {
+                skip_track_file(track_path, format!("could not be read: {}", error));
+                false
+            }
+        }
+    });
+    if track_paths.is_empty() {
+        eprintln!("None of the track paths could be read");
+        process::exit(1);
     }
 
-    // Check that the reference path has at least one track
-    let reference_track: Track = if reference_gpx.tracks.len() > 0 {
-        // If there are more than 1 track, print a warning that only the first track will be used and that it potentially should be manually checked if this is correct
-        if reference_gpx.tracks.len() > 1 {
-            print_info!(matches.json, "The reference path contains more than one track. Only the first track will be used. Please verify that this is the correct track.");
+    // Only the first tracks of every file are compared with --max-tracks, the others are dropped before any expensive work is done
+    if let Some(max_tracks) = matches.max_tracks {
+        for (track_path, track_gpx) in track_paths.iter().zip(track_gpxs.iter_mut()) {
+            let track_count = track_gpx.tracks.len();
+            if track_count > max_tracks as usize {
+                track_gpx.tracks.truncate(max_tracks as usize);
+                print_info!(
+                    suppress_info,
+                    "Comparing only the first {} of {} tracks in {:?}",
+                    max_tracks,
+                    track_count,
+                    track_path
+                );
+            }
         }
-
-        // Get the first track of the reference path
-        reference_gpx.tracks[0].clone()
     }
-    // Check if the reference path has any waypoints
-    // If so create a Track with a single TrackSegment containing all the waypoints
-    else if reference_gpx.waypoints.len() > 0 {
-        print_info!(matches.json, "The reference path does not contain any tracks, but it does contain waypoints. Creating a track from the waypoints");
-        let mut track: Track = Track::default();
-        let mut track_segment: TrackSegment = TrackSegment::default();
-        track_segment.points = reference_gpx.waypoints.clone();
-        track.segments.push(track_segment);
-        track.clone()
+    log::debug!(
+        "Parsing {} file(s) took {:.2?}",
+        reference_paths.len() + track_paths.len(),
+        parsing_start.elapsed()
+    );
+
+    // Select the reference track of every reference path
+    let mut reference_tracks: Vec<Track> = reference_gpxs
+        .iter()
+        .zip(&reference_paths)
+        .map(|(reference_gpx, reference_path)| {
+            select_reference_track(
+                reference_gpx,
+                reference_path,
+                matches
+                    .reference_track
+                    .as_ref()
+                    .or(matches.reference_from_track.as_ref()),
+                suppress_info,
+            )
+        })
+        .collect::<Result<_, String>>()
+        .unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1)
+        });
+
+    // Tracks with multiple segments are joined, unless only their first segment should be used or they should be rejected
+    // Empty segments are ignored, as they do not change the joined track
+    // If the segments of the reference path are candidates for the best match, they take precedence and the mode only applies to the tracks
+    let segment_mode_references = if matches.reference_segments == ReferenceSegments::Join {
+        reference_tracks.len()
     } else {
-        // No waypoints or tracks so we exit the program
-        eprintln!("The reference path does not contain any tracks or waypoints");
-        process::exit(1)
+        0
     };
+    match matches.segment_mode {
+        SegmentMode::Join => {}
+        SegmentMode::First => {
+            for track in reference_tracks
+                .iter_mut()
+                .take(segment_mode_references)
+                .chain(track_gpxs.iter_mut().flat_map(|gpx| &mut gpx.tracks))
+            {
+                track.segments.retain(|segment| !segment.points.is_empty());
+                track.segments.truncate(1);
+            }
+        }
+        SegmentMode::Error => {
+            let checked_tracks = reference_paths
+                .iter()
+                .zip(&reference_tracks)
+                .take(segment_mode_references)
+                .map(|(path, track)| ("reference path", path, track))
+                .chain(track_paths.iter().zip(&track_gpxs).flat_map(|(path, gpx)| {
+                    gpx.tracks.iter().map(move |track| ("track", path, track))
+                }));
+            for (kind, path, track) in checked_tracks {
+                let segment_count = track
+                    .segments
+                    .iter()
+                    .filter(|segment| !segment.points.is_empty())
+                    .count();
+                if segment_count > 1 {
+                    eprintln!("The {} {} in {:?} has {} segments. Use --segment-mode join to join them, --segment-mode first to only use the first one or --per-segment to compare them separately.", kind, track.name.as_deref().unwrap_or("-- Unnamed --"), path, segment_count);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+
+    // If only a section of the reference path is compared, clip the reference path between its waypoints
+    // The compared tracks are clipped to the same fractions of their length, as they are not necessarily recorded on the reference path
+    if let Some(section) = &matches.section {
+        let (start_fraction, end_fraction) = match locate_section(
+            &reference_tracks[0],
+            &reference_gpxs[0].waypoints,
+            section,
+            &reference_projection(&reference_tracks[0]),
+        ) {
+            Ok(fractions) => fractions,
+            Err(error) => {
+                eprintln!(
+                    "Failed to locate the section {} on the reference path {:?}: {}",
+                    section, reference_paths[0], error
+                );
+                process::exit(1);
+            }
+        };
+        log::debug!(
+            "Section {} spans {:.1}% to {:.1}% of the reference path",
+            section,
+            start_fraction * 100.0,
+            end_fraction * 100.0
+        );
+
+        reference_tracks[0] = clip_track(&reference_tracks[0], start_fraction, end_fraction);
+        for track in track_gpxs.iter_mut().flat_map(|gpx| &mut gpx.tracks) {
+            *track = clip_track(track, start_fraction, end_fraction);
+        }
+    }
+    let reference_track = &reference_tracks[0];
+
+    // If elevations are used, make sure that every point has one unless missing elevations should be ignored
+    if matches.use_elevation && matches.elevation_missing == ElevationMissing::Error {
+        for (reference_path, reference_track) in reference_paths.iter().zip(&reference_tracks) {
+            let missing_reference = count_missing_elevations(reference_track);
+            if missing_reference > 0 {
+                eprintln!("{} point(s) of the reference path {:?} have no elevation. Use --elevation-missing ignore to use the horizontal distance for them.", missing_reference, reference_path);
+                process::exit(1);
+            }
+        }
+
+        for (track_path, track_gpx) in track_paths.iter().zip(&track_gpxs) {
+            for track in &track_gpx.tracks {
+                let missing = count_missing_elevations(track);
+                if missing > 0 {
+                    eprintln!("{} point(s) of the track {} in {:?} have no elevation. Use --elevation-missing ignore to use the horizontal distance for them.", missing, track.name.as_deref().unwrap_or("-- Unnamed --"), track_path);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+
+    // Check all tracks for suspicious points, which are rejected in strict mode and only reported otherwise
+    // The issues are printed to stderr, so they are also visible in the machine readable modes without breaking their output
+    let checked_tracks =
+        reference_paths
+            .iter()
+            .zip(&reference_tracks)
+            .map(|(path, track)| ("reference path", path, track))
+            .chain(track_paths.iter().zip(&track_gpxs).flat_map(|(path, gpx)| {
+                gpx.tracks.iter().map(move |track| ("track", path, track))
+            }));
+    let mut has_suspicious_points = false;
+    for (kind, path, track) in checked_tracks {
+        let issues = validate_track(track, matches.max_jump);
+        if issues.is_empty() {
+            continue;
+        }
+        has_suspicious_points = true;
+        // Without --strict the issues are only warnings, which are suppressed in the quiet mode
+        if matches.quiet && !matches.strict {
+            continue;
+        }
+
+        eprintln!(
+            "{}: The {} {} in {:?} contains {} suspicious point(s):",
+            if matches.strict { "Error" } else { "Warning" },
+            kind,
+            track.name.as_deref().unwrap_or("-- Unnamed --"),
+            path,
+            issues.len()
+        );
+        // Only the first issues are listed, a track with a systematic problem would otherwise flood the console
+        for issue in issues.iter().take(MAX_LISTED_ISSUES) {
+            eprintln!("  - {}", issue);
+        }
+        if issues.len() > MAX_LISTED_ISSUES {
+            eprintln!("  - ... and {} more", issues.len() - MAX_LISTED_ISSUES);
+        }
+    }
+    if matches.strict && has_suspicious_points {
+        eprintln!("Rejecting the input because of the suspicious points above. Run without --strict to compare the tracks anyway.");
+        process::exit(1);
+    }
+
+    // Remember for every compared track the 0-based index of the track in its file and, if the segments are compared separately, the index of its segment
+    let mut track_origins: Vec<Vec<(usize, Option<usize>)>> = track_gpxs
+        .iter()
+        .map(|gpx| (0..gpx.tracks.len()).map(|index| (index, None)).collect())
+        .collect();
+
+    // In the per segment mode, every segment is compared as a track on its own
+    if matches.per_segment {
+        for (track_gpx, origins) in track_gpxs.iter_mut().zip(track_origins.iter_mut()) {
+            let mut segment_tracks = Vec::new();
+            origins.clear();
+            for (index, track) in track_gpx.tracks.iter().enumerate() {
+                for (segment_index, mut segment_track) in
+                    split_segments(track).into_iter().enumerate()
+                {
+                    segment_track.name = Some(format!(
+                        "{} (segment {})",
+                        track.name.as_deref().unwrap_or("-- Unnamed --"),
+                        segment_index + 1
+                    ));
+                    segment_tracks.push(segment_track);
+                    origins.push((index, Some(segment_index)));
+                }
+            }
+            track_gpx.tracks = segment_tracks;
+        }
+    }
+
+    // The track used as reference path with --reference-from-track is not compared to itself, unless this is explicitly requested
+    if let Some(selector) = &matches.reference_from_track {
+        let reference_index = match select_track(&reference_gpxs[0].tracks, selector) {
+            Ok(index) => index,
+            Err(error) => {
+                eprintln!(
+                    "Failed to select the reference track {} of {:?}: {}",
+                    selector, reference_paths[0], error
+                );
+                process::exit(1)
+            }
+        };
+        for ((track_path, track_gpx), origins) in track_paths
+            .iter()
+            .zip(track_gpxs.iter_mut())
+            .zip(track_origins.iter_mut())
+        {
+            if *track_path == reference_paths[0]
+                && exclude_reference_track(
+                    &mut track_gpx.tracks,
+                    origins,
+                    reference_index,
+                    matches.keep_reference_track,
+                ) > 0
+            {
+                print_info!(
+                    suppress_info,
+                    "Using track {} of {:?} as reference path, it is not compared to itself",
+                    reference_index + 1,
+                    track_path
+                );
+            }
+        }
+    }
+
+    // Tracks far away from every reference path are dropped with --bbox-prefilter before any expensive work is done
+    // The skipped tracks are reported on stderr, so they are also visible in the machine readable modes without breaking their output
+    if let Some(margin) = matches.bbox_prefilter {
+        let reference_projectors: Vec<FlatProjection<f64>> =
+            reference_tracks.iter().map(reference_projection).collect();
+        let mut skipped_tracks = 0;
+        for ((track_path, track_gpx), origins) in track_paths
+            .iter()
+            .zip(track_gpxs.iter_mut())
+            .zip(track_origins.iter_mut())
+        {
+            let mut origin_index = 0;
+            track_gpx.tracks.retain(|track| {
+                let overlaps = reference_tracks
+                    .iter()
+                    .zip(&reference_projectors)
+                    .any(|(reference_track, projector)| {
+                        bounding_boxes_overlap(reference_track, track, margin, projector)
+                    });
+                if overlaps {
+                    origin_index += 1;
+                } else {
+                    let (track_index, _) = origins.remove(origin_index);
+                    skipped_tracks += 1;
+                    print_warning!(
+                        matches.quiet,
+                        "No overlap: Skipping track {} ({}) in {:?}, as its bounding box is further than {}m from the reference path",
+                        track_index + 1,
+                        track.name.as_deref().unwrap_or("-- Unnamed --"),
+                        track_path,
+                        margin
+                    );
+                }
+                overlaps
+            });
+        }
+        log::debug!(
+            "The bounding box pre-filter skipped {} track(s)",
+            skipped_tracks
+        );
+    }
 
     // Get the total number of tracks by iterating all the track GPXs and summing the number of tracks
     let total_tracks: usize = track_gpxs.iter().map(|gpx| gpx.tracks.len()).sum();
 
-    print_info!(
-        matches.json,
-        "Calculating average distance between reference path ({}) and {} track(s)... ",
-        reference_track
-            .name
+    if matches.multi_reference {
+        print_info!(
+            suppress_info,
+            "Calculating distances between {} reference paths and {} track(s)... ",
+            reference_tracks.len(),
+            total_tracks
+        );
+    } else {
+        print_info!(
+            suppress_info,
+            "Calculating average distance between reference path ({}) and {} track(s)... ",
+            reference_track
+                .name
+                .as_ref()
+                .unwrap_or(&"-- Unnamed --".to_string())
+                .italic(),
+            total_tracks
+        );
+    }
+
+    // Unless it is centered on both tracks, the projection only depends on the reference path, so it is created once and shared by all tracks
+    let projectors: Vec<FlatProjection<f64>> = reference_tracks
+        .iter()
+        .map(|reference_track| {
+            let origin =
+                projection_origin(reference_track, reference_track, matches.projection_origin);
+            if matches.projection_origin == ProjectionOrigin::Both {
+                log::debug!(
+                    "Projection origin: average position of the reference path and every track"
+                );
+            } else {
+                log::debug!(
+                    "Projection origin: {:.6}, {:.6} (lat, lon)",
+                    origin.y(),
+                    origin.x()
+                );
+            }
+            FlatProjection::new(origin.x(), origin.y())
+        })
+        .collect();
+    // Function to get the projection used to compare a track to one of the reference paths
+    let projector_for = |track: &Track, reference: usize| match matches.projection_origin {
+        ProjectionOrigin::Both => {
+            track_projection(&reference_tracks[reference], track, ProjectionOrigin::Both)
+        }
+        _ => projectors[reference],
+    };
+
+    let track_files = TrackFiles {
+        paths: &track_paths,
+        gpxs: &track_gpxs,
+        origins: &track_origins,
+    };
+
+    // Compare every track to the checkpoints of the reference path instead of the reference path as a line
+    if matches.reference_as_checkpoints {
+        report_checkpoints(
+            &matches,
+            reference_track,
+            &track_files,
+            &projector_for,
+            &mut output,
+            precision,
+        );
+        flush_output(&mut output);
+        return;
+    }
+
+    // Features for the GeoJSON export, starting with the reference path
+    let mut geojson_features: Vec<serde_json::Value> = Vec::new();
+    if matches.export_geojson.is_some() {
+        geojson_features.push(geojson_linestring_feature(
+            &join_segments(&reference_track.segments),
+            serde_json::json!({
+                "role": "reference",
+                "name": reference_track.name,
+            }),
+        ));
+    }
+
+    // Lines of the WKT export, starting with the reference path
+    let mut wkt_lines: Vec<String> = Vec::new();
+    if matches.export_wkt.is_some() {
+        wkt_lines.push(format!(
+            "reference\t{}",
+            wkt_linestring(&join_segments(&reference_track.segments))
+        ));
+    }
+
+    // Compare every track to the reference path first, as each track is compared independently this can be done in parallel
+    // The results are collected in the order of the tracks, so the output below stays deterministic
+    let all_tracks: Vec<&Track> = track_gpxs
+        .iter()
+        .flat_map(|track_gpx| &track_gpx.tracks)
+        .collect();
+    // Every track is compared to every reference path, the comparison of track t to reference path r ends up at index t * references + r
+    let reference_count = reference_tracks.len();
+    let all_pairs: Vec<(&Track, usize)> = all_tracks
+        .iter()
+        .flat_map(|&track| (0..reference_count).map(move |reference| (track, reference)))
+        .collect();
+
+    // Show a progress bar while comparing, unless the output is machine readable or not shown in a terminal
+    let progress = if suppress_info || matches.no_progress || !std::io::stdout().is_terminal() {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(all_pairs.len() as u64).with_style(
+            ProgressStyle::with_template("{bar:40} {pos}/{len} comparisons ({elapsed})")
+                .expect("The progress bar template is valid"),
+        )
+    };
+    let compare = |&(track, reference): &(&Track, usize)| {
+        let comparison = compare_tracks_with_projection(
+            &reference_tracks[reference],
+            track,
+            &compare_options,
+            &projector_for(track, reference),
+        );
+        progress.inc(1);
+        comparison
+    };
+
+    let comparison_start = Instant::now();
+    let comparisons: Result<Vec<TrackComparison>, _> =
+        run_parallel(thread_pool(matches.jobs).as_ref(), &all_pairs, compare)
+            .into_iter()
+            .collect();
+    progress.finish_and_clear();
+    // The reference paths were checked when they were selected, but preprocessing them (e.g. --section) could remove all their points
+    let comparisons = comparisons.unwrap_or_else(|error| {
+        eprintln!("Can not compare the tracks: {}", error);
+        process::exit(1)
+    });
+    let comparison_time = comparison_start.elapsed();
+
+    // In the benchmark mode the comparison above is only a warm-up, the comparisons are repeated to measure how long they take
+    if let Some(runs) = matches.benchmark {
+        run_benchmark(
+            &matches,
+            runs,
+            &all_pairs,
+            &reference_tracks,
+            &compare_options,
+            &projector_for,
+            &mut output,
+        );
+        flush_output(&mut output);
+        return;
+    }
+
+    // Tracks with a very different length than their reference path usually come from mismatched files, which makes all metrics meaningless
+    // Like the suspicious points, the mismatches are rejected in strict mode and only reported otherwise
+    // In the multi reference mode most reference paths are expected to not match a track, so the lengths are not checked
+    let mut has_length_mismatch = false;
+    let mut pair_comparisons = comparisons.iter();
+    for (gpx_index, track_gpx) in track_gpxs
+        .iter()
+        .enumerate()
+        .filter(|_| !matches.multi_reference)
+    {
+        for (export_track_index, track) in track_gpx.tracks.iter().enumerate() {
+            for reference_path in &reference_paths {
+                let comparison = pair_comparisons
+                    .next()
+                    .expect("Every track is compared to every reference path");
+                let ratio = match comparison.length_ratio() {
+                    Some(ratio) if ratio > matches.length_ratio_threshold => ratio,
+                    _ => continue,
+                };
+                has_length_mismatch = true;
+                if matches.quiet && !matches.strict {
+                    continue;
+                }
+
+                eprintln!(
+                    "{}: The lengths of the track {} ({}) in {:?} and the reference path {:?} differ by a factor of {:.2} (track: {:.*}m, reference path: {:.*}m), which is more than the length ratio threshold of {}",
+                    if matches.strict { "Error" } else { "Warning" },
+                    track_origins[gpx_index][export_track_index].0 + 1,
+                    track.name.as_deref().unwrap_or("-- Unnamed --"),
+                    track_paths[gpx_index],
+                    reference_path,
+                    ratio,
+                    precision,
+                    comparison.current_track_length_m,
+                    precision,
+                    comparison.reference_track_length_m,
+                    matches.length_ratio_threshold
+                );
+            }
+        }
+    }
+    if matches.strict && has_length_mismatch {
+        eprintln!("Rejecting the input because of the length mismatches above. Check that the right files are compared, or raise --length-ratio-threshold.");
+        process::exit(1);
+    }
+
+    // The reference paths are described before the first track, so the JSON lines are self-contained
+    if matches.json_header {
+        write_reference_headers(
+            &matches,
+            &reference_tracks,
+            &reference_paths,
+            &mut output,
+            precision,
+        );
+    }
+
+    // In the multi reference mode, report the distances to every reference path and the best matching one per track
+    if matches.multi_reference {
+        report_multi_reference(
+            &matches,
+            &reference_paths,
+            &track_files,
+            &comparisons,
+            &mut output,
+            precision,
+        );
+        flush_output(&mut output);
+        log::debug!(
+            "Total time: {:.2?} (comparing the tracks: {:.2?})",
+            start.elapsed(),
+            comparison_time
+        );
+        return;
+    }
+
+    // The closest point pairs are written to their own CSV file, with a row per point of every compared track
+    let mut debug_pairs_file =
+        matches
+            .debug_pairs
             .as_ref()
-            .unwrap_or(&"-- Unnamed --".to_string())
-            .italic(),
-        total_tracks
-    );
+            .map(|debug_pairs_path| match File::create(debug_pairs_path) {
+                Ok(file) => BufWriter::new(file),
+                Err(error) => {
+                    eprintln!(
+                        "Failed to create the debug pairs file {:?}: {}",
+                        debug_pairs_path, error
+                    );
+                    process::exit(1);
+                }
+            });
+    if let Some(file) = debug_pairs_file.as_mut() {
+        if let Err(error) = writeln!(file, "{}", DEBUG_PAIRS_CSV_HEADER) {
+            eprintln!("Failed to write the debug pairs file: {}", error);
+            process::exit(1);
+        }
+    }
 
-    // Keep track of the current index of a track
-    let mut track_index: usize = 0;
+    // The point distances are written to their own CSV file as well, with the distances in the units of the output
+    let mut point_distances_file = matches.export_point_distances.as_ref().map(|path| {
+        let mut file = match File::create(path) {
+            Ok(file) => BufWriter::new(file),
+            Err(error) => {
+                eprintln!(
+                    "Failed to create the point distances file {:?}: {}",
+                    path, error
+                );
+                process::exit(1);
+            }
+        };
+        if let Err(error) = writeln!(
+            file,
+            "{}",
+            csv_header(POINT_DISTANCES_CSV_HEADER, matches.units)
+        ) {
+            eprintln!("Failed to write the point distances file: {}", error);
+            process::exit(1);
+        }
+        file
+    });
+
+    // Tracks exceeding one of the thresholds, reported after all tracks were printed
+    let mut threshold_failures: Vec<ThresholdFailure> = Vec::new();
+
+    // The reports of the report directory, kept for the summary which is written after all tracks
+    let mut reports: Vec<(String, serde_json::Value)> = Vec::new();
+    if let Some(report_dir) = &matches.report_dir {
+        if let Err(error) = fs::create_dir_all(report_dir) {
+            eprintln!(
+                "Failed to create the report directory {:?}: {}",
+                report_dir, error
+            );
+            process::exit(1);
+        }
+    }
+
+    // The CSV header is only printed once, before the first row
+    // When appending to an existing file, it was already written by a previous run
+    if matches.csv && !output_has_content {
+        write_output!(output, "{}", csv_header(CSV_HEADER, matches.units));
+    }
+
+    // Create a copy of the gpx files so we can modify them and reexport them if needed
+    let mut track_gpx_copies: Vec<Gpx> = track_gpxs.clone();
+    // The distances of every reexported track, which are stored in the extensions of the track
+    let mut exported_metrics: Vec<Vec<Option<ExportedMetrics>>> = track_gpxs
+        .iter()
+        .map(|gpx| vec![None; gpx.tracks.len()])
+        .collect();
+
+    // All tracks are compared already, so they can be reported in any order
+    // Every entry is the index of the file, of the track in the file and of the comparison
+    let mut report_order: Vec<(usize, usize, usize)> = track_gpxs
+        .iter()
+        .enumerate()
+        .flat_map(|(gpx_index, track_gpx)| {
+            (0..track_gpx.tracks.len())
+                .map(move |export_track_index| (gpx_index, export_track_index))
+        })
+        .enumerate()
+        .map(|(track_index, (gpx_index, export_track_index))| {
+            (gpx_index, export_track_index, track_index)
+        })
+        .collect();
+    if let Some(sort) = matches.sort {
+        // The sort is stable, so tracks with equal values stay in the input order
+        let track_and_comparison =
+            |&(gpx_index, export_track_index, track_index): &(usize, usize, usize)| {
+                (
+                    &track_gpxs[gpx_index].tracks[export_track_index],
+                    &comparisons[track_index],
+                )
+            };
+        report_order.sort_by(|a, b| {
+            sort.compare(
+                track_and_comparison(a),
+                track_and_comparison(b),
+                matches.reverse,
+            )
+        });
+    }
 
     // Iterate every track now
-    for (gpx_index, track_gpx) in track_gpxs.iter().enumerate() {
-        // Create a copy of the gpx file so we can modify it and  reexport it if needed
-        let mut track_gpx_copy: Gpx = track_gpx.clone();
+    for (gpx_index, export_track_index, track_index) in report_order {
+        let track = &track_gpxs[gpx_index].tracks[export_track_index];
+        let track_gpx_copy = &mut track_gpx_copies[gpx_index];
+        let comparison = &comparisons[track_index];
+        let (cur_track_index, segment_index) = track_origins[gpx_index][export_track_index];
 
-        for (cur_track_index, track) in track_gpx.tracks.iter().enumerate() {
-            print_info!(
-                matches.json,
+        // Tracks without any points can not be compared, so they are skipped
+        // The warning is printed to stderr, so it is also visible in the machine readable modes without breaking their output
+        if comparison.point_count == 0 && comparison.indeterminate_points == 0 {
+            print_warning!(
+                matches.quiet,
+                "Warning: Skipping track {} ({}) in {:?}, as it does not contain any points",
+                cur_track_index + 1,
+                track.name.as_deref().unwrap_or("-- Unnamed --"),
+                track_paths[gpx_index]
+            );
+            continue;
+        }
+        warn_if_downsampled(
+            &matches,
+            comparison,
+            track.name.as_deref().unwrap_or("-- Unnamed --"),
+            &track_paths[gpx_index],
+        );
+        warn_if_indeterminate(
+            &matches,
+            comparison,
+            track.name.as_deref().unwrap_or("-- Unnamed --"),
+            &track_paths[gpx_index],
+        );
+        warn_if_statistics_skipped(
+            &matches,
+            comparison,
+            track.name.as_deref().unwrap_or("-- Unnamed --"),
+            &track_paths[gpx_index],
+        );
+
+        if !machine_readable {
+            write_output!(
+                output,
                 "Track {}: {}",
                 track_index + 1,
                 track.name.as_ref().unwrap_or(&"-- Unnamed --".to_string())
             );
+        }
 
-            // Tracks may contain multiple segments, we however assume that there is only one segment, thus in
-            // Files with multiple segments, we combine them into a single LineString which is then used for the calculations
+        if let Some(spacing) = matches.resample {
+            log::debug!(
+                "Resampled to a spacing of {}m: {} points, reference path: {} points",
+                spacing,
+                comparison.point_count,
+                comparison.reference_point_count
+            );
+        }
 
-            // Additionally, GPS tracks are stored in LatLon coordinates, which are not suitable for distance calculations
-            // as the distance in meter varies depending on the latitude (1° latitude ranges from ~111 km at the equator to 0 km at the poles)
-            // To solve this, we project the coordinates to a flat coordinate system
-            // This is "very precise" for distances of up to about 500km
+        // The time spent per phase shows whether e.g. the spatial index or the Fréchet distance is the bottleneck
+        let timings = &comparison.timings;
+        log::debug!("Timings: preprocessing {:.2?}, projection {:.2?}, spatial index {:.2?}, average {:.2?}, simplification {:.2?}, Fréchet {:.2?}, Hausdorff {:.2?}, DTW {:.2?}, area {:.2?} (total {:.2?})",
+                timings.preprocessing,
+                timings.projection,
+                timings.index,
+                timings.average,
+                timings.simplification,
+                timings.frechet,
+                timings.hausdorff,
+                timings.dtw,
+                timings.area,
+                timings.total()
+            );
 
-            // To do so, find the average position of all the points in the reference track, around which we can project the coordinates
-            let total_points = reference_track
-                .segments
-                .iter()
-                .map(|segment| segment.points.len() as f64)
-                .sum::<f64>();
-            let sum_positions = reference_track
-                .segments
-                .iter()
-                .flat_map(|segment| &segment.points)
-                .fold(Point::new(0.0, 0.0), |acc, waypoint| {
-                    Point::new(
-                        acc.x() + waypoint.point().x(),
-                        acc.y() + waypoint.point().y(),
-                    )
-                });
-            let average_position = Point::new(
-                sum_positions.x() / total_points,
-                sum_positions.y() / total_points,
-            );
-
-            let projector = FlatProjection::new(average_position.x(), average_position.y());
-
-            /// Function to join segments and project them into a flat coordinate system.
-            /// This function takes a list of segments, projects their points, and returns a single LineString.
-            /// - `segments`: The GPS track segments to be joined and projected.
-            /// - `projector`: The flat coordinate system used for projection.
-            /// - Returns: A LineString containing all the projected points.
-            fn join_and_project_segments(
-                segments: &[TrackSegment],
-                projector: &FlatProjection<f64>,
-            ) -> LineString {
-                let mut joined_segment = TrackSegment::new();
-                segments.iter().for_each(|segment| {
-                    joined_segment
-                        .points
-                        .extend(segment.points.iter().map(|point| {
-                            let projected_point =
-                                projector.project(point.point().x(), point.point().y());
-                            Waypoint::new(Point::new(projected_point.x, projected_point.y))
-                        }));
-                });
-                joined_segment.linestring()
-            }
-
-            /// Function to unproject a LineString from a flat coordinate system back to LatLon coordinates.
-            /// This function takes a LineString in a flat coordinate system and unprojects the points back to LatLon coordinates.
-            /// - `linestring`: The LineString to be unprojected.
-            /// - `projector`: The flat coordinate system used for projection.
-            /// - Returns: A LineString containing all the unprojected points.
-            fn unproject_linestring(
-                linestring: &LineString,
-                projector: &FlatProjection<f64>,
-            ) -> LineString {
-                linestring
-                    .points()
-                    .map(|point| {
-                        let unprojected_point = projector.unproject(&FlatPoint {
-                            x: point.x(),
-                            y: point.y(),
-                        });
-                        Coord {
-                            x: unprojected_point.0,
-                            y: unprojected_point.1,
-                        }
-                    })
-                    .collect()
-            }
-
-            /// Function to calculate the total length of a LineString.
-            /// The length is computed as the sum of distances between consecutive points in the LineString.
-            /// - `linestring`: The LineString whose total length is to be calculated.
-            /// - Returns: The total length of the LineString in kilometers.
-            fn calculate_total_length(linestring: &LineString) -> f64 {
-                linestring
-                    .points()
-                    .zip(linestring.points().skip(1))
-                    .map(|(p1, p2)| p1.euclidean_distance(&p2))
-                    .sum()
-            }
-
-            // Use the function to join and project both the current and reference tracks.
-            let joined_current_linestring = join_and_project_segments(&track.segments, &projector);
-            let joined_reference_linestring =
-                join_and_project_segments(&reference_track.segments, &projector);
-
-            // Calculate the total length of the joined LineStrings for both the current and reference tracks.
-            let current_track_length = calculate_total_length(&joined_current_linestring);
-            let reference_track_length = calculate_total_length(&joined_reference_linestring);
-
-            // If either track length is above 500km, print a warning that the distance may not be as precise
-            if current_track_length > 500.0 || reference_track_length > 500.0 {
-                print_info!(matches.json,
-                    "Warning: The total length of the current track is {} km and the total length of the reference track is {} km. The distance computations may not be as precise due to using a fast flat projection.",
-                    format!("{:.3}", current_track_length).red().bold(),
-                    format!("{:.3}", reference_track_length).red().bold()
-                );
+        // Report every point together with its closest point on the reference path, to spot bad GPS fixes
+        match debug_pairs_file.as_mut() {
+            Some(file) => {
+                for pair in &comparison.nearest_points {
+                    if let Err(error) = writeln!(
+                        file,
+                        "{},{:.7},{:.7},{:.7},{:.7},{:.*}",
+                        track_index + 1,
+                        pair.line.start.y,
+                        pair.line.start.x,
+                        pair.line.end.y,
+                        pair.line.end.x,
+                        precision,
+                        pair.distance_m
+                    ) {
+                        eprintln!("Failed to write the debug pairs file: {}", error);
+                        process::exit(1);
+                    }
+                }
             }
+            None => {
+                for (index, pair) in comparison.nearest_points.iter().enumerate() {
+                    log::debug!("Point {}: {:.7}, {:.7} -> closest point {:.7}, {:.7} (lat, lon), distance {:.*}m",
+                            index,
+                            pair.line.start.y,
+                            pair.line.start.x,
+                            pair.line.end.y,
+                            pair.line.end.x,
+                            precision,
+                            pair.distance_m
+                        );
+                }
+            }
+        }
 
-            // Calculate the frechet distance (in kilometers)
-            let frechet_distance =
-                joined_current_linestring.frechet_distance(&joined_reference_linestring);
-
-            // Calculate the hausdorff distance (in kilometers)
-            let hausdorff_distance =
-                joined_current_linestring.hausdorff_distance(&joined_reference_linestring);
+        if let Some(file) = point_distances_file.as_mut() {
+            for pair in &comparison.nearest_points {
+                // Points without an elevation leave the column empty
+                if let Err(error) = writeln!(
+                    file,
+                    "{:.7},{:.7},{},{:.*},{:.*}",
+                    pair.line.start.x,
+                    pair.line.start.y,
+                    pair.elevation.map_or(String::new(), |elevation| format!(
+                        "{:.*}",
+                        precision,
+                        matches.units.length(elevation)
+                    )),
+                    precision,
+                    matches.units.length(pair.distance_m),
+                    precision,
+                    matches.units.length(pair.cumulative_arc_m)
+                ) {
+                    eprintln!("Failed to write the point distances file: {}", error);
+                    process::exit(1);
+                }
+            }
+        }
 
-            let mut total_distance: f64 = 0.0;
-            let mut total_points: usize = 0;
-            let mut total_distance_simplified: f64 = 0.0;
-            let mut total_points_simplified: usize = 0;
+        // If a point of either track is further away from the origin of the projection than the threshold, print a warning that the distance may not be as precise
+        // This only applies to the flat projection, the geodesic mode does not lose precision for long tracks
+        // The extent is used instead of the length of the tracks, as e.g. a long loop can still stay close to the origin
+        // The UTM projection does not have this problem within its zone, so it only warns about points outside of the zone
+        if matches.distance_mode == DistanceMode::Flat && matches.projection == Projection::Utm {
+            let zone = UtmProjection::for_point(reference_origin(reference_track));
+            let max_offset = zone
+                .max_meridian_offset(reference_track)
+                .max(zone.max_meridian_offset(track));
+            if max_offset > UTM_HALF_ZONE_WIDTH {
+                print_info!(suppress_info,
+                        "Warning: The tracks extend up to {}° in longitude from the central meridian of UTM zone {}, which is outside of the zone (more than {}°). The distance computations may not be as precise. Consider using --distance-mode geodesic.",
+                        format!("{:.*}", precision, max_offset).red().bold(),
+                        zone.zone,
+                        UTM_HALF_ZONE_WIDTH
+                    );
+            }
+        }
+        if matches.distance_mode == DistanceMode::Flat && matches.projection == Projection::Flat {
+            let projector = projector_for(track, 0);
+            let max_distance = max_distance_from_origin(reference_track, &projector)
+                .max(max_distance_from_origin(track, &projector));
+            if max_distance > matches.precision_warn_km * 1000.0 {
+                print_info!(suppress_info,
+                        "Warning: The tracks extend up to {} km from the origin of the flat projection, which is more than {} km. The distance computations may not be as precise due to using a fast flat projection. Consider using --distance-mode geodesic or --projection utm.",
+                        format!("{:.*}", precision, max_distance / 1000.0).red().bold(),
+                        matches.precision_warn_km
+                    );
+            }
+        }
 
-            // If we want to reexport the GPX files, we need to clear the track segments
-            if matches.export_track {
-                track_gpx_copy.tracks[cur_track_index].segments.clear();
+        // Check the metrics against the thresholds
+        let thresholds = [
+            (
+                "average_distance_m",
+                Some(comparison.average_distance_m),
+                matches.max_average,
+            ),
+            (
+                "frechet_distance_m",
+                comparison.frechet_distance_m,
+                matches.max_frechet,
+            ),
+        ];
+        for (metric, value, threshold) in thresholds {
+            // Metrics which are not available (e.g. curve metrics of single point tracks) can not be checked
+            if let (Some(value), Some(threshold)) = (value, threshold) {
+                if value > threshold {
+                    threshold_failures.push(ThresholdFailure {
+                        track_path: track_paths[gpx_index].clone(),
+                        track_index: cur_track_index + 1,
+                        track_name: track.name.clone().unwrap_or("-- Unnamed --".to_string()),
+                        metric,
+                        value,
+                        threshold,
+                    });
+                }
             }
+        }
 
-            // Next we want to compute the "average" and the "simplified average" distance between the reference path and the current track
-            // The average distance is computed by taking every point of the current track and finding the closest point on the reference path, then calculating the distance between them and summing them up divided by the total number of points
-            // For the simplified average distance, we first simplify the reference path by removing points that are closer than a certain epsilon value to each other using the Ramer-Douglas-Peucker algorithm and then do the same as for the average distance
+        // If we want to reexport the GPX files, replace the track segments with the simplified track
+        // The retained points keep the metadata of their original waypoints, e.g. elevation and time
+        if matches.export_track {
+            let mut track_segment = TrackSegment::new();
+            track_segment.points = simplified_waypoints(track, &comparison.simplified_track);
+            track_gpx_copy.tracks[export_track_index].segments = vec![track_segment];
+            exported_metrics[gpx_index][export_track_index] =
+                Some(ExportedMetrics::from_comparison(comparison));
+        }
 
-            // Function to calculate the average distance between two LineStrings.
-            let calculate_average_distance =
-                |current_linestring: &LineString,
-                 reference_linestring: &LineString,
-                 distance: &mut f64,
-                 points: &mut usize| {
-                    current_linestring.points().for_each(|point| {
-                        // Find the distance to the closest point on the reference path
-                        let closest_point = reference_linestring.closest_point(&point);
+        // Only the reported values are converted, the thresholds above are checked on the values in meters
+        let comparison = &matches.units.convert_comparison(comparison);
 
-                        let current_distance = match closest_point {
-                            Closest::Intersection(p) => p.euclidean_distance(&point),
-                            Closest::Indeterminate => f64::INFINITY,
-                            Closest::SinglePoint(p) => p.euclidean_distance(&point),
-                        };
+        // Construct the JSON output describing the comparison
+        let json_output = ComparisonOutput {
+            segment_index: segment_index.map(|index| index + 1),
+            ..ComparisonOutput::new(
+                cur_track_index + 1,
+                track.name.as_ref().unwrap_or(&"-- Unnamed --".to_string()),
+                comparison,
+                precision,
+            )
+        };
 
-                        // Add the distance to the total distance
-                        *distance += current_distance;
-                        *points += 1;
-                    });
-                };
+        // Write the report of the track, which is the JSON output together with the path of its file
+        if let Some(report_dir) = &matches.report_dir {
+            let stem = if is_stdin(&track_paths[gpx_index]) {
+                "stdin".to_string()
+            } else {
+                track_paths[gpx_index]
+                    .file_stem()
+                    .map_or("track".to_string(), |stem| {
+                        stem.to_string_lossy().to_string()
+                    })
+            };
+            let mut file_name = format!("{}_track_{}", stem, cur_track_index + 1);
+            if let Some(segment_index) = json_output.segment_index {
+                file_name.push_str(&format!("_segment_{}", segment_index));
+            }
+            // Track files with the same name in different directories are told apart by their position on the command line
+            if reports
+                .iter()
+                .any(|(name, _)| *name == format!("{}.json", file_name))
+            {
+                file_name = format!("file_{}_{}", gpx_index + 1, file_name);
+            }
+            file_name.push_str(".json");
+
+            let mut report = serde_json::to_value(&json_output)
+                .expect("The JSON output can always be serialized");
+            matches.units.rename_json_fields(&mut report);
+            if let serde_json::Value::Object(fields) = &mut report {
+                fields.insert(
+                    "track_path".to_string(),
+                    track_paths[gpx_index].display().to_string().into(),
+                );
+            }
+            if let Err(error) = fs::write(report_dir.join(&file_name), json_string(&report, true)) {
+                eprintln!("Failed to write the report {:?}: {}", file_name, error);
+                process::exit(1);
+            }
+            reports.push((file_name, report));
+        }
 
-            // First we create a simplified version of the reference path
-            let simplified_linestring: LineString =
-                joined_current_linestring.simplify(&(matches.simplify_epsilon / 1000.0));
+        // Add the full or the simplified track to the WKT export, the segment is part of the label if the segments are compared separately
+        if matches.export_wkt.is_some() {
+            let mut label = format!("file_{}_track_{}", gpx_index + 1, cur_track_index + 1);
+            if let Some(segment_index) = json_output.segment_index {
+                label.push_str(&format!("_segment_{}", segment_index));
+            }
+            let linestring = if matches.export_wkt_simplified {
+                comparison.simplified_track.clone()
+            } else {
+                join_segments(&track.segments)
+            };
+            wkt_lines.push(format!("{}\t{}", label, wkt_linestring(&linestring)));
+        }
 
-            // If we want to reexport the GPX files, we need to add the simplified LineString to the track segments
-            // For this we need to convert the flat coordinates back to LatLon coordinates
-            if matches.export_track {
-                let simplified_unprojected_linestring =
-                    unproject_linestring(&simplified_linestring, &projector);
+        // Add the track, and optionally the lines to the closest points, to the GeoJSON export
+        if matches.export_geojson.is_some() {
+            let mut properties = serde_json::to_value(&json_output)
+                .expect("The JSON output can always be serialized");
+            matches.units.rename_json_fields(&mut properties);
+            properties["role"] = "track".into();
+            geojson_features.push(geojson_linestring_feature(
+                &join_segments(&track.segments),
+                properties,
+            ));
 
-                let mut track_segment = TrackSegment::new();
-                track_segment.points = simplified_unprojected_linestring
-                    .points()
-                    .map(|point| Waypoint::new(point))
-                    .collect();
-                track_gpx_copy.tracks[cur_track_index]
-                    .segments
-                    .push(track_segment);
+            // The pairs may also have been collected for debugging, so they are only exported if requested
+            for pair in comparison
+                .nearest_points
+                .iter()
+                .filter(|_| matches.export_geojson_nearest)
+            {
+                let mut properties = serde_json::json!({
+                    "role": "nearest_point",
+                    "track_index": cur_track_index + 1,
+                    "track_name": json_output.track_name,
+                    "distance_m": round_to_precision(pair.distance_m, precision),
+                });
+                matches.units.rename_json_fields(&mut properties);
+                geojson_features.push(geojson_linestring_feature(
+                    &LineString::from(pair.line),
+                    properties,
+                ));
             }
+        }
 
-            // Calculate the average distance between the current and reference tracks
-            calculate_average_distance(
-                &joined_current_linestring,
-                &joined_reference_linestring,
-                &mut total_distance,
-                &mut total_points,
+        if matches.csv {
+            // Print the CSV row for this track
+            write_output!(
+                output,
+                "{}",
+                comparison_csv(
+                    cur_track_index + 1,
+                    track.name.as_ref().unwrap_or(&"-- Unnamed --".to_string()),
+                    comparison,
+                    precision,
+                )
             );
-
-            // Calculate the average distance between the simplified current and reference tracks
-            calculate_average_distance(
-                &simplified_linestring,
-                &joined_reference_linestring,
-                &mut total_distance_simplified,
-                &mut total_points_simplified,
+        } else if matches.json {
+            // Print the JSON object
+            write_output!(
+                output,
+                "{}",
+                json_string_in_units(&json_output, matches.units, matches.pretty)
             );
+        } else {
+            write_comparison(&matches, comparison, &mut output, precision);
+        }
+    }
 
-            if matches.json {
-                // Construct a JSON object and print it
-                let json_output = serde_json::json!({
-                    "track_index": cur_track_index + 1,
-                    "track_name": track.name.as_ref().unwrap_or(&"-- Unnamed --".to_string()),
-                    "current_track_length_m": current_track_length * 1000.0,
-                    "reference_track_length_m": reference_track_length * 1000.0,
-                    "average_distance_m": (total_distance / total_points as f64) * 1000.0,
-                    "simplified_average_distance_m": (total_distance_simplified / total_points_simplified as f64) * 1000.0,
-                    "frechet_distance_m": frechet_distance * 1000.0,
-                    "hausdorff_distance_m": hausdorff_distance * 1000.0,
-                });
+    export_track_files(
+        &matches,
+        &track_files,
+        &track_gpx_copies,
+        &exported_metrics,
+        &projector_for,
+    );
 
-                // Print the JSON object
-                println!("{}", json_output.to_string());
-            } else {
-                // Print the lengths of the tracks
-                println!(
-                    "Total length of current track: {}",
-                    format!("{:.3}m", current_track_length * 1000.0).bold()
-                );
-                println!(
-                    "Total length of reference track: {}",
-                    format!("{:.3}m", reference_track_length * 1000.0).bold()
-                );
-                println!(
-                    "Average distance (in time): {} (counting every point)",
-                    (format!("{:.3}m", (total_distance / total_points as f64) * 1000.0))
-                        .cyan()
-                        .bold()
-                );
-                println!(
-                    "Average distance (location dependent): {} (counting only simplified points)",
-                    (format!(
-                        "{:.3}m",
-                        (total_distance_simplified / total_points_simplified as f64) * 1000.0
-                    ))
-                    .yellow()
-                    .bold()
-                );
-                println!(
-                    "Fréchet distance: {}",
-                    (format!("{:.3}m", frechet_distance * 1000.0))
-                        .magenta()
-                        .bold()
-                );
-                println!(
-                    "Hausdorff distance: {}",
-                    (format!("{:.3}m", hausdorff_distance * 1000.0))
-                        .green()
-                        .bold()
-                );
-            }
+    // Write all collected features to the GeoJSON file
+    if let Some(geojson_path) = &matches.export_geojson {
+        let geojson_file = File::create(geojson_path).expect("Failed to create GeoJSON file");
+        serde_json::to_writer(geojson_file, &geojson_feature_collection(geojson_features))
+            .expect("Failed to write GeoJSON file");
+        print_info!(suppress_info, "Exported GeoJSON file to {:?}", geojson_path);
+    }
 
-            track_index += 1;
+    // Summarize all reports of the report directory
+    if let Some(report_dir) = &matches.report_dir {
+        let summary_path = report_dir.join("summary.json");
+        let summary = ReportSummaryOutput::new(&reports, precision);
+        if let Err(error) = fs::write(&summary_path, json_string(&summary, true)) {
+            eprintln!("Failed to write the summary {:?}: {}", summary_path, error);
+            process::exit(1);
         }
+        print_info!(
+            suppress_info,
+            "Wrote {} report(s) and the summary to {:?}",
+            reports.len(),
+            report_dir
+        );
+    }
 
-        // If we want to reexport the GPX files, do it now by writing the modified GPX file to the same path, adding .modified before the extension
-        if matches.export_track {
-            let track_path = track_paths[gpx_index].clone();
-            let mut modified_path = track_path.clone();
-            modified_path.set_extension("modified.gpx");
-            let track_file =
-                File::create(&modified_path).expect("Failed to create modified track file");
-            write(&track_gpx_copy, track_file).expect("Failed to write modified track file");
-            println!("Exported modified track file to {:?}", &modified_path);
+    // Write all collected geometries to the WKT file
+    if let Some(wkt_path) = &matches.export_wkt {
+        let mut content = wkt_lines.join("\n");
+        content.push('\n');
+        if let Err(error) = fs::write(wkt_path, content) {
+            eprintln!("Failed to write the WKT file {:?}: {}", wkt_path, error);
+            process::exit(1);
+        }
+        print_info!(suppress_info, "Exported WKT file to {:?}", wkt_path);
+    }
+
+    // Make sure all results are written before exiting, including the buffered CSV files which would not be flushed by process::exit
+    for (kind, file) in [
+        ("debug pairs", debug_pairs_file.as_mut()),
+        ("point distances", point_distances_file.as_mut()),
+    ] {
+        if let Some(Err(error)) = file.map(|file| file.flush()) {
+            eprintln!("Failed to write the {} file: {}", kind, error);
+            process::exit(1);
+        }
+    }
+    flush_output(&mut output);
+    // The comparisons run in parallel, so their wall-clock time is usually less than the sum of the per track timings
+    log::debug!(
+        "Total time: {:.2?} (comparing the tracks: {:.2?})",
+        start.elapsed(),
+        comparison_time
+    );
+
+    // List the skipped files again, so they are not lost between the warnings of a large batch
+    if !skipped_track_files.is_empty() {
+        print_warning!(
+            matches.quiet,
+            "Skipped {} of {} track file(s):",
+            skipped_track_files.len(),
+            track_file_count
+        );
+        for (track_path, reason) in &skipped_track_files {
+            print_warning!(matches.quiet, "  {:?} {}", track_path, reason);
         }
     }
+
+    // Report all tracks which exceeded a threshold and exit with a dedicated status code, so CI pipelines can fail on it
+    if !threshold_failures.is_empty() {
+        report_threshold_failures(&matches, &threshold_failures, precision);
+        process::exit(3);
+    }
 }
@@ -0,0 +1,257 @@
+//! Batch comparisons described by a JSON manifest file, e.g. for reproducible, version-controlled comparison suites.
+//!
+//! The manifest is an array of jobs like
+//! `[{"name": "commute", "reference": "commute.gpx", "tracks": ["monday.gpx", "tuesday.fit"], "simplify_epsilon": 2.0, "max_average": 10.0}]`.
+//! Every job compares all tracks of its track files to the first track of its reference file. Options which are not given
+//! by a job are taken from the command line, relative paths are resolved relative to the directory of the manifest.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::input::read_file;
+use crate::output::{round_to_precision, ComparisonOutput};
+use crate::{
+    check_reference, compare_tracks_with_projection, first_track_or_route, track_projection,
+    CompareOptions, CompareOptionsBuilder, DistanceMode, Units,
+};
+
+/// Function to deserialize an optional value from its string representation, e.g. a distance mode.
+/// - `deserializer`: The deserializer.
+/// - Returns: The parsed value, `None` if the field is `null`.
+fn from_str_option<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: std::str::FromStr<Err = String>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|value| value.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// A single comparison job of a manifest.
+/// All options are optional and fall back to the value given on the command line.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestJob {
+    /// Name of the job, which is repeated in its result
+    #[serde(default)]
+    pub name: Option<String>,
+    /// File containing the reference path, its first track (or without tracks its first route) is used
+    pub reference: PathBuf,
+    /// Files containing the tracks to compare
+    pub tracks: Vec<PathBuf>,
+    /// Epsilon of the simplification in meters, see [`CompareOptions::simplify_epsilon`]
+    #[serde(default)]
+    pub simplify_epsilon: Option<f64>,
+    /// Width of the band of the DTW distance in points, see [`CompareOptions::dtw_window`]
+    #[serde(default)]
+    pub dtw_window: Option<usize>,
+    /// `flat` or `geodesic`, see [`CompareOptions::distance_mode`]
+    #[serde(default, deserialize_with = "from_str_option")]
+    pub distance_mode: Option<DistanceMode>,
+    /// Spacing of the resampling in meters, see [`CompareOptions::resample`]
+    #[serde(default)]
+    pub resample: Option<f64>,
+    /// Tolerance in meters, see [`CompareOptions::tolerance`]
+    #[serde(default)]
+    pub tolerance: Option<f64>,
+    /// Whether the elevation is included in the distances, see [`CompareOptions::use_elevation`]
+    #[serde(default)]
+    pub use_elevation: Option<bool>,
+    /// Whether the reverse average is calculated as well, see [`CompareOptions::symmetric`]
+    #[serde(default)]
+    pub symmetric: Option<bool>,
+    /// Maximum number of points for the curve metrics, see [`CompareOptions::max_points`]
+    #[serde(default)]
+    pub max_points: Option<usize>,
+    /// Maximum allowed average distance of a track in meters
+    #[serde(default)]
+    pub max_average: Option<f64>,
+    /// Maximum allowed Fréchet distance of a track in meters
+    #[serde(default)]
+    pub max_frechet: Option<f64>,
+}
+
+impl ManifestJob {
+    /// Function to get the options of the job.
+    /// - `defaults`: The options given on the command line.
+    /// - Returns: The options with all values given by the job replaced.
+    pub fn compare_options(&self, defaults: &CompareOptions) -> CompareOptions {
+        CompareOptionsBuilder::from_options(defaults.clone())
+            .simplify_epsilon(self.simplify_epsilon.unwrap_or(defaults.simplify_epsilon))
+            .dtw_window(self.dtw_window.or(defaults.dtw_window))
+            .distance_mode(self.distance_mode.unwrap_or(defaults.distance_mode))
+            .resample(self.resample.or(defaults.resample))
+            .tolerance(self.tolerance.or(defaults.tolerance))
+            .use_elevation(self.use_elevation.unwrap_or(defaults.use_elevation))
+            .symmetric(self.symmetric.unwrap_or(defaults.symmetric))
+            .max_points(self.max_points.or(defaults.max_points))
+            .build()
+    }
+}
+
+/// A metric of a track which exceeds the threshold of its job.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestThresholdFailure {
+    /// Path of the file containing the track
+    pub track_path: String,
+    /// 1-based index of the track in its file
+    pub track_index: usize,
+    /// Name of the metric, matching the key in the output of the track (in the units of the result)
+    pub metric: String,
+    /// The value and the threshold, in the units of the result
+    pub value_m: f64,
+    pub threshold_m: f64,
+}
+
+/// The comparisons of all tracks of a single track file of a job.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestFileResult {
+    /// Path of the track file, as given in the manifest
+    pub track_path: String,
+    /// The comparison of every track of the file which contains points
+    pub tracks: Vec<ComparisonOutput>,
+    /// Why the file could not be compared
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The result of a single job of a manifest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestResult {
+    /// The name of the job
+    pub name: Option<String>,
+    /// Path of the reference file, as given in the manifest
+    pub reference_path: String,
+    /// Whether all files were compared and no track exceeded a threshold
+    pub passed: bool,
+    /// The comparisons of every track file
+    pub files: Vec<ManifestFileResult>,
+    /// All metrics exceeding the thresholds of the job
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub threshold_failures: Vec<ManifestThresholdFailure>,
+    /// Why the job failed completely, e.g. because the reference file could not be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Function to parse a manifest.
+/// - `content`: The content of the manifest file.
+/// - Returns: All jobs of the manifest, or an error message.
+pub fn parse_manifest(content: &str) -> Result<Vec<ManifestJob>, String> {
+    serde_json::from_str(content).map_err(|error| format!("invalid manifest: {}", error))
+}
+
+/// Function to run a single job of a manifest.
+/// - `job`: The job.
+/// - `base_directory`: The directory relative to which the paths of the job are resolved, usually the directory of the manifest.
+/// - `defaults`: The options given on the command line, which are used if the job does not give them.
+/// - `precision`: The number of decimal places of the values in the result.
+/// - `units`: The units of the distances in the result, the thresholds are always given in meters.
+/// - Returns: The result of the job.
+pub fn run_job(
+    job: &ManifestJob,
+    base_directory: &Path,
+    defaults: &CompareOptions,
+    precision: usize,
+    units: Units,
+) -> ManifestResult {
+    let opts = job.compare_options(defaults);
+    let mut result = ManifestResult {
+        name: job.name.clone(),
+        reference_path: job.reference.display().to_string(),
+        passed: false,
+        files: Vec::new(),
+        threshold_failures: Vec::new(),
+        error: None,
+    };
+
+    let reference_gpx = match read_file(&base_directory.join(&job.reference)) {
+        Ok(gpx) => gpx,
+        Err(error) => {
+            result.error = Some(format!("failed to read the reference path: {}", error));
+            return result;
+        }
+    };
+    let Some(reference) = &first_track_or_route(&reference_gpx) else {
+        result.error = Some("the reference file does not contain any tracks or routes".to_string());
+        return result;
+    };
+    if let Err(error) = check_reference(reference) {
+        result.error = Some(error.to_string());
+        return result;
+    }
+
+    for track_path in &job.tracks {
+        let mut file_result = ManifestFileResult {
+            track_path: track_path.display().to_string(),
+            tracks: Vec::new(),
+            error: None,
+        };
+        match read_file(&base_directory.join(track_path)) {
+            Ok(track_gpx) => {
+                for (index, track) in track_gpx.tracks.iter().enumerate() {
+                    let comparison = match compare_tracks_with_projection(
+                        reference,
+                        track,
+                        &opts,
+                        &track_projection(reference, track, opts.projection_origin),
+                    ) {
+                        Ok(comparison) => comparison,
+                        Err(error) => {
+                            result.error = Some(error.to_string());
+                            return result;
+                        }
+                    };
+                    // Like on the command line, tracks without any points are skipped
+                    if comparison.point_count == 0 && comparison.indeterminate_points == 0 {
+                        continue;
+                    }
+
+                    let thresholds = [
+                        (
+                            "average_distance_m",
+                            Some(comparison.average_distance_m),
+                            job.max_average,
+                        ),
+                        (
+                            "frechet_distance_m",
+                            comparison.frechet_distance_m,
+                            job.max_frechet,
+                        ),
+                    ];
+                    for (metric, value, threshold) in thresholds {
+                        if let (Some(value), Some(threshold)) = (value, threshold) {
+                            if value > threshold {
+                                result.threshold_failures.push(ManifestThresholdFailure {
+                                    track_path: file_result.track_path.clone(),
+                                    track_index: index + 1,
+                                    metric: units.field_name(metric),
+                                    value_m: round_to_precision(units.length(value), precision),
+                                    threshold_m: round_to_precision(
+                                        units.length(threshold),
+                                        precision,
+                                    ),
+                                });
+                            }
+                        }
+                    }
+
+                    file_result.tracks.push(ComparisonOutput::new(
+                        index + 1,
+                        track.name.as_deref().unwrap_or("-- Unnamed --"),
+                        &units.convert_comparison(&comparison),
+                        precision,
+                    ));
+                }
+            }
+            Err(error) => file_result.error = Some(format!("failed to read the track: {}", error)),
+        }
+        result.files.push(file_result);
+    }
+
+    result.passed = result.threshold_failures.is_empty()
+        && result.files.iter().all(|file| file.error.is_none());
+    result
+}
@@ -0,0 +1,281 @@
+//! Curve metrics which can be computed with different ways of measuring the distance between two points.
+
+use std::fmt;
+use std::str::FromStr;
+
+use geo::{EuclideanDistance, HaversineDistance, LineString, Point};
+
+/// How the distance between two points is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DistanceMode {
+    /// Euclidean distance in a flat projection around the reference path, in kilometers.
+    /// Fast and very precise for tracks up to about 500 km.
+    #[default]
+    Flat,
+    /// Haversine (great circle) distance on the LatLon coordinates, in meters.
+    /// Slower, but does not degrade for long tracks.
+    Geodesic,
+}
+
+impl DistanceMode {
+    /// Function to calculate the distance between two points.
+    /// - `a`: The first point.
+    /// - `b`: The second point.
+    /// - Returns: The distance in the units of this mode (see [`DistanceMode::unit_in_meters`]).
+    pub fn distance(&self, a: &Point, b: &Point) -> f64 {
+        match self {
+            DistanceMode::Flat => a.euclidean_distance(b),
+            DistanceMode::Geodesic => a.haversine_distance(b),
+        }
+    }
+
+    /// Function to get the length of the unit of the distances of this mode.
+    /// - Returns: The number of meters per unit.
+    pub fn unit_in_meters(&self) -> f64 {
+        match self {
+            DistanceMode::Flat => 1000.0,
+            DistanceMode::Geodesic => 1.0,
+        }
+    }
+}
+
+impl FromStr for DistanceMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flat" => Ok(DistanceMode::Flat),
+            "geodesic" => Ok(DistanceMode::Geodesic),
+            _ => Err(format!("unknown distance mode {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for DistanceMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DistanceMode::Flat => write!(f, "flat"),
+            DistanceMode::Geodesic => write!(f, "geodesic"),
+        }
+    }
+}
+
+/// Function to calculate the discrete Fréchet distance between two lists of points.
+/// This is the same metric as [`geo::FrechetDistance`], but computed iteratively with a custom distance between points.
+/// - `a`: The points of the first track.
+/// - `b`: The points of the second track.
+/// - `distance`: Function calculating the distance between two points.
+/// - Returns: The Fréchet distance, or 0 if one of the tracks is empty.
+pub fn frechet_distance<T>(a: &[T], b: &[T], distance: impl Fn(&T, &T) -> f64) -> f64 {
+    frechet_distance_with_pair(a, b, distance).map_or(0.0, |(distance, _)| distance)
+}
+
+/// Function to calculate the discrete Fréchet distance between two lists of points, together with the pair of points which determines it.
+/// Every cell of the coupling matrix remembers the pair with the largest distance on its best coupling, so the pair of the
+/// final cell is the pair with the longest leash of the optimal coupling, without having to keep the whole matrix.
+/// - `a`: The points of the first track.
+/// - `b`: The points of the second track.
+/// - `distance`: Function calculating the distance between two points.
+/// - Returns: The Fréchet distance and the indices of the critical pair of points in `a` and `b`, or `None` if one of the tracks is empty.
+pub fn frechet_distance_with_pair<T>(
+    a: &[T],
+    b: &[T],
+    distance: impl Fn(&T, &T) -> f64,
+) -> Option<(f64, (usize, usize))> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+
+    // Only the previous and current row of the coupling matrix are needed, which keeps the memory usage linear
+    let mut previous_row = vec![(0.0_f64, (0, 0)); b.len()];
+    let mut current_row = vec![(0.0_f64, (0, 0)); b.len()];
+
+    for (i, a_point) in a.iter().enumerate() {
+        for (j, b_point) in b.iter().enumerate() {
+            let point_distance = distance(a_point, b_point);
+            // The best coupling reaching this cell comes from the predecessor with the smallest leash
+            let best_previous = match (i, j) {
+                (0, 0) => None,
+                (0, _) => Some(current_row[j - 1]),
+                (_, 0) => Some(previous_row[0]),
+                _ => [previous_row[j], previous_row[j - 1], current_row[j - 1]]
+                    .into_iter()
+                    .min_by(|(a, _), (b, _)| a.total_cmp(b)),
+            };
+            current_row[j] = match best_previous {
+                Some(previous) if previous.0 >= point_distance => previous,
+                _ => (point_distance, (i, j)),
+            };
+        }
+
+        std::mem::swap(&mut previous_row, &mut current_row);
+    }
+
+    Some(previous_row[b.len() - 1])
+}
+
+/// Function to calculate the directed Hausdorff distance from one list of points to another.
+/// Unlike the Hausdorff distance this is not symmetric: it is large if a point of `from` is far away from all points of `to`,
+/// but not if only a point of `to` is far away from all points of `from`.
+/// - `from`: The points whose closest points are searched.
+/// - `to`: The points on which the closest points are searched.
+/// - `distance`: Function calculating the distance between two points.
+/// - Returns: The largest distance of a point of `from` to its closest point of `to`.
+pub fn directed_hausdorff_distance<T>(
+    from: &[T],
+    to: &[T],
+    distance: impl Fn(&T, &T) -> f64,
+) -> f64 {
+    from.iter()
+        .map(|p1| {
+            to.iter()
+                .map(|p2| distance(p1, p2))
+                .fold(f64::MAX, f64::min)
+        })
+        .fold(f64::MIN, f64::max)
+}
+
+/// Function to calculate the Hausdorff distance between two lists of points.
+/// This is the same metric as [`geo::HausdorffDistance`], but with a custom distance between points.
+/// It is the larger of the two directed Hausdorff distances, see [`directed_hausdorff_distance`].
+/// - `a`: The points of the first track.
+/// - `b`: The points of the second track.
+/// - `distance`: Function calculating the distance between two points.
+/// - Returns: The Hausdorff distance.
+pub fn hausdorff_distance<T>(a: &[T], b: &[T], distance: impl Fn(&T, &T) -> f64) -> f64 {
+    directed_hausdorff_distance(a, b, &distance).max(directed_hausdorff_distance(b, a, &distance))
+}
+
+/// Function to calculate the Dynamic Time Warping (DTW) distance between two lists of points.
+/// The DTW distance is the minimal summed distance over all monotonic alignments of the points of both tracks,
+/// which makes it robust against different sampling rates of the tracks.
+/// - `a`: The points of the first track.
+/// - `b`: The points of the second track.
+/// - `window`: Optional width of the Sakoe-Chiba band in points. Only points whose indices differ by at most this value are aligned.
+///   The band is widened to the difference in point counts if necessary, so an alignment always exists.
+/// - `distance`: Function calculating the distance between two points.
+/// - Returns: The DTW distance, or infinity if one of the tracks is empty.
+pub fn dtw_distance_with<T>(
+    a: &[T],
+    b: &[T],
+    window: Option<usize>,
+    distance: impl Fn(&T, &T) -> f64,
+) -> f64 {
+    let n = a.len();
+    let m = b.len();
+
+    if n == 0 || m == 0 {
+        return f64::INFINITY;
+    }
+
+    let window = window.unwrap_or(n.max(m)).max(n.abs_diff(m));
+
+    // Only the previous and current row of the cost matrix are needed, which keeps the memory usage linear
+    let mut previous_row = vec![f64::INFINITY; m + 1];
+    let mut current_row = vec![f64::INFINITY; m + 1];
+    previous_row[0] = 0.0;
+
+    for i in 1..=n {
+        current_row.fill(f64::INFINITY);
+        let start = i.saturating_sub(window).max(1);
+        let end = (i + window).min(m);
+
+        for j in start..=end {
+            let cost = distance(&a[i - 1], &b[j - 1]);
+            let best_previous = previous_row[j - 1]
+                .min(previous_row[j])
+                .min(current_row[j - 1]);
+            current_row[j] = cost + best_previous;
+        }
+
+        std::mem::swap(&mut previous_row, &mut current_row);
+    }
+
+    previous_row[m]
+}
+
+/// Function to calculate the Dynamic Time Warping (DTW) distance between two LineStrings using the Euclidean distance.
+/// See [`dtw_distance_with`] for details.
+/// - `current_linestring`: The first LineString.
+/// - `reference_linestring`: The second LineString.
+/// - `window`: Optional width of the Sakoe-Chiba band in points.
+/// - Returns: The DTW distance in the units of the LineStrings, or infinity if one of the LineStrings is empty.
+pub fn dtw_distance(
+    current_linestring: &LineString,
+    reference_linestring: &LineString,
+    window: Option<usize>,
+) -> f64 {
+    let current_points: Vec<Point> = current_linestring.points().collect();
+    let reference_points: Vec<Point> = reference_linestring.points().collect();
+    dtw_distance_with(&current_points, &reference_points, window, |a, b| {
+        a.euclidean_distance(b)
+    })
+}
+
+/// Function to uniformly downsample a list of points, keeping the first and the last point.
+/// - `points`: The points.
+/// - `max_points`: The maximum number of points to keep, at least 2.
+/// - Returns: The points at evenly spaced indices, or all points if there are not more than `max_points`.
+pub fn downsample<T: Clone>(points: &[T], max_points: usize) -> Vec<T> {
+    let max_points = max_points.max(2);
+    if points.len() <= max_points {
+        return points.to_vec();
+    }
+
+    let step = (points.len() - 1) as f64 / (max_points - 1) as f64;
+    (0..max_points)
+        .map(|index| points[(index as f64 * step).round() as usize].clone())
+        .collect()
+}
+
+/// Function to calculate the area between a track and the reference path.
+/// Every pair of consecutive points of the track forms a quadrilateral together with their closest points on the reference path,
+/// whose areas are summed up. If the track crosses the reference path between two points, the quadrilateral is split at the
+/// crossing into two triangles, so the areas on both sides of the reference path are added instead of cancelling each other out.
+/// All points have to be given in a flat coordinate system.
+/// - `points`: The points of the track.
+/// - `closest_points`: The closest point on the reference path of every point of the track.
+/// - Returns: The area in the squared units of the coordinates.
+pub fn area_between(points: &[Point], closest_points: &[Point]) -> f64 {
+    // Absolute area of a triangle
+    let triangle = |a: Point, b: Point, c: Point| a.cross_prod(b, c).abs() / 2.0;
+
+    points
+        .windows(2)
+        .zip(closest_points.windows(2))
+        .map(|(current, closest)| {
+            let (c1, c2, r1, r2) = (current[0], current[1], closest[0], closest[1]);
+
+            // Check whether the track segment intersects the line between the closest points,
+            // by solving c1 + t * (c2 - c1) = r1 + u * (r2 - r1)
+            let track_direction = c2 - c1;
+            let reference_direction = r2 - r1;
+            let denominator = track_direction.x() * reference_direction.y()
+                - track_direction.y() * reference_direction.x();
+
+            if denominator != 0.0 {
+                let offset = r1 - c1;
+                let t = (offset.x() * reference_direction.y()
+                    - offset.y() * reference_direction.x())
+                    / denominator;
+                let u = (offset.x() * track_direction.y() - offset.y() * track_direction.x())
+                    / denominator;
+
+                if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+                    let crossing = c1 + track_direction * t;
+                    return triangle(c1, crossing, r1) + triangle(crossing, c2, r2);
+                }
+            }
+
+            // Otherwise the area of the quadrilateral c1, c2, r2, r1 (shoelace formula)
+            let quadrilateral = [c1, c2, r2, r1];
+            quadrilateral
+                .iter()
+                .zip(quadrilateral.iter().cycle().skip(1))
+                .map(|(a, b)| a.x() * b.y() - b.x() * a.y())
+                .sum::<f64>()
+                .abs()
+                / 2.0
+        })
+        .sum()
+}
@@ -0,0 +1,165 @@
+//! Correction of a constant offset of a track, e.g. the systematic bias of a GPS device, before it is compared.
+//!
+//! The offset is a translation in the flat projection, given in meters north and east. It can either be known beforehand
+//! or be estimated as the translation which moves the track closest onto the reference path, so the remaining distances
+//! only contain the random error.
+
+use std::fmt;
+use std::ops::Neg;
+use std::str::FromStr;
+
+use flat_projection::{FlatPoint, FlatProjection};
+use geo::{Closest, EuclideanDistance, Point};
+use gpx::Track;
+
+use crate::{
+    join_and_project_segments, move_waypoint, project_point, unproject_point, ReferenceIndex,
+};
+
+/// Maximum number of iterations of the offset estimation.
+pub const MAX_OFFSET_ITERATIONS: usize = 50;
+
+/// The estimation stops once a step moves the track by less than this distance, in meters.
+const OFFSET_CONVERGENCE_M: f64 = 0.001;
+
+/// A translation of a track (or the bias of its positions), in meters.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TrackOffset {
+    /// Distance the track is moved to the north, negative values move it to the south
+    pub north_m: f64,
+    /// Distance the track is moved to the east, negative values move it to the west
+    pub east_m: f64,
+}
+
+impl FromStr for TrackOffset {
+    type Err = String;
+
+    /// The offset is given as `north,east` in meters, e.g. `-1.5,2`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid offset {:?}, expected the meters north and east separated by a comma (e.g. -1.5,2)",
+                s
+            )
+        };
+        let (north, east) = s.split_once(',').ok_or_else(invalid)?;
+        let parse = |value: &str| {
+            value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .ok_or_else(invalid)
+        };
+        Ok(TrackOffset {
+            north_m: parse(north)?,
+            east_m: parse(east)?,
+        })
+    }
+}
+
+impl fmt::Display for TrackOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.north_m, self.east_m)
+    }
+}
+
+impl Neg for TrackOffset {
+    type Output = TrackOffset;
+
+    fn neg(self) -> Self::Output {
+        TrackOffset {
+            north_m: -self.north_m,
+            east_m: -self.east_m,
+        }
+    }
+}
+
+/// Function to move every point of a track by a translation.
+/// All other data of the points (e.g. elevation and time) is kept.
+/// - `track`: The track to move.
+/// - `offset`: The translation.
+/// - `projector`: The flat coordinate system in which the track is moved.
+/// - Returns: A copy of the track with moved points.
+pub fn offset_track(track: &Track, offset: TrackOffset, projector: &FlatProjection<f64>) -> Track {
+    let mut moved_track = track.clone();
+    for waypoint in moved_track
+        .segments
+        .iter_mut()
+        .flat_map(|segment| &mut segment.points)
+    {
+        let projected = project_point(projector, waypoint.point());
+        // The flat projection is in kilometers
+        let unprojected = unproject_point(
+            projector,
+            &FlatPoint {
+                x: projected.x + offset.east_m / 1000.0,
+                y: projected.y + offset.north_m / 1000.0,
+            },
+        );
+        *waypoint = move_waypoint(waypoint, unprojected);
+    }
+    moved_track
+}
+
+/// Function to estimate the translation which moves a track closest onto the reference path, i.e. the negated bias of the track.
+/// Starting from `initial`, the track is repeatedly moved by the mean vector from its points to their closest points on the reference path
+/// (like the iterative closest point algorithm), and the offset with the smallest average distance of all steps is returned.
+/// - `reference`: The reference track.
+/// - `track`: The track whose offset is estimated.
+/// - `initial`: The translation to start from, e.g. the negated known bias.
+/// - `projector`: The flat coordinate system in which the track is moved.
+/// - Returns: The estimated translation, `initial` if either track has no points.
+pub fn estimate_offset(
+    reference: &Track,
+    track: &Track,
+    initial: TrackOffset,
+    projector: &FlatProjection<f64>,
+) -> TrackOffset {
+    let reference_index =
+        ReferenceIndex::new(&join_and_project_segments(&reference.segments, projector));
+    let points: Vec<Point> = join_and_project_segments(&track.segments, projector)
+        .points()
+        .collect();
+    if points.is_empty() {
+        return initial;
+    }
+
+    // All calculations are done in kilometers, the unit of the flat projection
+    let (mut east, mut north) = (initial.east_m / 1000.0, initial.north_m / 1000.0);
+    let mut best: Option<(f64, f64, f64)> = None;
+    for _ in 0..MAX_OFFSET_ITERATIONS {
+        let (mut total_distance, mut step_east, mut step_north, mut count) = (0.0, 0.0, 0.0, 0);
+        for point in &points {
+            let moved = Point::new(point.x() + east, point.y() + north);
+            if let Closest::Intersection(closest) | Closest::SinglePoint(closest) =
+                reference_index.closest_point(&moved)
+            {
+                total_distance += moved.euclidean_distance(&closest);
+                step_east += closest.x() - moved.x();
+                step_north += closest.y() - moved.y();
+                count += 1;
+            }
+        }
+        if count == 0 {
+            return initial;
+        }
+
+        let average = total_distance / count as f64;
+        if best.is_none_or(|(best_average, _, _)| average < best_average) {
+            best = Some((average, east, north));
+        }
+        let (step_east, step_north) = (step_east / count as f64, step_north / count as f64);
+        if step_east.hypot(step_north) * 1000.0 < OFFSET_CONVERGENCE_M {
+            break;
+        }
+        east += step_east;
+        north += step_north;
+    }
+
+    let (_, east, north) = best.expect("At least one iteration was done");
+    TrackOffset {
+        north_m: north * 1000.0,
+        east_m: east * 1000.0,
+    }
+}
@@ -0,0 +1,187 @@
+//! Fluent construction of [`CompareOptions`], so only the options which differ from the defaults have to be given.
+//!
+//! ```
+//! use gps_path_average_distance::{CompareOptions, DistanceMode};
+//!
+//! let options = CompareOptions::builder()
+//!     .distance_mode(DistanceMode::Geodesic)
+//!     .simplify_epsilon(2.5)
+//!     .tolerance(Some(5.0))
+//!     .build();
+//! assert_eq!(options.distance_mode, DistanceMode::Geodesic);
+//! assert_eq!(options.tolerance, Some(5.0));
+//! // All other options keep their defaults
+//! assert_eq!(options.smooth_window, CompareOptions::default().smooth_window);
+//! ```
+
+use crate::{
+    CompareOptions, DistanceMode, Projection, ProjectionOrigin, ReferenceSegments, ScoreWeights,
+    SimplifyAlgorithm, SmoothingMethod, TrackOffset, Trim,
+};
+
+/// Builder of [`CompareOptions`], starting from [`CompareOptions::default`].
+/// Every setter replaces a single option, see the field of the same name of [`CompareOptions`] for its meaning.
+#[derive(Debug, Clone, Default)]
+pub struct CompareOptionsBuilder {
+    options: CompareOptions,
+}
+
+/// Macro to define a setter which replaces a single field of the options.
+macro_rules! setter {
+    ($(#[$doc:meta])* $name:ident: $type:ty) => {
+        $(#[$doc])*
+        pub fn $name(mut self, $name: $type) -> Self {
+            self.options.$name = $name;
+            self
+        }
+    };
+}
+
+impl CompareOptions {
+    /// Function to start building options from the defaults.
+    /// - Returns: A builder with the default options.
+    pub fn builder() -> CompareOptionsBuilder {
+        CompareOptionsBuilder::default()
+    }
+}
+
+impl CompareOptionsBuilder {
+    /// Function to start building options from existing ones, e.g. to change a few options of a shared configuration.
+    /// - `options`: The options to start from.
+    /// - Returns: A builder with the given options.
+    ///
+    /// ```
+    /// use gps_path_average_distance::{CompareOptions, CompareOptionsBuilder};
+    ///
+    /// let shared = CompareOptions::builder().symmetric(true).build();
+    /// let options = CompareOptionsBuilder::from_options(shared).max_points(Some(2000)).build();
+    /// assert!(options.symmetric);
+    /// assert_eq!(options.max_points, Some(2000));
+    /// ```
+    pub fn from_options(options: CompareOptions) -> Self {
+        CompareOptionsBuilder { options }
+    }
+
+    /// Function to finish building the options.
+    /// - Returns: The options with all given values.
+    pub fn build(self) -> CompareOptions {
+        self.options
+    }
+
+    setter!(
+        /// Epsilon of the simplification, in meters (or square meters for Visvalingam-Whyatt).
+        simplify_epsilon: f64
+    );
+    setter!(
+        /// Algorithm of the simplification.
+        simplify_algorithm: SimplifyAlgorithm
+    );
+    setter!(
+        /// Width of the band of the DTW distance in points, `None` for the unconstrained distance.
+        dtw_window: Option<usize>
+    );
+    setter!(
+        /// Whether the pairs of every point and its closest point are collected.
+        collect_nearest_points: bool
+    );
+    setter!(
+        /// How the distance between two points is measured.
+        distance_mode: DistanceMode
+    );
+    setter!(
+        /// Additional epsilons (in meters) of the simplified average distance.
+        epsilon_sweep: Vec<f64>
+    );
+    setter!(
+        /// Whether the elevations are included in the point distances.
+        use_elevation: bool
+    );
+    setter!(
+        /// Spacing (in meters) to which both tracks are resampled, `None` to compare the recorded points.
+        resample: Option<f64>
+    );
+    setter!(
+        /// How the compared track is smoothed.
+        smoothing: SmoothingMethod
+    );
+    setter!(
+        /// Number of points within the window of the smoothing.
+        smooth_window: usize
+    );
+    setter!(
+        /// Tolerance in meters of the tolerance statistics, `None` to skip them.
+        tolerance: Option<f64>
+    );
+    setter!(
+        /// Full width in meters of the corridor statistics, `None` to skip them.
+        corridor_width: Option<f64>
+    );
+    setter!(
+        /// Length in meters of the windows of the distances along the track, `None` to skip them.
+        window_report: Option<f64>
+    );
+    setter!(
+        /// Whether the average distance is also calculated from the reference path to the track.
+        symmetric: bool
+    );
+    setter!(
+        /// Whether the average distance is also weighted by the accuracy of the points.
+        weight_by_hdop: bool
+    );
+    setter!(
+        /// How the segments of the reference path are used.
+        reference_segments: ReferenceSegments
+    );
+    setter!(
+        /// Whether tracks running the reference path backwards are reversed.
+        auto_orient: bool
+    );
+    setter!(
+        /// Whether the duration and speeds are calculated.
+        time_stats: bool
+    );
+    setter!(
+        /// Whether the elevation profiles are compared.
+        elevation_profile: bool
+    );
+    setter!(
+        /// Whether the points are also matched to the reference path by their timestamps.
+        match_by_time: bool
+    );
+    setter!(
+        /// Coordinate system of the flat distance mode.
+        projection: Projection
+    );
+    setter!(
+        /// Where the flat projection is centered.
+        projection_origin: ProjectionOrigin
+    );
+    setter!(
+        /// Maximum number of points of the curve metrics, `None` to use all points.
+        max_points: Option<usize>
+    );
+    setter!(
+        /// Whether the gaps between segments are excluded from the lengths.
+        exclude_segment_gaps: bool
+    );
+    setter!(
+        /// How much of the start of both tracks is dropped.
+        start_trim: Option<Trim>
+    );
+    setter!(
+        /// How much of the end of both tracks is dropped.
+        end_trim: Option<Trim>
+    );
+    setter!(
+        /// Known offset which is removed from the compared track.
+        track_offset: Option<TrackOffset>
+    );
+    setter!(
+        /// Whether the offset of the compared track is estimated and removed.
+        auto_offset: bool
+    );
+    setter!(
+        /// Relative weights of the distances in the match score.
+        score_weights: ScoreWeights
+    );
+}
@@ -0,0 +1,576 @@
+//! Typed structures of the JSON output, which can be used to deserialize the results again.
+
+use serde::{Deserialize, Serialize};
+
+use crate::TrackComparison;
+
+/// Function to round a value to the given number of decimal places.
+/// - `value`: The value to round.
+/// - `precision`: The number of decimal places.
+/// - Returns: The rounded value.
+pub fn round_to_precision(value: f64, precision: usize) -> f64 {
+    let factor = 10_f64.powi(precision as i32);
+    (value * factor).round() / factor
+}
+
+/// JSON output of a position, rounded to 7 decimal places (about 1cm).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LatLonOutput {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl From<geo::Point> for LatLonOutput {
+    fn from(point: geo::Point) -> Self {
+        LatLonOutput {
+            lat: round_to_precision(point.y(), 7),
+            lon: round_to_precision(point.x(), 7),
+        }
+    }
+}
+
+/// JSON output of the comparison of a single track to the reference path.
+/// All distances and lengths are given in meters, see [`TrackComparison`] for the meaning of the fields.
+/// Values which are not available (NaN) are serialized as `null`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparisonOutput {
+    /// 1-based index of the track in its file
+    pub track_index: usize,
+    /// Name of the track
+    pub track_name: String,
+    /// 1-based index of the segment of the track, only set if the segments are compared separately
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment_index: Option<usize>,
+    /// 1-based index of the reference path, only set in the multi reference mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_index: Option<usize>,
+    /// Path of the reference file, only set in the multi reference mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_path: Option<String>,
+    pub current_track_length_m: f64,
+    pub reference_track_length_m: f64,
+    pub average_distance_m: f64,
+    pub simplified_average_distance_m: f64,
+    /// Number of points of the compared track before and after the simplification, and which fraction of them was kept
+    pub original_point_count: usize,
+    pub simplified_point_count: usize,
+    pub simplified_point_fraction: Option<f64>,
+    pub rms_distance_m: f64,
+    /// The reverse and symmetric averages are only included with --symmetric
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub average_distance_reverse_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub average_distance_symmetric_m: Option<f64>,
+    /// The weighted average is only included with --weight-by-hdop
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weighted_average_distance_m: Option<f64>,
+    pub median_distance_m: f64,
+    pub p90_distance_m: f64,
+    pub p95_distance_m: f64,
+    pub max_distance_m: f64,
+    pub max_distance_point_index: Option<usize>,
+    /// Closest point on the reference path of the point with the maximum distance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_distance_reference_point: Option<LatLonOutput>,
+    /// Distances between the ends of both tracks, not included if either track has no points
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_offset_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_offset_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_to_end_offset_m: Option<f64>,
+    /// Number of points without a closest point on the reference path, which are excluded from all point statistics
+    #[serde(default)]
+    pub indeterminate_points: usize,
+    /// 1-based index of the best matching segment of the reference path, only set with --reference-segments best-match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_segment_index: Option<usize>,
+    /// Whether the track was compared in reverse, as it runs the reference path backwards (see --auto-orient)
+    #[serde(default)]
+    pub reversed: bool,
+    pub mean_signed_offset_m: f64,
+    pub fraction_right: f64,
+    /// Components of the distances perpendicular to and along the reference path
+    pub mean_cross_track_m: f64,
+    pub mean_along_track_m: f64,
+    pub frechet_distance_m: Option<f64>,
+    pub frechet_normalized: Option<f64>,
+    /// The pair of points with the longest leash of the optimal Fréchet coupling
+    pub frechet_track_point: Option<LatLonOutput>,
+    pub frechet_reference_point: Option<LatLonOutput>,
+    pub hausdorff_distance_m: Option<f64>,
+    /// Both directed Hausdorff distances and which of them is larger (`track_to_reference`, `reference_to_track` or `equal`)
+    pub hausdorff_track_to_reference_m: Option<f64>,
+    pub hausdorff_reference_to_track_m: Option<f64>,
+    pub hausdorff_larger_direction: Option<String>,
+    pub dtw_distance_m: Option<f64>,
+    pub area_between_m2: Option<f64>,
+    /// Match quality from 0 to 100, see --score-weights
+    pub match_score: Option<f64>,
+    /// The tolerance statistics are only included if a tolerance was given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tolerance_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub within_tolerance_fraction: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub longest_out_of_tolerance_points: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub longest_out_of_tolerance_m: Option<f64>,
+    /// The corridor statistics are only included if a corridor width was given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corridor_width_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inside_corridor_fraction: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outside_corridor_length_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outside_corridor_area_m2: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indeterminate_corridor_length_m: Option<f64>,
+    /// The removed offset is only included with --track-offset or --auto-offset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset_north_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset_east_m: Option<f64>,
+    /// The time statistics are only included with --time-stats and if the track has timestamps, the speeds are always in meters per second
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_s: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub average_speed_mps: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_speed_mps: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_speed_mps: Option<f64>,
+    /// The elevation profile is only included with --elevation-profile and if both tracks have elevations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mean_elevation_diff_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_ascent_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_descent_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_ascent_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_descent_m: Option<f64>,
+    /// The time matched distances are only included with --match-by-time and if the track has timestamps within the time span of the reference path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_matched_average_distance_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_matched_max_distance_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_matched_points: Option<usize>,
+    /// Only included if an epsilon sweep was requested
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub epsilon_sweep: Vec<EpsilonSweepOutput>,
+    /// Only included if a window report was requested
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub windows: Vec<DistanceWindowOutput>,
+}
+
+/// JSON output of a single window along the compared track, see [`crate::DistanceWindow`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DistanceWindowOutput {
+    pub start_m: f64,
+    pub end_m: f64,
+    pub average_m: f64,
+    pub max_m: f64,
+}
+
+/// JSON output of a single epsilon of an epsilon sweep.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EpsilonSweepOutput {
+    pub epsilon_m: f64,
+    pub simplified_average_distance_m: f64,
+    pub simplified_point_count: usize,
+}
+
+impl ComparisonOutput {
+    /// Function to build the output of the comparison of a single track.
+    /// - `track_index`: The 1-based index of the track in its file.
+    /// - `track_name`: The name of the track.
+    /// - `comparison`: The result of the comparison.
+    /// - `precision`: The number of decimal places all values are rounded to.
+    /// - Returns: The output structure.
+    pub fn new(
+        track_index: usize,
+        track_name: &str,
+        comparison: &TrackComparison,
+        precision: usize,
+    ) -> Self {
+        let round = |value: f64| round_to_precision(value, precision);
+        ComparisonOutput {
+            track_index,
+            track_name: track_name.to_string(),
+            segment_index: None,
+            reference_index: None,
+            reference_path: None,
+            current_track_length_m: round(comparison.current_track_length_m),
+            reference_track_length_m: round(comparison.reference_track_length_m),
+            average_distance_m: round(comparison.average_distance_m),
+            simplified_average_distance_m: round(comparison.simplified_average_distance_m),
+            original_point_count: comparison.point_count,
+            simplified_point_count: comparison.simplified_point_count,
+            simplified_point_fraction: comparison.simplified_point_fraction().map(round),
+            rms_distance_m: round(comparison.rms_distance_m),
+            average_distance_reverse_m: comparison.average_distance_reverse_m.map(round),
+            weighted_average_distance_m: comparison.weighted_average_distance_m.map(round),
+            average_distance_symmetric_m: comparison.average_distance_symmetric_m.map(round),
+            median_distance_m: round(comparison.median_distance_m),
+            p90_distance_m: round(comparison.p90_distance_m),
+            p95_distance_m: round(comparison.p95_distance_m),
+            max_distance_m: round(comparison.max_distance_m),
+            max_distance_point_index: comparison.max_distance_point_index,
+            max_distance_reference_point: comparison
+                .max_distance_reference_point
+                .map(LatLonOutput::from),
+            start_offset_m: comparison.start_offset_m.map(round),
+            end_offset_m: comparison.end_offset_m.map(round),
+            start_to_end_offset_m: comparison.start_to_end_offset_m.map(round),
+            indeterminate_points: comparison.indeterminate_points,
+            reference_segment_index: comparison.reference_segment_index,
+            reversed: comparison.reversed,
+            mean_signed_offset_m: round(comparison.mean_signed_offset_m),
+            fraction_right: round(comparison.fraction_right),
+            mean_cross_track_m: round(comparison.mean_cross_track_m),
+            mean_along_track_m: round(comparison.mean_along_track_m),
+            frechet_distance_m: comparison.frechet_distance_m.map(round),
+            // The ratio is not rounded, as it is usually far below 1 and would otherwise lose all precision
+            frechet_normalized: comparison.frechet_normalized,
+            frechet_track_point: comparison.frechet_track_point.map(LatLonOutput::from),
+            frechet_reference_point: comparison.frechet_reference_point.map(LatLonOutput::from),
+            hausdorff_distance_m: comparison.hausdorff_distance_m.map(round),
+            hausdorff_track_to_reference_m: comparison.hausdorff_track_to_reference_m.map(round),
+            hausdorff_reference_to_track_m: comparison.hausdorff_reference_to_track_m.map(round),
+            hausdorff_larger_direction: comparison
+                .hausdorff_direction()
+                .map(|direction| direction.to_string()),
+            dtw_distance_m: comparison.dtw_distance_m.map(round),
+            area_between_m2: comparison.area_between_m2.map(round),
+            match_score: comparison.match_score.map(round),
+            tolerance_m: comparison
+                .tolerance
+                .as_ref()
+                .map(|tolerance| round(tolerance.tolerance_m)),
+            within_tolerance_fraction: comparison
+                .tolerance
+                .as_ref()
+                .map(|tolerance| round(tolerance.within_tolerance_fraction)),
+            longest_out_of_tolerance_points: comparison
+                .tolerance
+                .as_ref()
+                .map(|tolerance| tolerance.longest_out_of_tolerance_points),
+            longest_out_of_tolerance_m: comparison
+                .tolerance
+                .as_ref()
+                .map(|tolerance| round(tolerance.longest_out_of_tolerance_m)),
+            corridor_width_m: comparison
+                .corridor
+                .as_ref()
+                .map(|corridor| round(corridor.width_m)),
+            inside_corridor_fraction: comparison
+                .corridor
+                .as_ref()
+                .map(|corridor| round(corridor.inside_length_fraction)),
+            outside_corridor_length_m: comparison
+                .corridor
+                .as_ref()
+                .map(|corridor| round(corridor.outside_length_m)),
+            outside_corridor_area_m2: comparison
+                .corridor
+                .as_ref()
+                .map(|corridor| round(corridor.outside_area_m2)),
+            indeterminate_corridor_length_m: comparison
+                .corridor
+                .as_ref()
+                .map(|corridor| round(corridor.indeterminate_length_m)),
+            offset_north_m: comparison.track_offset.map(|offset| round(offset.north_m)),
+            offset_east_m: comparison.track_offset.map(|offset| round(offset.east_m)),
+            duration_s: comparison
+                .time_statistics
+                .as_ref()
+                .map(|statistics| round(statistics.duration_s)),
+            average_speed_mps: comparison
+                .time_statistics
+                .as_ref()
+                .map(|statistics| round(statistics.average_speed_mps)),
+            min_speed_mps: comparison
+                .time_statistics
+                .as_ref()
+                .map(|statistics| round(statistics.min_speed_mps)),
+            max_speed_mps: comparison
+                .time_statistics
+                .as_ref()
+                .map(|statistics| round(statistics.max_speed_mps)),
+            mean_elevation_diff_m: comparison
+                .elevation_profile
+                .as_ref()
+                .and_then(|profile| profile.mean_elevation_diff_m.map(round)),
+            reference_ascent_m: comparison
+                .elevation_profile
+                .as_ref()
+                .map(|profile| round(profile.reference_ascent_m)),
+            reference_descent_m: comparison
+                .elevation_profile
+                .as_ref()
+                .map(|profile| round(profile.reference_descent_m)),
+            track_ascent_m: comparison
+                .elevation_profile
+                .as_ref()
+                .map(|profile| round(profile.track_ascent_m)),
+            track_descent_m: comparison
+                .elevation_profile
+                .as_ref()
+                .map(|profile| round(profile.track_descent_m)),
+            time_matched_average_distance_m: comparison
+                .time_matched_distance
+                .as_ref()
+                .map(|distance| round(distance.average_distance_m)),
+            time_matched_max_distance_m: comparison
+                .time_matched_distance
+                .as_ref()
+                .map(|distance| round(distance.max_distance_m)),
+            time_matched_points: comparison
+                .time_matched_distance
+                .as_ref()
+                .map(|distance| distance.matched_points),
+            epsilon_sweep: comparison
+                .epsilon_sweep
+                .iter()
+                .map(|entry| EpsilonSweepOutput {
+                    epsilon_m: round(entry.epsilon_m),
+                    simplified_average_distance_m: round(entry.simplified_average_distance_m),
+                    simplified_point_count: entry.simplified_point_count,
+                })
+                .collect(),
+            windows: comparison
+                .distance_windows
+                .iter()
+                .map(|window| DistanceWindowOutput {
+                    start_m: round(window.start_m),
+                    end_m: round(window.end_m),
+                    average_m: round(window.average_m),
+                    max_m: round(window.max_m),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// JSON output of a reference path, printed before the comparisons with --json-header.
+/// The length is measured on the sphere, independent of the distance mode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceHeaderOutput {
+    /// Always "reference", to tell the header apart from the comparisons of the tracks
+    pub record: String,
+    /// 1-based index of the reference path, more than one only in the multi reference mode
+    pub reference_index: usize,
+    /// Path of the reference file
+    pub reference_path: String,
+    /// Name of the reference path
+    pub reference_name: Option<String>,
+    pub reference_length_m: f64,
+    /// Number of points of all segments of the reference path
+    pub reference_point_count: usize,
+    /// How the origin of the flat projection is chosen (`reference`, `auto-both` or `lat,lon`)
+    pub projection_origin: String,
+    /// The origin of the flat projection, `null` if it depends on the compared track (`auto-both`)
+    pub projection_origin_point: Option<LatLonOutput>,
+    /// How distances are computed (`flat` or `geodesic`)
+    pub distance_mode: String,
+}
+
+/// JSON output of a single track in the multi reference mode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiReferenceOutput {
+    /// 1-based index of the track in its file
+    pub track_index: usize,
+    /// Name of the track
+    pub track_name: String,
+    /// 1-based index of the segment of the track, only set if the segments are compared separately
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment_index: Option<usize>,
+    /// Path of the file containing the track
+    pub track_path: String,
+    /// 1-based index of the reference path with the smallest Fréchet distance
+    pub best_reference_index: Option<usize>,
+    /// Path of the reference file with the smallest Fréchet distance
+    pub best_reference_path: Option<String>,
+    /// The comparisons to every reference path
+    pub references: Vec<ComparisonOutput>,
+}
+
+/// JSON output of a single checkpoint, see [`crate::CheckpointDistance`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointOutput {
+    /// 1-based index of the checkpoint along the reference path
+    pub checkpoint_index: usize,
+    pub position: LatLonOutput,
+    /// Smallest distance of the track to the checkpoint, `null` if the track has no points
+    pub distance_m: Option<f64>,
+    /// Whether the distance is within the tolerance, only set if a tolerance was given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hit: Option<bool>,
+}
+
+/// JSON output of a single track compared to the checkpoints of the reference path (see --reference-as-checkpoints).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointsOutput {
+    /// 1-based index of the track in its file
+    pub track_index: usize,
+    /// Name of the track
+    pub track_name: String,
+    /// 1-based index of the segment of the track, only set if the segments are compared separately
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment_index: Option<usize>,
+    /// Path of the file containing the track
+    pub track_path: String,
+    pub checkpoint_count: usize,
+    /// The tolerance and the number of checkpoints within it, only included if a tolerance was given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tolerance_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hit_count: Option<usize>,
+    pub checkpoints: Vec<CheckpointOutput>,
+}
+
+/// JSON output of a single track compared while it was read (see --streaming).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamingOutput {
+    /// 1-based index of the track in its file
+    pub track_index: usize,
+    /// Name of the track
+    pub track_name: String,
+    /// Path of the file containing the track
+    pub track_path: String,
+    pub point_count: usize,
+    pub current_track_length_m: f64,
+    pub reference_track_length_m: f64,
+    /// The distances are `null` if no point of the track has a closest point on the reference path
+    pub average_distance_m: Option<f64>,
+    pub rms_distance_m: Option<f64>,
+    pub max_distance_m: Option<f64>,
+}
+
+/// JSON output of the inventory of a single track, see [`FileListingOutput`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackListingOutput {
+    /// 1-based index of the track in its file, as used by --reference-track
+    pub track_index: usize,
+    /// Name of the track, `null` for unnamed tracks
+    pub track_name: Option<String>,
+    /// Number of points of every segment of the track
+    pub segment_point_counts: Vec<usize>,
+    /// Number of points of all segments of the track
+    pub point_count: usize,
+    /// Length of the track over all segments
+    pub length_m: f64,
+}
+
+/// JSON output of the inventory of a file, printed with --list-tracks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileListingOutput {
+    /// Path of the file
+    pub path: String,
+    /// Names of the waypoints of the file, e.g. to mark a section with --section
+    pub waypoint_names: Vec<Option<String>>,
+    /// All tracks of the file
+    pub tracks: Vec<TrackListingOutput>,
+}
+
+/// JSON output of the validation of a file, printed with --validate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationOutput {
+    /// Path of the file
+    pub path: String,
+    /// How the file is used, either "reference" or "track"
+    pub role: String,
+    /// Whether the file can be parsed and has no issues
+    pub valid: bool,
+    /// Why the file could not be parsed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Number of tracks of the file
+    pub track_count: usize,
+    /// Number of points of all tracks of the file
+    pub point_count: usize,
+    /// Descriptions of all issues of the file
+    pub issues: Vec<String>,
+}
+
+/// JSON output of a pair of files with the same name, compared with --reference-dir and --track-dir.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DirectoryPairOutput {
+    /// Name of both files without their extensions
+    pub name: String,
+    /// Path of the file containing the reference path
+    pub reference_path: String,
+    /// Path of the file containing the compared track(s)
+    pub track_path: String,
+    /// The comparisons of every track of the track file to the reference path
+    pub tracks: Vec<ComparisonOutput>,
+}
+
+/// Statistics of a single metric over all reports of a report directory, see [`ReportSummaryOutput`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricSummaryOutput {
+    /// Name of the metric, matching the key in the reports
+    pub metric: String,
+    /// Number of reports in which the metric has a value
+    pub count: usize,
+    pub min: f64,
+    pub mean: f64,
+    pub max: f64,
+}
+
+/// JSON output of the `summary.json` of a report directory (see --report-dir), aggregating the reports of all compared tracks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportSummaryOutput {
+    /// Number of compared tracks, i.e. of report files
+    pub track_count: usize,
+    /// File names of all reports, relative to the report directory
+    pub reports: Vec<String>,
+    /// The statistics of every numeric metric, in the order of the fields of the reports
+    pub metrics: Vec<MetricSummaryOutput>,
+}
+
+impl ReportSummaryOutput {
+    /// Function to aggregate the reports of all compared tracks.
+    /// Every numeric top-level field of the reports is aggregated, except for the indices (fields ending in `index`).
+    /// Fields without a value (`null`) are left out of the statistics of their metric.
+    /// - `reports`: The file name and the JSON content of every report.
+    /// - `precision`: The number of decimal places of the statistics.
+    /// - Returns: The summary.
+    pub fn new(reports: &[(String, serde_json::Value)], precision: usize) -> Self {
+        let mut metrics: Vec<(String, Vec<f64>)> = Vec::new();
+        for (_, report) in reports {
+            let Some(fields) = report.as_object() else {
+                continue;
+            };
+            for (name, value) in fields {
+                let Some(value) = value.as_f64().filter(|_| !name.ends_with("index")) else {
+                    continue;
+                };
+                match metrics.iter_mut().find(|(metric, _)| metric == name) {
+                    Some((_, values)) => values.push(value),
+                    None => metrics.push((name.clone(), vec![value])),
+                }
+            }
+        }
+
+        let round = |value: f64| round_to_precision(value, precision);
+        ReportSummaryOutput {
+            track_count: reports.len(),
+            reports: reports.iter().map(|(name, _)| name.clone()).collect(),
+            metrics: metrics
+                .into_iter()
+                .map(|(metric, values)| MetricSummaryOutput {
+                    metric,
+                    count: values.len(),
+                    min: round(values.iter().copied().fold(f64::INFINITY, f64::min)),
+                    mean: round(values.iter().sum::<f64>() / values.len() as f64),
+                    max: round(values.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Cheap pre-filter which rules out tracks far away from the reference path before any expensive metric is calculated.
+
+use flat_projection::FlatProjection;
+use gpx::Track;
+
+use crate::project_point;
+
+/// Function to calculate the bounding box of a track in a flat coordinate system.
+/// - `track`: The track.
+/// - `projector`: The flat coordinate system used for projection.
+/// - Returns: The minimum and maximum projected coordinates as `((min_x, min_y), (max_x, max_y))` in kilometers, `None` for tracks without points.
+fn projected_bounding_box(
+    track: &Track,
+    projector: &FlatProjection<f64>,
+) -> Option<((f64, f64), (f64, f64))> {
+    track
+        .segments
+        .iter()
+        .flat_map(|segment| &segment.points)
+        .map(|waypoint| project_point(projector, waypoint.point()))
+        .fold(None, |bounds, point| {
+            let ((min_x, min_y), (max_x, max_y)) =
+                bounds.unwrap_or(((point.x, point.y), (point.x, point.y)));
+            Some((
+                (min_x.min(point.x), min_y.min(point.y)),
+                (max_x.max(point.x), max_y.max(point.y)),
+            ))
+        })
+}
+
+/// Function to check whether the bounding boxes of a track and the reference path overlap, allowing for a margin between them.
+/// Tracks which do not overlap can not follow the reference path, so comparing them can be skipped.
+/// - `reference`: The reference track.
+/// - `track`: The compared track.
+/// - `margin_m`: The largest gap in meters between the bounding boxes which still counts as an overlap.
+/// - `projector`: The flat coordinate system used for projection.
+/// - Returns: Whether the bounding boxes overlap, `true` if either track has no points, so that such tracks are still reported.
+pub fn bounding_boxes_overlap(
+    reference: &Track,
+    track: &Track,
+    margin_m: f64,
+    projector: &FlatProjection<f64>,
+) -> bool {
+    let (Some(reference_box), Some(track_box)) = (
+        projected_bounding_box(reference, projector),
+        projected_bounding_box(track, projector),
+    ) else {
+        return true;
+    };
+    // The projected coordinates are given in kilometers
+    let margin = margin_m / 1000.0;
+    let ((reference_min_x, reference_min_y), (reference_max_x, reference_max_y)) = reference_box;
+    let ((track_min_x, track_min_y), (track_max_x, track_max_y)) = track_box;
+
+    track_min_x <= reference_max_x + margin
+        && track_max_x >= reference_min_x - margin
+        && track_min_y <= reference_max_y + margin
+        && track_max_y >= reference_min_y - margin
+}
@@ -0,0 +1,104 @@
+//! Resampling of tracks to a fixed spacing between their points.
+
+use flat_projection::{FlatPoint, FlatProjection};
+use geo::Coord;
+use gpx::{Track, TrackSegment, Waypoint};
+
+use crate::{project_point, unproject_point};
+
+/// Function to resample a track to evenly spaced points along its length.
+/// All segments of the track are joined (like for all other calculations) and the points are linearly interpolated
+/// in the flat projection, the first and last point of the track are always kept.
+/// Elevations and HDOP values are interpolated as well, if both neighbouring points have one, so the resampled points
+/// keep their weight for [`CompareOptions::weight_by_hdop`](crate::CompareOptions::weight_by_hdop).
+/// - `track`: The track to resample.
+/// - `spacing`: The distance between two consecutive points of the resampled track, in meters.
+/// - `projector`: The flat coordinate system in which the points are interpolated.
+/// - Returns: A copy of the track with a single segment containing the resampled points.
+pub fn resample_track(track: &Track, spacing: f64, projector: &FlatProjection<f64>) -> Track {
+    // The flat projection is in kilometers
+    let spacing = spacing / 1000.0;
+
+    let points: Vec<(Coord, Option<f64>, Option<f64>)> = track
+        .segments
+        .iter()
+        .flat_map(|segment| &segment.points)
+        .map(|waypoint| {
+            let projected = project_point(projector, waypoint.point());
+            (
+                Coord {
+                    x: projected.x,
+                    y: projected.y,
+                },
+                waypoint.elevation,
+                waypoint.hdop,
+            )
+        })
+        .collect();
+
+    let mut resampled: Vec<(Coord, Option<f64>, Option<f64>)> = Vec::new();
+    if let Some(&first) = points.first() {
+        resampled.push(first);
+    }
+
+    // Walk along the track and emit a point every time another `spacing` kilometers have been covered
+    // `next_distance` is the distance from the start of the current line at which the next point is placed
+    let mut next_distance = spacing;
+    for window in points.windows(2) {
+        let ((start, start_elevation, start_hdop), (end, end_elevation, end_hdop)) =
+            (window[0], window[1]);
+        let length = (end.x - start.x).hypot(end.y - start.y);
+        while next_distance < length {
+            let fraction = next_distance / length;
+            resampled.push((
+                start + (end - start) * fraction,
+                interpolate(start_elevation, end_elevation, fraction),
+                interpolate(start_hdop, end_hdop, fraction),
+            ));
+            next_distance += spacing;
+        }
+        next_distance -= length;
+    }
+
+    // The last point is always kept, unless it would duplicate the last resampled point
+    if points.len() >= 2 {
+        let last = points[points.len() - 1];
+        if resampled.last().map(|(coord, _, _)| *coord) != Some(last.0) {
+            resampled.push(last);
+        }
+    }
+
+    let mut segment = TrackSegment::new();
+    segment.points = resampled
+        .into_iter()
+        .map(|(coord, elevation, hdop)| {
+            let unprojected = unproject_point(
+                projector,
+                &FlatPoint {
+                    x: coord.x,
+                    y: coord.y,
+                },
+            );
+            let mut waypoint = Waypoint::new(unprojected);
+            waypoint.elevation = elevation;
+            waypoint.hdop = hdop;
+            waypoint
+        })
+        .collect();
+
+    let mut resampled_track = track.clone();
+    resampled_track.segments = vec![segment];
+    resampled_track
+}
+
+/// Function to linearly interpolate an optional value of a point between two neighbouring points.
+/// - `start`: The value of the point before.
+/// - `end`: The value of the point after.
+/// - `fraction`: The position between both points, 0 at the point before and 1 at the point after.
+/// - Returns: The interpolated value, `None` if either point has no value.
+fn interpolate(start: Option<f64>, end: Option<f64>, fraction: f64) -> Option<f64> {
+    match (start, end) {
+        (Some(start), Some(end)) => Some(start + (end - start) * fraction),
+        _ => None,
+    }
+}
@@ -0,0 +1,110 @@
+//! A single "match quality" score of a comparison, e.g. for dashboards which should not show several distances.
+//!
+//! Each of the Fréchet, Hausdorff and average distance is mapped to a score from 0 to 100 which halves with every
+//! [`HALF_SCORE_DISTANCE_M`] of distance, i.e. `100 * 0.5^(distance / HALF_SCORE_DISTANCE_M)`. A perfect overlay
+//! (all distances zero) scores 100, a distance of 10m scores 50, 20m scores 25 and so on. The match score is the
+//! weighted mean of these scores.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Distance in meters at which the score of a single distance is halved.
+pub const HALF_SCORE_DISTANCE_M: f64 = 10.0;
+
+/// Relative weights of the distances in the match score.
+/// The weights do not have to add up to one, only their ratio matters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreWeights {
+    pub frechet: f64,
+    pub hausdorff: f64,
+    pub average: f64,
+}
+
+impl Default for ScoreWeights {
+    /// All distances are weighted equally.
+    fn default() -> Self {
+        ScoreWeights {
+            frechet: 1.0,
+            hausdorff: 1.0,
+            average: 1.0,
+        }
+    }
+}
+
+impl FromStr for ScoreWeights {
+    type Err = String;
+
+    /// The weights are given as `frechet,hausdorff,average`, e.g. `1,1,2`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid score weights {:?}, expected three non-negative weights of the Fréchet, Hausdorff and average distance separated by commas (e.g. 1,1,2)",
+                s
+            )
+        };
+        let weights = s
+            .split(',')
+            .map(|weight| {
+                weight
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|weight| weight.is_finite() && *weight >= 0.0)
+                    .ok_or_else(invalid)
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+        match weights.as_slice() {
+            [frechet, hausdorff, average] if frechet + hausdorff + average > 0.0 => {
+                Ok(ScoreWeights {
+                    frechet: *frechet,
+                    hausdorff: *hausdorff,
+                    average: *average,
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for ScoreWeights {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.frechet, self.hausdorff, self.average)
+    }
+}
+
+/// Function to map a distance to a score from 0 to 100, see the module documentation.
+/// - `distance_m`: The distance in meters.
+/// - Returns: 100 for a distance of zero, halved with every [`HALF_SCORE_DISTANCE_M`].
+pub fn distance_score(distance_m: f64) -> f64 {
+    100.0 * 0.5_f64.powf(distance_m / HALF_SCORE_DISTANCE_M)
+}
+
+/// Function to calculate the match score as weighted mean of the scores of the single distances.
+/// Distances which are not available (e.g. the curve metrics of single point tracks) are left out of the mean.
+/// - `frechet_m`: The Fréchet distance in meters.
+/// - `hausdorff_m`: The Hausdorff distance in meters.
+/// - `average_m`: The average distance in meters.
+/// - `weights`: The relative weights of the distances.
+/// - Returns: The score from 0 to 100, `None` if no distance with a weight above zero is available.
+pub fn match_score(
+    frechet_m: Option<f64>,
+    hausdorff_m: Option<f64>,
+    average_m: Option<f64>,
+    weights: &ScoreWeights,
+) -> Option<f64> {
+    let (total, total_weight) = [
+        (frechet_m, weights.frechet),
+        (hausdorff_m, weights.hausdorff),
+        (average_m, weights.average),
+    ]
+    .into_iter()
+    .filter_map(|(distance, weight)| {
+        distance
+            .filter(|distance| !distance.is_nan() && weight > 0.0)
+            .map(|distance| (distance_score(distance) * weight, weight))
+    })
+    .fold((0.0, 0.0), |(total, total_weight), (score, weight)| {
+        (total + score, total_weight + weight)
+    });
+    (total_weight > 0.0).then(|| total / total_weight)
+}
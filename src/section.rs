@@ -0,0 +1,211 @@
+//! Clipping of tracks to a section of the reference path, which is marked by two named waypoints.
+
+use std::fmt;
+use std::str::FromStr;
+
+use flat_projection::FlatProjection;
+use geo::{HaversineDistance, Point};
+use gpx::{Track, Waypoint};
+
+use crate::project_point;
+use crate::trim::retain_point_range;
+
+/// A section of the reference path between two named waypoints.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// Name of the waypoint marking the start of the section
+    pub start: String,
+    /// Name of the waypoint marking the end of the section
+    pub end: String,
+}
+
+impl FromStr for Section {
+    type Err = String;
+
+    /// The names of both waypoints are separated with a colon, e.g. `Start:Summit`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some((start, end)) if !start.trim().is_empty() && !end.trim().is_empty() => {
+                Ok(Section {
+                    start: start.trim().to_string(),
+                    end: end.trim().to_string(),
+                })
+            }
+            _ => Err(format!(
+                "invalid section {:?}, expected the names of two waypoints separated with a colon (e.g. Start:Summit)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.start, self.end)
+    }
+}
+
+/// Errors which can occur while locating a section on the reference path.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SectionError {
+    /// No waypoint has the given name
+    WaypointNotFound(String),
+    /// The start waypoint is not before the end waypoint along the reference path, containing their positions as fraction of its length
+    WrongOrder {
+        start: String,
+        end: String,
+        start_fraction: f64,
+        end_fraction: f64,
+    },
+}
+
+impl fmt::Display for SectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SectionError::WaypointNotFound(name) => write!(f, "no waypoint is named {:?}", name),
+            SectionError::WrongOrder {
+                start,
+                end,
+                start_fraction,
+                end_fraction,
+            } => write!(
+                f,
+                "the start waypoint {:?} (at {:.1}% of the reference path) is not before the end waypoint {:?} (at {:.1}%)",
+                start,
+                start_fraction * 100.0,
+                end,
+                end_fraction * 100.0
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SectionError {}
+
+/// Function to calculate the distance along a track to every point, measured over all segments of the track.
+/// - `track`: The track.
+/// - Returns: The distance (in meters) from the first point to every point.
+pub(crate) fn cumulative_lengths(track: &Track) -> Vec<f64> {
+    let mut length = 0.0;
+    let mut previous: Option<Point> = None;
+    track
+        .segments
+        .iter()
+        .flat_map(|segment| &segment.points)
+        .map(|waypoint| {
+            let point = waypoint.point();
+            length += previous.map_or(0.0, |previous| previous.haversine_distance(&point));
+            previous = Some(point);
+            length
+        })
+        .collect()
+}
+
+/// Function to locate a point on a track, as fraction of the length of the track.
+/// The point is located at the closest point on the track, which is searched in the flat projection.
+/// - `track`: The track.
+/// - `point`: The point to locate.
+/// - `projector`: The flat coordinate system in which the closest point is searched.
+/// - Returns: The distance along the track to the closest point divided by the length of the track, between 0 and 1.
+pub fn locate_fraction(track: &Track, point: Point, projector: &FlatProjection<f64>) -> f64 {
+    let lengths = cumulative_lengths(track);
+    let total_length = lengths.last().copied().unwrap_or(0.0);
+    if total_length <= 0.0 {
+        return 0.0;
+    }
+
+    let projected: Vec<(f64, f64)> = track
+        .segments
+        .iter()
+        .flat_map(|segment| &segment.points)
+        .map(|waypoint| {
+            let flat = project_point(projector, waypoint.point());
+            (flat.x, flat.y)
+        })
+        .collect();
+    let target = project_point(projector, point);
+
+    // Search the closest point on every line of the track, remembering the distance along the track to it
+    let mut closest = (f64::INFINITY, 0.0);
+    for (index, pair) in projected.windows(2).enumerate() {
+        let ((ax, ay), (bx, by)) = (pair[0], pair[1]);
+        let (dx, dy) = (bx - ax, by - ay);
+        let line_length_squared = dx * dx + dy * dy;
+        let t = if line_length_squared > 0.0 {
+            (((target.x - ax) * dx + (target.y - ay) * dy) / line_length_squared).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let distance = (ax + t * dx - target.x).hypot(ay + t * dy - target.y);
+        if distance < closest.0 {
+            let along = lengths[index] + t * (lengths[index + 1] - lengths[index]);
+            closest = (distance, along);
+        }
+    }
+
+    closest.1 / total_length
+}
+
+/// Function to locate the section on the reference path.
+/// Waypoint names are matched case-insensitively, if multiple waypoints have the same name the first one is used.
+/// - `reference`: The reference path.
+/// - `waypoints`: The named waypoints, usually of the file containing the reference path.
+/// - `section`: The names of the waypoints marking the section.
+/// - `projector`: The flat coordinate system in which the waypoints are located on the reference path.
+/// - Returns: The start and end of the section as fractions of the length of the reference path.
+pub fn locate_section(
+    reference: &Track,
+    waypoints: &[Waypoint],
+    section: &Section,
+    projector: &FlatProjection<f64>,
+) -> Result<(f64, f64), SectionError> {
+    let find_waypoint = |name: &str| {
+        waypoints
+            .iter()
+            .find(|waypoint| {
+                waypoint.name.as_ref().is_some_and(|waypoint_name| {
+                    waypoint_name.to_lowercase() == name.to_lowercase()
+                })
+            })
+            .ok_or_else(|| SectionError::WaypointNotFound(name.to_string()))
+    };
+    let start = find_waypoint(&section.start)?;
+    let end = find_waypoint(&section.end)?;
+
+    let start_fraction = locate_fraction(reference, start.point(), projector);
+    let end_fraction = locate_fraction(reference, end.point(), projector);
+    if start_fraction >= end_fraction {
+        return Err(SectionError::WrongOrder {
+            start: section.start.clone(),
+            end: section.end.clone(),
+            start_fraction,
+            end_fraction,
+        });
+    }
+
+    Ok((start_fraction, end_fraction))
+}
+
+/// Function to clip a track to a section given as fractions of its length.
+/// Like [`crate::trim_track`], the points outside of the section are dropped without interpolating a new first or last point.
+/// - `track`: The track to clip.
+/// - `start_fraction`: The start of the section as fraction of the length of the track.
+/// - `end_fraction`: The end of the section as fraction of the length of the track.
+/// - Returns: A copy of the track only containing the points within the section.
+pub fn clip_track(track: &Track, start_fraction: f64, end_fraction: f64) -> Track {
+    let lengths = cumulative_lengths(track);
+    let total_length = lengths.last().copied().unwrap_or(0.0);
+
+    let start_length = start_fraction * total_length;
+    let end_length = end_fraction * total_length;
+    let start = lengths
+        .iter()
+        .position(|&length| length >= start_length)
+        .unwrap_or(lengths.len());
+    let end = lengths
+        .iter()
+        .rposition(|&length| length <= end_length)
+        .map_or(0, |index| index + 1);
+
+    retain_point_range(track, start..end.max(start))
+}
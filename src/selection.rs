@@ -0,0 +1,142 @@
+//! Selection of a single track out of a file containing multiple tracks.
+
+use std::fmt;
+use std::str::FromStr;
+
+use gpx::Track;
+
+/// Selects a track either by its 1-based index or by its name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrackSelector {
+    /// 1-based index of the track, matching the numbering of the console output
+    Index(usize),
+    /// Name of the track, matched case-insensitively
+    Name(String),
+}
+
+impl FromStr for TrackSelector {
+    type Err = String;
+
+    /// Anything which parses as a number is treated as an index, everything else as a name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().parse::<usize>() {
+            Ok(0) => Err("track indices start at 1".to_string()),
+            Ok(index) => Ok(TrackSelector::Index(index)),
+            Err(_) if s.trim().is_empty() => Err("the track name must not be empty".to_string()),
+            Err(_) => Ok(TrackSelector::Name(s.trim().to_string())),
+        }
+    }
+}
+
+impl fmt::Display for TrackSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackSelector::Index(index) => write!(f, "#{}", index),
+            TrackSelector::Name(name) => write!(f, "{:?}", name),
+        }
+    }
+}
+
+/// Errors which can occur while selecting a track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectionError {
+    /// The index is larger than the number of tracks
+    IndexOutOfRange { index: usize, track_count: usize },
+    /// No track has the given name
+    NameNotFound(String),
+    /// Multiple tracks have the given name, containing their 1-based indices
+    AmbiguousName { name: String, indices: Vec<usize> },
+}
+
+impl fmt::Display for SelectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectionError::IndexOutOfRange { index, track_count } => write!(
+                f,
+                "track #{} does not exist, the file only contains {} track(s)",
+                index, track_count
+            ),
+            SelectionError::NameNotFound(name) => write!(f, "no track is named {:?}", name),
+            SelectionError::AmbiguousName { name, indices } => write!(
+                f,
+                "the name {:?} is ambiguous, it matches the tracks {}. Please select the track by its index instead",
+                name,
+                indices
+                    .iter()
+                    .map(|index| format!("#{}", index))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SelectionError {}
+
+/// Function to select a single track.
+/// - `tracks`: The tracks to select from.
+/// - `selector`: The index or name of the track.
+/// - Returns: The 0-based index of the selected track.
+pub fn select_track(tracks: &[Track], selector: &TrackSelector) -> Result<usize, SelectionError> {
+    match selector {
+        TrackSelector::Index(index) => {
+            if *index == 0 || *index > tracks.len() {
+                Err(SelectionError::IndexOutOfRange {
+                    index: *index,
+                    track_count: tracks.len(),
+                })
+            } else {
+                Ok(index - 1)
+            }
+        }
+        TrackSelector::Name(name) => {
+            let matches: Vec<usize> = tracks
+                .iter()
+                .enumerate()
+                .filter(|(_, track)| {
+                    track
+                        .name
+                        .as_ref()
+                        .is_some_and(|track_name| track_name.to_lowercase() == name.to_lowercase())
+                })
+                .map(|(index, _)| index)
+                .collect();
+
+            match matches.as_slice() {
+                [] => Err(SelectionError::NameNotFound(name.clone())),
+                [index] => Ok(*index),
+                _ => Err(SelectionError::AmbiguousName {
+                    name: name.clone(),
+                    indices: matches.iter().map(|index| index + 1).collect(),
+                }),
+            }
+        }
+    }
+}
+
+/// Function to remove the track used as reference path from the compared tracks of its file, so it is not compared to itself.
+/// The other tracks keep their order and their origins, so they are still reported with their index in the file.
+/// - `tracks`: The compared tracks of the file, either its tracks or (if the segments are compared separately) the segments of its tracks.
+/// - `origins`: For every compared track the 0-based index of its track in the file and the index of its segment, if it is a segment.
+/// - `reference_index`: The 0-based index of the track in the file which is used as reference path.
+/// - `keep_reference_track`: Whether the reference track is compared to itself anyway, which keeps all tracks.
+/// - Returns: The number of removed tracks, e.g. all segments of the reference track.
+pub fn exclude_reference_track(
+    tracks: &mut Vec<Track>,
+    origins: &mut Vec<(usize, Option<usize>)>,
+    reference_index: usize,
+    keep_reference_track: bool,
+) -> usize {
+    if keep_reference_track {
+        return 0;
+    }
+    let track_count = tracks.len();
+    let (kept_tracks, kept_origins) = tracks
+        .drain(..)
+        .zip(origins.drain(..))
+        .filter(|(_, (index, _))| *index != reference_index)
+        .unzip();
+    *tracks = kept_tracks;
+    *origins = kept_origins;
+    track_count - tracks.len()
+}
@@ -0,0 +1,201 @@
+//! Long-running server mode, comparing tracks given as newline-delimited JSON (NDJSON) requests.
+//!
+//! Every line of the input is a request like
+//! `{"id": 1, "reference": {"path": "reference.gpx"}, "track": {"base64": "H4sI..."}}`,
+//! for which exactly one line is written to the output. The response either contains the comparison of every track
+//! of the track file (`tracks`) or an `error` message, together with the `id` of the request if it had one.
+
+use std::io::{BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+
+use base64::Engine;
+use gpx::Gpx;
+use serde::{Deserialize, Serialize};
+
+use crate::input::{read_file, read_reader};
+use crate::output::ComparisonOutput;
+use crate::{
+    check_reference, compare_tracks_with_projection, first_track_or_route, track_projection,
+    CompareOptions,
+};
+
+/// Source of a file of a request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServeInput {
+    /// Path of the file, its format is detected like for files given on the command line
+    Path(PathBuf),
+    /// Base64 encoded content of the file, its format is detected by sniffing the content
+    Base64(String),
+}
+
+impl ServeInput {
+    /// Function to read the file of the request.
+    /// - Returns: The content of the file converted to a GPX structure, or an error message.
+    fn read(&self) -> Result<Gpx, String> {
+        match self {
+            ServeInput::Path(path) => {
+                read_file(path).map_err(|error| format!("failed to read {:?}: {}", path, error))
+            }
+            ServeInput::Base64(data) => {
+                let data = base64::engine::general_purpose::STANDARD
+                    .decode(data.trim())
+                    .map_err(|error| format!("invalid base64: {}", error))?;
+                read_reader(data.as_slice()).map_err(|error| error.to_string())
+            }
+        }
+    }
+}
+
+/// A single request of the server mode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServeRequest {
+    /// Arbitrary value identifying the request, which is repeated in the response
+    #[serde(default)]
+    pub id: Option<serde_json::Value>,
+    /// File containing the reference path, its first track (or without tracks its first route) is used
+    pub reference: ServeInput,
+    /// File containing the track(s) to compare
+    pub track: ServeInput,
+}
+
+/// The response to a single request of the server mode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServeResponse {
+    /// The `id` of the request, `null` if the request had none or could not be parsed
+    pub id: Option<serde_json::Value>,
+    /// The comparison of every track of the track file which contains points
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracks: Option<Vec<ComparisonOutput>>,
+    /// Why the request failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Function to handle a single request.
+/// - `request`: The request.
+/// - `opts`: The options used for all comparisons.
+/// - `precision`: The number of decimal places of the values in the response.
+/// - Returns: The comparisons of all tracks, or an error message.
+fn compare_request(
+    request: &ServeRequest,
+    opts: &CompareOptions,
+    precision: usize,
+) -> Result<Vec<ComparisonOutput>, String> {
+    let reference_gpx = request
+        .reference
+        .read()
+        .map_err(|error| format!("reference: {}", error))?;
+    let track_gpx = request
+        .track
+        .read()
+        .map_err(|error| format!("track: {}", error))?;
+
+    let reference = &first_track_or_route(&reference_gpx)
+        .ok_or_else(|| "reference: the file does not contain any tracks or routes".to_string())?;
+    check_reference(reference).map_err(|error| format!("reference: {}", error))?;
+    track_gpx
+        .tracks
+        .iter()
+        .enumerate()
+        .map(|(index, track)| {
+            compare_tracks_with_projection(
+                reference,
+                track,
+                opts,
+                &track_projection(reference, track, opts.projection_origin),
+            )
+            .map(|comparison| (index, track, comparison))
+            .map_err(|error| format!("reference: {}", error))
+        })
+        // Tracks without any points can not be compared, like on the command line they are skipped
+        .filter(|result| {
+            result.as_ref().map_or(true, |(_, _, comparison)| {
+                comparison.point_count > 0 || comparison.indeterminate_points > 0
+            })
+        })
+        .map(|result| {
+            result.map(|(index, track, comparison)| {
+                ComparisonOutput::new(
+                    index + 1,
+                    track.name.as_deref().unwrap_or("-- Unnamed --"),
+                    &comparison,
+                    precision,
+                )
+            })
+        })
+        .collect()
+}
+
+/// Function to answer a single line of the input.
+/// Malformed requests and failing comparisons are answered with an error, so a single request can never stop the server.
+/// - `line`: The line containing the JSON request.
+/// - `opts`: The options used for all comparisons.
+/// - `precision`: The number of decimal places of the values in the response.
+/// - Returns: The response to the request.
+pub fn handle_request(line: &str, opts: &CompareOptions, precision: usize) -> ServeResponse {
+    let request: ServeRequest = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(error) => {
+            // The id can still be recovered if only the rest of the request is malformed
+            let id = serde_json::from_str::<serde_json::Value>(line)
+                .ok()
+                .and_then(|value| value.get("id").cloned());
+            return ServeResponse {
+                id,
+                tracks: None,
+                error: Some(format!("invalid request: {}", error)),
+            };
+        }
+    };
+
+    // A panic (e.g. of a parser on unusual input) only fails this request, so the server keeps running
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        compare_request(&request, opts, precision)
+    }))
+    .unwrap_or_else(|_| Err("the comparison failed unexpectedly".to_string()));
+    match result {
+        Ok(tracks) => ServeResponse {
+            id: request.id,
+            tracks: Some(tracks),
+            error: None,
+        },
+        Err(error) => ServeResponse {
+            id: request.id,
+            tracks: None,
+            error: Some(error),
+        },
+    }
+}
+
+/// Function to answer requests until the input ends.
+/// Every non-empty line of the input is answered with exactly one line in the output, which is flushed immediately.
+/// - `input`: The input providing one JSON request per line, usually the standard input.
+/// - `output`: The output receiving one JSON response per line, usually the standard output.
+/// - `opts`: The options used for all comparisons.
+/// - `precision`: The number of decimal places of the values in the responses.
+/// - Returns: An error if reading the input or writing the output failed.
+pub fn serve<R: BufRead, W: Write>(
+    mut input: R,
+    mut output: W,
+    opts: &CompareOptions,
+    precision: usize,
+) -> std::io::Result<()> {
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        if input.read_until(b'\n', &mut buffer)? == 0 {
+            return Ok(());
+        }
+        // Invalid UTF-8 is not an error of the input itself, it only makes this request malformed
+        let line = String::from_utf8_lossy(&buffer);
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = handle_request(&line, opts, precision);
+        let json = serde_json::to_string(&response).expect("The response can always be serialized");
+        writeln!(output, "{}", json)?;
+        output.flush()?;
+    }
+}
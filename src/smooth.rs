@@ -0,0 +1,113 @@
+//! Smoothing of tracks to reduce the jitter of the GPS positions.
+
+use std::fmt;
+use std::str::FromStr;
+
+use flat_projection::{FlatPoint, FlatProjection};
+use gpx::Track;
+
+use crate::{move_waypoint, project_point, unproject_point};
+
+/// How the points of a track are smoothed before it is compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SmoothingMethod {
+    /// The recorded points are used as they are
+    #[default]
+    None,
+    /// Every point is replaced by the average of the points within the window
+    MovingAverage,
+    /// Every point is replaced by the value of a quadratic polynomial fitted to the points within the window,
+    /// which keeps the shape of curves better than the moving average
+    SavitzkyGolay,
+}
+
+impl FromStr for SmoothingMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(SmoothingMethod::None),
+            "moving-average" => Ok(SmoothingMethod::MovingAverage),
+            "savitzky-golay" => Ok(SmoothingMethod::SavitzkyGolay),
+            _ => Err(format!("unknown smoothing method: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for SmoothingMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SmoothingMethod::None => write!(f, "none"),
+            SmoothingMethod::MovingAverage => write!(f, "moving-average"),
+            SmoothingMethod::SavitzkyGolay => write!(f, "savitzky-golay"),
+        }
+    }
+}
+
+/// Function to calculate the weights of the points within a window of the given half width.
+/// - `method`: The smoothing method.
+/// - `half_width`: The number of points on each side of the smoothed point.
+/// - Returns: The weights of the `2 * half_width + 1` points, summing up to 1.
+fn weights(method: SmoothingMethod, half_width: usize) -> Vec<f64> {
+    let width = 2 * half_width + 1;
+    match method {
+        SmoothingMethod::None => (0..width)
+            .map(|index| if index == half_width { 1.0 } else { 0.0 })
+            .collect(),
+        SmoothingMethod::MovingAverage => vec![1.0 / width as f64; width],
+        SmoothingMethod::SavitzkyGolay => {
+            // Closed form of the convolution coefficients of a quadratic fit, e.g. [-3, 12, 17, 12, -3] / 35 for 5 points
+            let m = half_width as f64;
+            let denominator = (2.0 * m - 1.0) * (2.0 * m + 1.0) * (2.0 * m + 3.0);
+            (0..width)
+                .map(|index| {
+                    let i = index as f64 - m;
+                    3.0 * (3.0 * m * m + 3.0 * m - 1.0 - 5.0 * i * i) / denominator
+                })
+                .collect()
+        }
+    }
+}
+
+/// Function to smooth the points of a track.
+/// The points are smoothed in the flat projection, every segment on its own. Close to the ends of a segment the window
+/// shrinks symmetrically, so the first and last point of every segment are preserved.
+/// All other data of the points (e.g. elevation and time) is kept.
+/// - `track`: The track to smooth.
+/// - `method`: The smoothing method.
+/// - `window`: The number of points within the window, an odd number of at least 3.
+/// - `projector`: The flat coordinate system in which the points are smoothed.
+/// - Returns: A copy of the track with smoothed points.
+pub fn smooth_track(
+    track: &Track,
+    method: SmoothingMethod,
+    window: usize,
+    projector: &FlatProjection<f64>,
+) -> Track {
+    let mut smoothed_track = track.clone();
+    if method == SmoothingMethod::None {
+        return smoothed_track;
+    }
+
+    let max_half_width = window / 2;
+    for segment in &mut smoothed_track.segments {
+        let projected: Vec<FlatPoint<f64>> = segment
+            .points
+            .iter()
+            .map(|waypoint| project_point(projector, waypoint.point()))
+            .collect();
+
+        for (index, waypoint) in segment.points.iter_mut().enumerate() {
+            let half_width = max_half_width.min(index).min(projected.len() - 1 - index);
+            let (mut x, mut y) = (0.0, 0.0);
+            for (offset, weight) in weights(method, half_width).into_iter().enumerate() {
+                let point = projected[index - half_width + offset];
+                x += point.x * weight;
+                y += point.y * weight;
+            }
+            *waypoint = move_waypoint(waypoint, unproject_point(projector, &FlatPoint { x, y }));
+        }
+    }
+
+    smoothed_track
+}
@@ -0,0 +1,219 @@
+//! Comparison of tracks which are read incrementally, see [`crate::input::stream_file`].
+//!
+//! Every point is compared to the reference path as soon as it is read and only added to running sums, so the memory
+//! needed does not depend on the number of points of the compared tracks. This only works for the metrics which are
+//! sums over the points (the lengths, the average, the RMS and the maximum distance). Metrics which need all points at
+//! once (e.g. the median, the simplified average or the curve metrics) are not available.
+
+use flat_projection::FlatProjection;
+use geo::{Closest, Coord, LineString, Point};
+use gpx::{Track, TrackSegment};
+
+use crate::input::StreamEvent;
+use crate::{
+    join_and_project_segments, join_segments, project_point, reference_origin, segment_gaps,
+    CompareOptions, DistanceMode, Projection, ReferenceIndex, UtmProjection,
+};
+
+/// The metrics of a track compared while it was read.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StreamingStatistics {
+    /// Name of the track, if the file contains one
+    pub track_name: Option<String>,
+    /// Number of points of the track
+    pub point_count: usize,
+    /// Length of the track in meters
+    pub current_track_length_m: f64,
+    /// Length of the reference path in meters
+    pub reference_track_length_m: f64,
+    /// Average distance of all points to the reference path, `None` if no point has a closest point
+    pub average_distance_m: Option<f64>,
+    /// Root mean square of the distances of all points to the reference path, `None` if no point has a closest point
+    pub rms_distance_m: Option<f64>,
+    /// Largest distance of a point to the reference path, `None` if no point has a closest point
+    pub max_distance_m: Option<f64>,
+}
+
+/// Running sums of the track which is currently read.
+#[derive(Default)]
+struct TrackState {
+    statistics: StreamingStatistics,
+    /// The previous point in the coordinates of the distance mode
+    previous: Option<Point>,
+    /// Whether the next point is the first one of a new segment
+    segment_start: bool,
+    total_distance: f64,
+    total_squared_distance: f64,
+    /// Number of points with a closest point on the reference path
+    compared_points: usize,
+}
+
+/// Compares the points of tracks to a reference path while they are read.
+///
+/// Pass every [`StreamEvent`] of the tracks to [`StreamingComparison::handle`], then get the metrics of all tracks
+/// from [`StreamingComparison::finish`].
+pub struct StreamingComparison {
+    reference_index: ReferenceIndex,
+    reference_track_length_m: f64,
+    distance_mode: DistanceMode,
+    projector: FlatProjection<f64>,
+    utm: Option<UtmProjection>,
+    exclude_segment_gaps: bool,
+    finished: Vec<StreamingStatistics>,
+    current: Option<TrackState>,
+}
+
+impl StreamingComparison {
+    /// Function to prepare the comparison to a reference path.
+    /// Only the distance mode, the projection and whether the segment gaps are excluded are used from the options.
+    /// - `reference`: The reference track, which is loaded completely.
+    /// - `opts`: The options used for the comparison.
+    /// - `projector`: The flat coordinate system used for projection.
+    /// - Returns: The comparison, without any compared track.
+    pub fn new(reference: &Track, opts: &CompareOptions, projector: &FlatProjection<f64>) -> Self {
+        let distance_mode = opts.distance_mode;
+        let utm = (distance_mode == DistanceMode::Flat && opts.projection == Projection::Utm)
+            .then(|| UtmProjection::for_point(reference_origin(reference)));
+        let reference_linestring = match (distance_mode, utm) {
+            (DistanceMode::Flat, Some(utm)) => join_segments(&reference.segments)
+                .points()
+                .map(|point| project_utm(&utm, point))
+                .collect(),
+            (DistanceMode::Flat, None) => join_and_project_segments(&reference.segments, projector),
+            (DistanceMode::Geodesic, _) => join_segments(&reference.segments),
+        };
+
+        let mut comparison = StreamingComparison {
+            reference_index: ReferenceIndex::with_distance_mode(
+                &reference_linestring,
+                distance_mode,
+            ),
+            reference_track_length_m: 0.0,
+            distance_mode,
+            projector: *projector,
+            utm,
+            exclude_segment_gaps: opts.exclude_segment_gaps,
+            finished: Vec::new(),
+            current: None,
+        };
+        comparison.reference_track_length_m =
+            comparison.length(&reference_linestring, &reference.segments);
+        comparison
+    }
+
+    /// Function to process the next part of the compared tracks.
+    /// Points before the first track or segment start one implicitly.
+    /// - `event`: The next part of the tracks.
+    pub fn handle(&mut self, event: StreamEvent) {
+        match event {
+            StreamEvent::Track => self.finish_track(),
+            StreamEvent::TrackName(name) => self.track().statistics.track_name = Some(name),
+            StreamEvent::Segment => self.track().segment_start = true,
+            StreamEvent::Point(point) => {
+                let point = self.project(point);
+                let distance_mode = self.distance_mode;
+                let unit = distance_mode.unit_in_meters();
+                let exclude_segment_gaps = self.exclude_segment_gaps;
+                let closest = match self.reference_index.closest_point(&point) {
+                    Closest::Intersection(closest) | Closest::SinglePoint(closest) => {
+                        Some(distance_mode.distance(&closest, &point) * unit)
+                    }
+                    Closest::Indeterminate => None,
+                };
+
+                let track = self.track();
+                if let Some(previous) = track.previous {
+                    // The line connecting two segments has no length if the gaps are excluded
+                    if !(exclude_segment_gaps && track.segment_start) {
+                        track.statistics.current_track_length_m +=
+                            distance_mode.distance(&previous, &point) * unit;
+                    }
+                }
+                track.previous = Some(point);
+                track.segment_start = false;
+                track.statistics.point_count += 1;
+
+                if let Some(distance) = closest {
+                    track.total_distance += distance;
+                    track.total_squared_distance += distance * distance;
+                    track.compared_points += 1;
+                    if track
+                        .statistics
+                        .max_distance_m
+                        .is_none_or(|max_distance| distance > max_distance)
+                    {
+                        track.statistics.max_distance_m = Some(distance);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Function to finish the comparison of all tracks.
+    /// - Returns: The metrics of every track, in the order in which they were read.
+    pub fn finish(mut self) -> Vec<StreamingStatistics> {
+        self.finish_track();
+        self.finished
+    }
+
+    /// Function to get the running sums of the current track, starting a new track if there is none.
+    fn track(&mut self) -> &mut TrackState {
+        self.current.get_or_insert_with(TrackState::default)
+    }
+
+    /// Function to move the current track, if there is one, to the finished tracks.
+    fn finish_track(&mut self) {
+        if let Some(track) = self.current.take() {
+            let count = track.compared_points as f64;
+            self.finished.push(StreamingStatistics {
+                reference_track_length_m: self.reference_track_length_m,
+                average_distance_m: (track.compared_points > 0)
+                    .then(|| track.total_distance / count),
+                rms_distance_m: (track.compared_points > 0)
+                    .then(|| (track.total_squared_distance / count).sqrt()),
+                ..track.statistics
+            });
+        }
+    }
+
+    /// Function to convert a point into the coordinates in which the distances are computed.
+    fn project(&self, point: Point) -> Point {
+        match (self.distance_mode, self.utm) {
+            (DistanceMode::Flat, Some(utm)) => project_utm(&utm, point).into(),
+            (DistanceMode::Flat, None) => {
+                let projected = project_point(&self.projector, point);
+                Point::new(projected.x, projected.y)
+            }
+            (DistanceMode::Geodesic, _) => point,
+        }
+    }
+
+    /// Function to calculate the length of the joined segments of the reference path in meters.
+    fn length(&self, linestring: &LineString, segments: &[TrackSegment]) -> f64 {
+        let gaps = if self.exclude_segment_gaps {
+            segment_gaps(segments)
+        } else {
+            Vec::new()
+        };
+        linestring
+            .lines()
+            .enumerate()
+            .filter(|(index, _)| !gaps.get(*index).copied().unwrap_or(false))
+            .fold(0.0, |length, (_, line)| {
+                length
+                    + self
+                        .distance_mode
+                        .distance(&line.start_point(), &line.end_point())
+                        * self.distance_mode.unit_in_meters()
+            })
+    }
+}
+
+/// Function to project a point into a UTM zone, in kilometers like the flat projection.
+fn project_utm(utm: &UtmProjection, point: Point) -> Coord {
+    let projected = utm.project(point);
+    Coord {
+        x: projected.x() / 1000.0,
+        y: projected.y() / 1000.0,
+    }
+}
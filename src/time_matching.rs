@@ -0,0 +1,96 @@
+//! Distances between a track and the reference path at the same points in time, e.g. to compare two recordings of the same ride.
+//!
+//! The average distance matches every point to its geometrically closest point on the reference path, regardless of
+//! when the reference path was there. Matching by time instead interpolates where the reference path was at the
+//! timestamp of every point, so a track lagging behind on the same line has a large distance.
+
+use flat_projection::{FlatPoint, FlatProjection};
+use gpx::Track;
+use time::OffsetDateTime;
+
+use crate::project_point;
+
+/// Distances between the points of a track and the positions of the reference path at the same time, in meters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeMatchedDistance {
+    /// Average distance between every matched point and the position of the reference path at its timestamp
+    pub average_distance_m: f64,
+    /// Largest distance of a single matched point
+    pub max_distance_m: f64,
+    /// Number of points of the compared track with a timestamp within the time span of the reference path
+    pub matched_points: usize,
+}
+
+/// Function to get the timestamp of a point in seconds.
+/// - `time`: The time of the point.
+/// - Returns: The seconds since the Unix epoch, including fractions of a second.
+fn seconds(time: gpx::Time) -> f64 {
+    OffsetDateTime::from(time).unix_timestamp_nanos() as f64 / 1e9
+}
+
+/// Function to calculate the distances between the points of a track and the positions of the reference path at their timestamps.
+/// The position of the reference path is interpolated linearly between its two points around the timestamp, also across
+/// the gaps between its segments. Points of the reference path whose timestamp is not after the previous one are skipped.
+/// The distances are measured horizontally in the flat projection, in both distance modes.
+/// - `reference`: The reference track.
+/// - `track`: The compared track.
+/// - `projector`: The flat coordinate system used for projection.
+/// - Returns: The distances, `None` if no point of the compared track has a timestamp within the time span of the reference path.
+pub fn time_matched_distance(
+    reference: &Track,
+    track: &Track,
+    projector: &FlatProjection<f64>,
+) -> Option<TimeMatchedDistance> {
+    // The timed points of the reference path, with strictly increasing timestamps
+    let mut timeline: Vec<(f64, FlatPoint<f64>)> = Vec::new();
+    for point in reference
+        .segments
+        .iter()
+        .flat_map(|segment| &segment.points)
+    {
+        let Some(time) = point.time.map(seconds) else {
+            continue;
+        };
+        if timeline.last().is_none_or(|&(last, _)| time > last) {
+            timeline.push((time, project_point(projector, point.point())));
+        }
+    }
+    let (&(first_time, _), &(last_time, _)) = (timeline.first()?, timeline.last()?);
+
+    let distances: Vec<f64> = track
+        .segments
+        .iter()
+        .flat_map(|segment| &segment.points)
+        .filter_map(|point| {
+            let time = point.time.map(seconds)?;
+            if time < first_time || time > last_time {
+                return None;
+            }
+            // Index of the first point of the reference path after the timestamp, the position is between it and its predecessor
+            let next = timeline.partition_point(|&(reference_time, _)| reference_time <= time);
+            let position = match timeline.get(next) {
+                Some(&(end_time, end)) => {
+                    let (start_time, start) = timeline[next - 1];
+                    let fraction = (time - start_time) / (end_time - start_time);
+                    FlatPoint {
+                        x: start.x + (end.x - start.x) * fraction,
+                        y: start.y + (end.y - start.y) * fraction,
+                    }
+                }
+                // The timestamp is the last one of the reference path
+                None => timeline[next - 1].1,
+            };
+            // The flat projection is in kilometers
+            Some(project_point(projector, point.point()).distance(&position) * 1000.0)
+        })
+        .collect();
+    if distances.is_empty() {
+        return None;
+    }
+
+    Some(TimeMatchedDistance {
+        average_distance_m: distances.iter().sum::<f64>() / distances.len() as f64,
+        max_distance_m: distances.iter().copied().fold(0.0, f64::max),
+        matched_points: distances.len(),
+    })
+}
@@ -0,0 +1,74 @@
+//! Duration and speed of a track, calculated from the timestamps of its points.
+
+use flat_projection::FlatProjection;
+use gpx::Track;
+use time::OffsetDateTime;
+
+use crate::project_point;
+
+/// Duration and speeds of a track.
+/// The speeds are calculated between every two consecutive points of a segment which both have a timestamp.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeStatistics {
+    /// Elapsed time from the first to the last timestamp of the track (including pauses between segments), in seconds
+    pub duration_s: f64,
+    /// Distance covered between all timed pairs of points divided by their total time, in meters per second
+    pub average_speed_mps: f64,
+    /// Lowest speed between two consecutive points, in meters per second
+    pub min_speed_mps: f64,
+    /// Highest speed between two consecutive points, in meters per second
+    pub max_speed_mps: f64,
+    /// Number of pairs of consecutive points the speeds were calculated from
+    pub timed_intervals: usize,
+}
+
+/// Function to calculate the duration and speeds of a track.
+/// Pairs of points where either point has no timestamp, or whose timestamps are not increasing, are skipped.
+/// - `track`: The track.
+/// - `projector`: The flat coordinate system in which the distances between the points are measured.
+/// - Returns: The statistics, `None` if the track has no pair of consecutive points with increasing timestamps.
+pub fn time_statistics(track: &Track, projector: &FlatProjection<f64>) -> Option<TimeStatistics> {
+    let timestamp =
+        |time: gpx::Time| OffsetDateTime::from(time).unix_timestamp_nanos() as f64 / 1e9;
+
+    let timestamps = || {
+        track
+            .segments
+            .iter()
+            .flat_map(|segment| &segment.points)
+            .filter_map(|point| point.time.map(timestamp))
+    };
+    let first = timestamps().next()?;
+    let last = timestamps().next_back()?;
+
+    let (mut distance, mut time) = (0.0, 0.0);
+    let mut speeds = Vec::new();
+    for segment in &track.segments {
+        for pair in segment.points.windows(2) {
+            let (Some(start), Some(end)) = (pair[0].time, pair[1].time) else {
+                continue;
+            };
+            let elapsed = timestamp(end) - timestamp(start);
+            if elapsed <= 0.0 {
+                continue;
+            }
+            let project = |point: geo::Point| project_point(projector, point);
+            // The flat projection is in kilometers
+            let length = project(pair[0].point()).distance(&project(pair[1].point())) * 1000.0;
+            distance += length;
+            time += elapsed;
+            speeds.push(length / elapsed);
+        }
+    }
+    if speeds.is_empty() {
+        return None;
+    }
+
+    Some(TimeStatistics {
+        duration_s: last - first,
+        average_speed_mps: distance / time,
+        min_speed_mps: speeds.iter().copied().fold(f64::INFINITY, f64::min),
+        max_speed_mps: speeds.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        timed_intervals: speeds.len(),
+    })
+}
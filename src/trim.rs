@@ -0,0 +1,120 @@
+//! Clipping of the start and end of tracks, e.g. to drop points recorded while acquiring a GPS fix.
+
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use geo::HaversineDistance;
+use gpx::Track;
+
+/// How much of the start or end of a track is dropped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trim {
+    /// All points within this distance (in meters, measured along the track) are dropped
+    Meters(f64),
+    /// This number of points is dropped
+    Points(usize),
+}
+
+impl FromStr for Trim {
+    type Err = String;
+
+    /// Plain numbers and numbers with an `m` suffix are distances in meters, numbers with a `pts` suffix are a number of points.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(points) = s.strip_suffix("pts") {
+            return points
+                .trim()
+                .parse()
+                .map(Trim::Points)
+                .map_err(|_| format!("invalid number of points: {}", points));
+        }
+        let meters = s.strip_suffix('m').unwrap_or(s).trim();
+        match meters.parse::<f64>() {
+            Ok(meters) if meters >= 0.0 && meters.is_finite() => Ok(Trim::Meters(meters)),
+            _ => Err(format!(
+                "invalid trim {:?}, expected a distance in meters (e.g. 100 or 100m) or a number of points (e.g. 10pts)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Trim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trim::Meters(meters) => write!(f, "{}m", meters),
+            Trim::Points(points) => write!(f, "{}pts", points),
+        }
+    }
+}
+
+/// Function to count how many points at the start of a sequence are within the trim.
+/// - `points`: The points, starting at the end which is trimmed.
+/// - `trim`: How much is dropped.
+/// - Returns: The number of points to drop.
+fn trimmed_count<'a>(mut points: impl Iterator<Item = &'a geo::Point>, trim: Trim) -> usize {
+    match trim {
+        Trim::Points(count) => count,
+        Trim::Meters(meters) if meters <= 0.0 => 0,
+        Trim::Meters(meters) => {
+            let Some(mut previous) = points.next() else {
+                return 0;
+            };
+            // Points are dropped as long as their distance along the track from the first point is less than the trim
+            let (mut count, mut length) = (0, 0.0);
+            for point in points {
+                count += 1;
+                length += previous.haversine_distance(point);
+                if length >= meters {
+                    return count;
+                }
+                previous = point;
+            }
+            count + 1
+        }
+    }
+}
+
+/// Function to drop the start and end of a track.
+/// The distances are measured along all segments of the track (like for all other calculations), the points are dropped
+/// without interpolating a new first or last point. Segments which have no points left are removed.
+/// - `track`: The track to trim.
+/// - `start`: How much of the start of the track is dropped.
+/// - `end`: How much of the end of the track is dropped.
+/// - Returns: A copy of the track without the dropped points.
+pub fn trim_track(track: &Track, start: Option<Trim>, end: Option<Trim>) -> Track {
+    let points: Vec<geo::Point> = track
+        .segments
+        .iter()
+        .flat_map(|segment| &segment.points)
+        .map(|waypoint| waypoint.point())
+        .collect();
+
+    let start_count = start.map_or(0, |start| trimmed_count(points.iter(), start));
+    let end_count = end.map_or(0, |end| trimmed_count(points.iter().rev(), end));
+    // If the trims overlap, no point is left
+    let kept = start_count..points.len().saturating_sub(end_count).max(start_count);
+
+    retain_point_range(track, kept)
+}
+
+/// Function to keep only a range of the points of a track.
+/// - `track`: The track.
+/// - `kept`: The range of the kept points, counted over all segments of the track.
+/// - Returns: A copy of the track only containing the kept points. Segments which have no points left are removed.
+pub(crate) fn retain_point_range(track: &Track, kept: Range<usize>) -> Track {
+    let mut retained_track = track.clone();
+    let mut index = 0;
+    for segment in &mut retained_track.segments {
+        let segment_start = index;
+        index += segment.points.len();
+        let range = kept.start.clamp(segment_start, index) - segment_start
+            ..kept.end.clamp(segment_start, index) - segment_start;
+        segment.points = segment.points[range].to_vec();
+    }
+    retained_track
+        .segments
+        .retain(|segment| !segment.points.is_empty());
+    retained_track
+}
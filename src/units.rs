@@ -0,0 +1,231 @@
+//! Conversion of the reported distances into the unit system chosen for the output.
+//! All calculations are done in meters, only the results are converted right before they are reported.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{
+    CorridorStatistics, DistanceWindow, ElevationProfile, EpsilonSweepEntry, NearestPoint,
+    TimeMatchedDistance, ToleranceStatistics, TrackComparison, TrackOffset,
+};
+
+/// Number of feet per meter (1 ft = 0.3048 m).
+pub const FEET_PER_METER: f64 = 1.0 / 0.3048;
+
+/// The unit system of the reported distances, lengths and areas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Units {
+    /// Distances and lengths in meters, areas in square meters
+    #[default]
+    Metric,
+    /// Distances and lengths in feet, areas in square feet
+    Imperial,
+}
+
+impl FromStr for Units {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "metric" => Ok(Units::Metric),
+            "imperial" => Ok(Units::Imperial),
+            _ => Err(format!("unknown units {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Units::Metric => write!(f, "metric"),
+            Units::Imperial => write!(f, "imperial"),
+        }
+    }
+}
+
+impl Units {
+    /// Function to convert a distance or length.
+    /// - `meters`: The distance in meters.
+    /// - Returns: The distance in the units.
+    pub fn length(&self, meters: f64) -> f64 {
+        match self {
+            Units::Metric => meters,
+            Units::Imperial => meters * FEET_PER_METER,
+        }
+    }
+
+    /// Function to convert an area.
+    /// - `square_meters`: The area in square meters.
+    /// - Returns: The area in the squared units.
+    pub fn area(&self, square_meters: f64) -> f64 {
+        match self {
+            Units::Metric => square_meters,
+            Units::Imperial => square_meters * FEET_PER_METER * FEET_PER_METER,
+        }
+    }
+
+    /// Function to get the label which is appended to distances and lengths.
+    /// - Returns: `m` or `ft`.
+    pub fn length_label(&self) -> &'static str {
+        match self {
+            Units::Metric => "m",
+            Units::Imperial => "ft",
+        }
+    }
+
+    /// Function to get the label which is appended to areas.
+    /// - Returns: `m²` or `ft²`.
+    pub fn area_label(&self) -> &'static str {
+        match self {
+            Units::Metric => "m²",
+            Units::Imperial => "ft²",
+        }
+    }
+
+    /// Function to get the name of a field of the machine readable output in the units.
+    /// The field names of the output end in `_m` for lengths and `_m2` for areas, which become `_ft` and `_ft2` in imperial units.
+    /// - `name`: The name of the field in metric units.
+    /// - Returns: The name of the field in the units.
+    pub fn field_name(&self, name: &str) -> String {
+        match self {
+            Units::Metric => name.to_string(),
+            Units::Imperial => {
+                if let Some(stem) = name.strip_suffix("_m2") {
+                    format!("{}_ft2", stem)
+                } else if let Some(stem) = name.strip_suffix("_m") {
+                    format!("{}_ft", stem)
+                } else {
+                    name.to_string()
+                }
+            }
+        }
+    }
+
+    /// Function to rename the fields of a JSON value (and all values nested inside of it) to the units, see [`Units::field_name`].
+    /// Only the names are changed, the values have to be converted already.
+    /// - `value`: The JSON value, which is changed in place.
+    pub fn rename_json_fields(&self, value: &mut serde_json::Value) {
+        if *self == Units::Metric {
+            return;
+        }
+        match value {
+            serde_json::Value::Object(object) => {
+                *object = std::mem::take(object)
+                    .into_iter()
+                    .map(|(name, mut value)| {
+                        self.rename_json_fields(&mut value);
+                        (self.field_name(&name), value)
+                    })
+                    .collect();
+            }
+            serde_json::Value::Array(values) => {
+                values
+                    .iter_mut()
+                    .for_each(|value| self.rename_json_fields(value));
+            }
+            _ => {}
+        }
+    }
+
+    /// Function to convert all distances, lengths and areas of a comparison.
+    /// The field names keep their `_m` suffix, the values are in the units afterwards.
+    /// - `comparison`: The comparison in meters.
+    /// - Returns: A copy of the comparison with all values in the units.
+    pub fn convert_comparison(&self, comparison: &TrackComparison) -> TrackComparison {
+        let length = |meters: f64| self.length(meters);
+        TrackComparison {
+            current_track_length_m: length(comparison.current_track_length_m),
+            reference_track_length_m: length(comparison.reference_track_length_m),
+            average_distance_m: length(comparison.average_distance_m),
+            simplified_average_distance_m: length(comparison.simplified_average_distance_m),
+            rms_distance_m: length(comparison.rms_distance_m),
+            average_distance_reverse_m: comparison.average_distance_reverse_m.map(length),
+            average_distance_symmetric_m: comparison.average_distance_symmetric_m.map(length),
+            weighted_average_distance_m: comparison.weighted_average_distance_m.map(length),
+            median_distance_m: length(comparison.median_distance_m),
+            p90_distance_m: length(comparison.p90_distance_m),
+            p95_distance_m: length(comparison.p95_distance_m),
+            max_distance_m: length(comparison.max_distance_m),
+            start_offset_m: comparison.start_offset_m.map(length),
+            end_offset_m: comparison.end_offset_m.map(length),
+            start_to_end_offset_m: comparison.start_to_end_offset_m.map(length),
+            mean_signed_offset_m: length(comparison.mean_signed_offset_m),
+            mean_cross_track_m: length(comparison.mean_cross_track_m),
+            mean_along_track_m: length(comparison.mean_along_track_m),
+            frechet_distance_m: comparison.frechet_distance_m.map(length),
+            hausdorff_distance_m: comparison.hausdorff_distance_m.map(length),
+            hausdorff_track_to_reference_m: comparison.hausdorff_track_to_reference_m.map(length),
+            hausdorff_reference_to_track_m: comparison.hausdorff_reference_to_track_m.map(length),
+            dtw_distance_m: comparison.dtw_distance_m.map(length),
+            area_between_m2: comparison.area_between_m2.map(|area| self.area(area)),
+            tolerance: comparison
+                .tolerance
+                .as_ref()
+                .map(|tolerance| ToleranceStatistics {
+                    tolerance_m: length(tolerance.tolerance_m),
+                    longest_out_of_tolerance_m: length(tolerance.longest_out_of_tolerance_m),
+                    ..tolerance.clone()
+                }),
+            track_offset: comparison.track_offset.map(|offset| TrackOffset {
+                north_m: length(offset.north_m),
+                east_m: length(offset.east_m),
+            }),
+            elevation_profile: comparison.elevation_profile.as_ref().map(|profile| {
+                ElevationProfile {
+                    mean_elevation_diff_m: profile.mean_elevation_diff_m.map(length),
+                    reference_ascent_m: length(profile.reference_ascent_m),
+                    reference_descent_m: length(profile.reference_descent_m),
+                    track_ascent_m: length(profile.track_ascent_m),
+                    track_descent_m: length(profile.track_descent_m),
+                }
+            }),
+            time_matched_distance: comparison.time_matched_distance.as_ref().map(|distance| {
+                TimeMatchedDistance {
+                    average_distance_m: length(distance.average_distance_m),
+                    max_distance_m: length(distance.max_distance_m),
+                    ..distance.clone()
+                }
+            }),
+            corridor: comparison
+                .corridor
+                .as_ref()
+                .map(|corridor| CorridorStatistics {
+                    width_m: length(corridor.width_m),
+                    outside_length_m: length(corridor.outside_length_m),
+                    outside_area_m2: self.area(corridor.outside_area_m2),
+                    indeterminate_length_m: length(corridor.indeterminate_length_m),
+                    ..corridor.clone()
+                }),
+            distance_windows: comparison
+                .distance_windows
+                .iter()
+                .map(|window| DistanceWindow {
+                    start_m: length(window.start_m),
+                    end_m: length(window.end_m),
+                    average_m: length(window.average_m),
+                    max_m: length(window.max_m),
+                })
+                .collect(),
+            epsilon_sweep: comparison
+                .epsilon_sweep
+                .iter()
+                .map(|entry| EpsilonSweepEntry {
+                    epsilon_m: length(entry.epsilon_m),
+                    simplified_average_distance_m: length(entry.simplified_average_distance_m),
+                    ..entry.clone()
+                })
+                .collect(),
+            nearest_points: comparison
+                .nearest_points
+                .iter()
+                .map(|pair| NearestPoint {
+                    distance_m: length(pair.distance_m),
+                    elevation: pair.elevation.map(length),
+                    cumulative_arc_m: length(pair.cumulative_arc_m),
+                    ..*pair
+                })
+                .collect(),
+            ..comparison.clone()
+        }
+    }
+}
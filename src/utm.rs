@@ -0,0 +1,169 @@
+//! Universal Transverse Mercator (UTM) projection on the WGS84 ellipsoid.
+//!
+//! Unlike the flat projection, whose precision degrades with the distance from its origin, UTM is conformal within its zone
+//! and its scale error stays below 0.1% (the scale is 0.9996 at the central meridian and about 1.001 at the edges of the zone).
+//! The projection uses the series of Krüger to the third order, which is precise to about a millimeter within a zone.
+
+use std::f64::consts::PI;
+
+use geo::Point;
+use gpx::Track;
+
+use crate::wrap_longitude;
+
+/// Semi-major axis of the WGS84 ellipsoid, in meters.
+const WGS84_A: f64 = 6_378_137.0;
+/// Flattening of the WGS84 ellipsoid.
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+/// Scale at the central meridian of every zone.
+const UTM_K0: f64 = 0.9996;
+/// Easting of the central meridian, in meters.
+const UTM_FALSE_EASTING: f64 = 500_000.0;
+/// Northing of the equator on the southern hemisphere, in meters.
+const UTM_FALSE_NORTHING_SOUTH: f64 = 10_000_000.0;
+/// Largest difference in longitude from the central meridian within a zone, in degrees.
+pub const UTM_HALF_ZONE_WIDTH: f64 = 3.0;
+
+/// A UTM zone, in which points can be projected to meters east and north.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UtmProjection {
+    /// Number of the zone, from 1 to 60
+    pub zone: u8,
+    /// Whether the northings are counted from the equator (northern hemisphere) or from 10000km south of it (southern hemisphere)
+    pub north: bool,
+}
+
+/// Coefficients of the series of Krüger, depending only on the ellipsoid.
+struct Coefficients {
+    /// Radius of the rectifying sphere, multiplied with the scale at the central meridian
+    k0_a: f64,
+    alpha: [f64; 3],
+    beta: [f64; 3],
+    delta: [f64; 3],
+    /// Factor of the conformal latitude
+    e: f64,
+}
+
+impl Coefficients {
+    fn wgs84() -> Self {
+        let n = WGS84_F / (2.0 - WGS84_F);
+        let (n2, n3) = (n * n, n * n * n);
+        Coefficients {
+            k0_a: UTM_K0 * WGS84_A / (1.0 + n) * (1.0 + n2 / 4.0 + n2 * n2 / 64.0),
+            alpha: [
+                n / 2.0 - 2.0 * n2 / 3.0 + 5.0 * n3 / 16.0,
+                13.0 * n2 / 48.0 - 3.0 * n3 / 5.0,
+                61.0 * n3 / 240.0,
+            ],
+            beta: [
+                n / 2.0 - 2.0 * n2 / 3.0 + 37.0 * n3 / 96.0,
+                n2 / 48.0 + n3 / 15.0,
+                17.0 * n3 / 480.0,
+            ],
+            delta: [
+                2.0 * n - 2.0 * n2 / 3.0 - 2.0 * n3,
+                7.0 * n2 / 3.0 - 8.0 * n3 / 5.0,
+                56.0 * n3 / 15.0,
+            ],
+            e: 2.0 * n.sqrt() / (1.0 + n),
+        }
+    }
+}
+
+impl UtmProjection {
+    /// Function to get the zone containing a point.
+    /// The special zones of Norway and Svalbard are not used, the zones are always 6° wide.
+    /// - `point`: The point in LatLon coordinates.
+    /// - Returns: The zone of the point.
+    pub fn for_point(point: Point) -> Self {
+        let zone = ((point.x() + 180.0) / 6.0).floor() as i64 + 1;
+        UtmProjection {
+            zone: zone.clamp(1, 60) as u8,
+            north: point.y() >= 0.0,
+        }
+    }
+
+    /// Function to get the longitude of the central meridian of the zone.
+    /// - Returns: The longitude in degrees.
+    pub fn central_meridian(&self) -> f64 {
+        f64::from(self.zone) * 6.0 - 183.0
+    }
+
+    /// Function to find the point of a track which is furthest away from the zone in longitude.
+    /// The precision of the projection is only given within the zone, i.e. up to [`UTM_HALF_ZONE_WIDTH`] from the central meridian.
+    /// - `track`: The track.
+    /// - Returns: The largest difference in longitude of a point to the central meridian in degrees, 0 for tracks without points.
+    pub fn max_meridian_offset(&self, track: &Track) -> f64 {
+        track
+            .segments
+            .iter()
+            .flat_map(|segment| &segment.points)
+            .map(|waypoint| wrap_longitude(waypoint.point().x() - self.central_meridian()).abs())
+            .fold(0.0, f64::max)
+    }
+
+    /// Function to project a point into the zone.
+    /// Points outside of the zone are projected as well, but lose precision with their distance to it.
+    /// - `point`: The point in LatLon coordinates.
+    /// - Returns: The easting (x) and northing (y) of the point, in meters.
+    pub fn project(&self, point: Point) -> Point {
+        let c = Coefficients::wgs84();
+        let latitude = point.y().to_radians();
+        let longitude = (point.x() - self.central_meridian()).to_radians();
+
+        let t = (latitude.sin().atanh() - c.e * (c.e * latitude.sin()).atanh()).sinh();
+        let xi_prime = t.atan2(longitude.cos());
+        let eta_prime = (longitude.sin() / (1.0 + t * t).sqrt()).atanh();
+
+        let (mut xi, mut eta) = (xi_prime, eta_prime);
+        for (j, alpha) in c.alpha.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi += alpha * (k * xi_prime).sin() * (k * eta_prime).cosh();
+            eta += alpha * (k * xi_prime).cos() * (k * eta_prime).sinh();
+        }
+
+        Point::new(
+            UTM_FALSE_EASTING + c.k0_a * eta,
+            self.false_northing() + c.k0_a * xi,
+        )
+    }
+
+    /// Function to convert a projected point back to LatLon coordinates.
+    /// - `point`: The easting (x) and northing (y) of the point, in meters.
+    /// - Returns: The point in LatLon coordinates.
+    pub fn unproject(&self, point: Point) -> Point {
+        let c = Coefficients::wgs84();
+        let xi = (point.y() - self.false_northing()) / c.k0_a;
+        let eta = (point.x() - UTM_FALSE_EASTING) / c.k0_a;
+
+        let (mut xi_prime, mut eta_prime) = (xi, eta);
+        for (j, beta) in c.beta.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi_prime -= beta * (k * xi).sin() * (k * eta).cosh();
+            eta_prime -= beta * (k * xi).cos() * (k * eta).sinh();
+        }
+
+        let chi = (xi_prime.sin() / eta_prime.cosh()).asin();
+        let mut latitude = chi;
+        for (j, delta) in c.delta.iter().enumerate() {
+            latitude += delta * (2.0 * (j + 1) as f64 * chi).sin();
+        }
+        let longitude = eta_prime.sinh().atan2(xi_prime.cos());
+
+        // Zones next to the antimeridian reach beyond it
+        Point::new(
+            wrap_longitude(self.central_meridian() + longitude * 180.0 / PI),
+            latitude.to_degrees(),
+        )
+    }
+
+    /// Function to get the northing of the equator.
+    /// - Returns: 0 on the northern and 10000km on the southern hemisphere.
+    fn false_northing(&self) -> f64 {
+        if self.north {
+            0.0
+        } else {
+            UTM_FALSE_NORTHING_SOUTH
+        }
+    }
+}
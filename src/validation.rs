@@ -0,0 +1,227 @@
+//! Detection of suspicious points, which can be parsed but would silently skew the results.
+
+use std::fmt;
+
+use geo::HaversineDistance;
+use gpx::{Gpx, Track, TrackSegment};
+use time::OffsetDateTime;
+
+use crate::route_track;
+
+/// A suspicious point of a track.
+/// All indices are 0-based and counted over all segments of the track, like [`crate::TrackComparison::max_distance_point_index`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackIssue {
+    /// The point is exactly at latitude and longitude 0, which is usually a placeholder of a missing GPS fix
+    NullIsland { index: usize },
+    /// The latitude or longitude of the point is NaN, infinite or outside of its valid range
+    InvalidCoordinate { index: usize, lat: f64, lon: f64 },
+    /// The point is further away from the previous point of its segment than the allowed jump, in meters
+    Jump {
+        index: usize,
+        distance_m: f64,
+        max_jump_m: f64,
+    },
+    /// The timestamp of the point is before the timestamp of the previous point with a time, e.g. in a corrupt or merged log
+    TimeBackwards { index: usize, seconds: f64 },
+    /// The point has the same timestamp as the previous point with a time
+    DuplicateTime { index: usize },
+}
+
+impl fmt::Display for TrackIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackIssue::NullIsland { index } => {
+                write!(f, "point {} is at 0, 0 (lat, lon)", index)
+            }
+            TrackIssue::InvalidCoordinate { index, lat, lon } => write!(
+                f,
+                "point {} has the invalid coordinates {}, {} (lat, lon)",
+                index, lat, lon
+            ),
+            TrackIssue::Jump {
+                index,
+                distance_m,
+                max_jump_m,
+            } => write!(
+                f,
+                "point {} is {:.3}m away from the previous point, which is more than the maximum jump of {}m",
+                index, distance_m, max_jump_m
+            ),
+            TrackIssue::TimeBackwards { index, seconds } => write!(
+                f,
+                "point {} has a timestamp {:.3}s before the previous point",
+                index, seconds
+            ),
+            TrackIssue::DuplicateTime { index } => {
+                write!(f, "point {} has the same timestamp as the previous point", index)
+            }
+        }
+    }
+}
+
+/// An issue which makes a file unusable for a comparison, or at least suspicious.
+/// All indices are 0-based, the messages count tracks and segments from 1 like the output and --reference-track.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileIssue {
+    /// The file contains neither tracks, routes nor waypoints
+    NoTracksOrWaypoints,
+    /// The track has no segments
+    EmptyTrack { track_index: usize },
+    /// The segment of the track contains no points
+    EmptySegment {
+        track_index: usize,
+        segment_index: usize,
+    },
+    /// A point of the track is suspicious. Files without tracks use their first route or their waypoints as track 0, like for the reference path
+    SuspiciousPoint {
+        track_index: usize,
+        issue: TrackIssue,
+    },
+}
+
+impl fmt::Display for FileIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileIssue::NoTracksOrWaypoints => {
+                write!(f, "the file contains neither tracks, routes nor waypoints")
+            }
+            FileIssue::EmptyTrack { track_index } => {
+                write!(f, "track {} has no segments", track_index + 1)
+            }
+            FileIssue::EmptySegment {
+                track_index,
+                segment_index,
+            } => write!(
+                f,
+                "segment {} of track {} has no points",
+                segment_index + 1,
+                track_index + 1
+            ),
+            FileIssue::SuspiciousPoint { track_index, issue } => {
+                write!(f, "track {}: {}", track_index + 1, issue)
+            }
+        }
+    }
+}
+
+/// Function to check if a coordinate is a valid latitude and longitude.
+/// - `lat`: The latitude in degrees.
+/// - `lon`: The longitude in degrees.
+/// - Returns: `true` if both values are finite and within their range.
+fn is_valid_coordinate(lat: f64, lon: f64) -> bool {
+    lat.is_finite()
+        && lon.is_finite()
+        && (-90.0..=90.0).contains(&lat)
+        && (-180.0..=180.0).contains(&lon)
+}
+
+/// Function to find all suspicious points of a track.
+/// Jumps are only checked between consecutive points of the same segment, as there may be a gap between two segments.
+/// Timestamps have to increase over all segments, points without a time are skipped.
+/// - `track`: The track to check.
+/// - `max_jump`: The maximum allowed distance between two consecutive points in meters, `None` to not check for jumps.
+/// - Returns: All found issues, in the order of the points.
+pub fn validate_track(track: &Track, max_jump: Option<f64>) -> Vec<TrackIssue> {
+    let mut issues = Vec::new();
+    let mut index = 0;
+    let mut previous_time: Option<OffsetDateTime> = None;
+
+    for segment in &track.segments {
+        let mut previous = None;
+        for waypoint in &segment.points {
+            if let Some(time) = waypoint.time.map(OffsetDateTime::from) {
+                if let Some(previous_time) = previous_time {
+                    if time == previous_time {
+                        issues.push(TrackIssue::DuplicateTime { index });
+                    } else if time < previous_time {
+                        issues.push(TrackIssue::TimeBackwards {
+                            index,
+                            seconds: (previous_time - time).as_seconds_f64(),
+                        });
+                    }
+                }
+                // A single wrong timestamp should only be reported once, not for every point after it
+                previous_time = Some(time);
+            }
+
+            let point = waypoint.point();
+            let (lat, lon) = (point.y(), point.x());
+
+            if !is_valid_coordinate(lat, lon) {
+                issues.push(TrackIssue::InvalidCoordinate { index, lat, lon });
+                // The distance to an invalid point is meaningless
+                previous = None;
+            } else {
+                if lat == 0.0 && lon == 0.0 {
+                    issues.push(TrackIssue::NullIsland { index });
+                }
+
+                if let (Some(previous), Some(max_jump)) = (previous, max_jump) {
+                    let distance = point.haversine_distance(&previous);
+                    if distance > max_jump {
+                        issues.push(TrackIssue::Jump {
+                            index,
+                            distance_m: distance,
+                            max_jump_m: max_jump,
+                        });
+                    }
+                }
+                previous = Some(point);
+            }
+
+            index += 1;
+        }
+    }
+
+    issues
+}
+
+/// Function to find all issues of a file, e.g. before starting a long batch of comparisons.
+/// Files without tracks are checked like a single track made of their first route or, without routes, their waypoints, which is how they are used as reference path.
+/// - `gpx`: The content of the file.
+/// - `max_jump`: The maximum allowed distance between two consecutive points in meters, `None` to not check for jumps.
+/// - Returns: All found issues, in the order of the tracks.
+pub fn validate_file(gpx: &Gpx, max_jump: Option<f64>) -> Vec<FileIssue> {
+    if gpx.tracks.is_empty() {
+        let track = match gpx.routes.first() {
+            Some(route) => route_track(route),
+            None if gpx.waypoints.is_empty() => return vec![FileIssue::NoTracksOrWaypoints],
+            None => {
+                let mut track = Track::new();
+                track.segments.push(TrackSegment {
+                    points: gpx.waypoints.clone(),
+                });
+                track
+            }
+        };
+        return validate_track(&track, max_jump)
+            .into_iter()
+            .map(|issue| FileIssue::SuspiciousPoint {
+                track_index: 0,
+                issue,
+            })
+            .collect();
+    }
+
+    let mut issues = Vec::new();
+    for (track_index, track) in gpx.tracks.iter().enumerate() {
+        if track.segments.is_empty() {
+            issues.push(FileIssue::EmptyTrack { track_index });
+        }
+        for (segment_index, segment) in track.segments.iter().enumerate() {
+            if segment.points.is_empty() {
+                issues.push(FileIssue::EmptySegment {
+                    track_index,
+                    segment_index,
+                });
+            }
+        }
+        issues.extend(
+            validate_track(track, max_jump)
+                .into_iter()
+                .map(|issue| FileIssue::SuspiciousPoint { track_index, issue }),
+        );
+    }
+    issues
+}
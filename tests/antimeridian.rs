@@ -0,0 +1,84 @@
+mod common;
+
+use approx::assert_relative_eq;
+use common::METERS_PER_DEGREE;
+use geo::Point;
+use gps_path_average_distance::{
+    compare_tracks, reference_origin, wrap_longitude, CompareOptions, DistanceMode,
+};
+use gpx::{Track, TrackSegment, Waypoint};
+
+/// Function to build a track along a parallel from its LatLon points.
+fn track_from_latlon(points: &[(f64, f64)]) -> Track {
+    let mut track = Track::new();
+    track.segments.push(TrackSegment {
+        points: points
+            .iter()
+            .map(|&(lat, lon)| Waypoint::new(Point::new(lon, lat)))
+            .collect(),
+    });
+    track
+}
+
+/// Function to build a straight track of 2km near Fiji, which starts at the given longitude and heads east.
+/// - `start_longitude`: The longitude of the first point.
+/// - `north_m`: How far the track is moved to the north, in meters.
+fn pacific_track(start_longitude: f64, north_m: f64) -> Track {
+    let points: Vec<(f64, f64)> = (0..=20)
+        .map(|index| {
+            (
+                -17.0 + north_m / METERS_PER_DEGREE,
+                wrap_longitude(start_longitude + f64::from(index) * 0.001),
+            )
+        })
+        .collect();
+    track_from_latlon(&points)
+}
+
+/// A track crossing the antimeridian is compared like the same track anywhere else, in both distance modes
+#[test]
+fn test_pacific_crossing_track() {
+    let crossing_reference = pacific_track(179.99, 0.0);
+    let crossing_track = pacific_track(179.99, 10.0);
+    // The same tracks on the other side of the globe, away from the antimeridian
+    let reference = pacific_track(-0.01, 0.0);
+    let track = pacific_track(-0.01, 10.0);
+
+    // The projection is centered next to the tracks, not on the other side of the globe
+    let origin = reference_origin(&crossing_reference);
+    assert!(origin.x().abs() > 179.9, "origin {:?}", origin);
+
+    for distance_mode in [DistanceMode::Flat, DistanceMode::Geodesic] {
+        let opts = CompareOptions {
+            distance_mode,
+            ..CompareOptions::default()
+        };
+        let crossing = compare_tracks(&crossing_reference, &crossing_track, &opts)
+            .expect("The reference path has points");
+        let expected =
+            compare_tracks(&reference, &track, &opts).expect("The reference path has points");
+
+        assert_relative_eq!(
+            crossing.reference_track_length_m,
+            expected.reference_track_length_m,
+            max_relative = 1e-6
+        );
+        assert_relative_eq!(
+            crossing.average_distance_m,
+            expected.average_distance_m,
+            max_relative = 1e-6
+        );
+        assert_relative_eq!(
+            crossing.frechet_distance_m.unwrap(),
+            expected.frechet_distance_m.unwrap(),
+            max_relative = 1e-6
+        );
+        assert_relative_eq!(crossing.average_distance_m, 10.0, epsilon = 0.1);
+
+        // Geometries are reported with valid longitudes
+        assert!(crossing
+            .simplified_track
+            .points()
+            .all(|point| (-180.0..=180.0).contains(&point.x())));
+    }
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use common::track_from_meters;
+use gps_path_average_distance::{compare_tracks, CompareOptions};
+
+#[test]
+fn area_between_parallel_segments_is_rectangle() {
+    let reference = track_from_meters(&[(0.0, 0.0), (1000.0, 0.0)]);
+    let track = track_from_meters(&[(0.0, 10.0), (500.0, 10.0), (1000.0, 10.0)]);
+
+    let comparison = compare_tracks(&reference, &track, &CompareOptions::default())
+        .expect("The reference path has points");
+    let area = comparison.area_between_m2.expect("both tracks are curves");
+
+    // A rectangle of roughly 1000m x 10m, its sides are measured in the same projection as the area
+    let rectangle = comparison.reference_track_length_m * comparison.average_distance_m;
+    assert!((rectangle - 10_000.0).abs() < 100.0);
+    assert!((area - rectangle).abs() < 1e-6, "area was {}", area);
+}
+
+#[test]
+fn area_between_crossing_tracks_adds_both_sides() {
+    let reference = track_from_meters(&[(0.0, 0.0), (1000.0, 0.0)]);
+    let track = track_from_meters(&[(0.0, 10.0), (1000.0, -10.0)]);
+
+    let comparison = compare_tracks(&reference, &track, &CompareOptions::default())
+        .expect("The reference path has points");
+    let area = comparison.area_between_m2.expect("both tracks are curves");
+
+    // Two triangles of 500m x 10m on both sides of the reference path, which must not cancel each other out
+    let triangles = comparison.reference_track_length_m * comparison.max_distance_m / 2.0;
+    assert!((triangles - 5_000.0).abs() < 50.0);
+    assert!((area - triangles).abs() < 1e-6, "area was {}", area);
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use approx::assert_relative_eq;
+use common::track_from_meters_with_elevation;
+use gps_path_average_distance::{compare_tracks, CompareOptions};
+use gpx::Track;
+use time::{Duration, OffsetDateTime};
+
+/// Function to set the timestamps of the points of a single segment track, in seconds after a fixed start.
+fn with_times(mut track: Track, seconds: &[i64]) -> Track {
+    let start = OffsetDateTime::from_unix_timestamp(1_714_557_600).unwrap();
+    for (point, second) in track.segments[0].points.iter_mut().zip(seconds) {
+        point.time = Some((start + Duration::seconds(*second)).into());
+    }
+    track
+}
+
+/// A track climbing the reference path backwards is compared in reverse, but its speeds, ascent, distance windows and
+/// offsets still refer to the order in which it was recorded
+#[test]
+fn test_reversed_track_keeps_recorded_order() {
+    // The reference path runs north and descends by 100m
+    let reference = track_from_meters_with_elevation(&[(0.0, 0.0, 200.0), (0.0, 1000.0, 100.0)]);
+    // The track runs south in 100s and climbs, drifting further east of the reference path
+    // The offsets are east of the reference path, which runs north, so that they are exact
+    let track = with_times(
+        track_from_meters_with_elevation(&[
+            (3.0, 1000.0, 100.0),
+            (6.0, 500.0, 150.0),
+            (9.0, 0.0, 200.0),
+        ]),
+        &[0, 50, 100],
+    );
+
+    let comparison = compare_tracks(
+        &reference,
+        &track,
+        &CompareOptions {
+            auto_orient: true,
+            time_stats: true,
+            elevation_profile: true,
+            window_report: Some(400.0),
+            ..CompareOptions::default()
+        },
+    )
+    .expect("The reference path has points");
+    assert!(comparison.reversed);
+
+    let time_statistics = comparison
+        .time_statistics
+        .expect("The track has increasing timestamps in its recorded order");
+    assert_relative_eq!(time_statistics.duration_s, 100.0);
+    assert_eq!(time_statistics.timed_intervals, 2);
+
+    let profile = comparison
+        .elevation_profile
+        .expect("Both tracks have elevations");
+    assert_relative_eq!(profile.track_ascent_m, 100.0);
+    assert_relative_eq!(profile.track_descent_m, 0.0);
+    assert_relative_eq!(profile.reference_descent_m, 100.0);
+    // Compared in reverse, the track is at the same elevation as the reference path everywhere
+    assert_relative_eq!(profile.mean_elevation_diff_m.unwrap(), 0.0, epsilon = 1e-9);
+
+    // The windows start at the first recorded point, which is the closest one
+    let windows = &comparison.distance_windows;
+    assert_eq!(windows.len(), 3);
+    assert_eq!(windows[0].start_m, 0.0);
+    assert_relative_eq!(windows[0].max_m, 3.0, max_relative = 1e-6);
+    assert_relative_eq!(windows[2].max_m, 9.0, max_relative = 1e-6);
+
+    // The track starts at the end of the reference path, which it runs backwards
+    assert_relative_eq!(comparison.start_offset_m.unwrap(), 3.0, max_relative = 1e-6);
+    assert_relative_eq!(comparison.end_offset_m.unwrap(), 9.0, max_relative = 1e-6);
+    assert!(comparison.start_to_end_offset_m.unwrap() > 900.0);
+}
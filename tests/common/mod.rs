@@ -0,0 +1,116 @@
+//! Helpers shared by the integration tests.
+//!
+//! Computed distances are never compared for exact equality, as the last bits of floating point results differ between
+//! platforms (e.g. fused multiply-add and the trigonometric functions of the system library). Use [`assert_distance`] with
+//! the tolerances below instead.
+
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+
+use approx::relative_eq;
+use geo::Point;
+use gps_path_average_distance::input::read_file;
+use gpx::{Track, TrackSegment, Waypoint};
+
+/// Roughly the number of meters per degree of latitude / longitude at the equator
+pub const METERS_PER_DEGREE: f64 = 111_319.5;
+
+/// Absolute tolerance of distances in meters (1 µm), far below the precision of any GPS fix,
+/// but above the rounding noise of the projections.
+pub const DISTANCE_EPSILON_M: f64 = 1e-6;
+
+/// Relative tolerance of distances, lengths and areas (one part in a billion), which covers the rounding noise of large values
+/// like track lengths or areas where the absolute tolerance alone would be too strict.
+pub const MAX_RELATIVE: f64 = 1e-9;
+
+/// Function to check that a computed value matches the expected one within [`DISTANCE_EPSILON_M`] or [`MAX_RELATIVE`].
+/// - `metric`: The name of the value, shown if the check fails.
+/// - `actual`: The computed value.
+/// - `expected`: The expected value.
+#[track_caller]
+pub fn assert_distance(metric: &str, actual: f64, expected: f64) {
+    assert!(
+        relative_eq!(
+            actual,
+            expected,
+            epsilon = DISTANCE_EPSILON_M,
+            max_relative = MAX_RELATIVE
+        ),
+        "{} is {}, expected {}",
+        metric,
+        actual,
+        expected
+    );
+}
+
+/// Function to get the path of a file in the `tests/fixtures` directory.
+/// - `name`: The file name of the fixture.
+/// - Returns: The path of the fixture.
+pub fn fixture_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+/// Function to read the first track of a fixture.
+/// - `name`: The file name of the fixture.
+/// - Returns: The first track of the fixture.
+pub fn fixture_track(name: &str) -> Track {
+    read_file(&fixture_path(name))
+        .unwrap_or_else(|error| panic!("Failed to read the fixture {}: {}", name, error))
+        .tracks
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| panic!("The fixture {} has no track", name))
+}
+
+/// Function to build a track segment from points given in meters (east, north) around the origin.
+/// - `points`: The points of the segment.
+/// - Returns: The track segment.
+pub fn segment_from_meters(points: &[(f64, f64)]) -> TrackSegment {
+    TrackSegment {
+        points: points
+            .iter()
+            .map(|(east, north)| {
+                Waypoint::new(Point::new(
+                    east / METERS_PER_DEGREE,
+                    north / METERS_PER_DEGREE,
+                ))
+            })
+            .collect(),
+    }
+}
+
+/// Function to build a track from the given segments.
+/// - `segments`: The segments of the track.
+/// - Returns: The track.
+pub fn track_from_segments(segments: Vec<TrackSegment>) -> Track {
+    let mut track = Track::new();
+    track.segments = segments;
+    track
+}
+
+/// Function to build a track from points given in meters (east, north) around the origin.
+/// - `points`: The points of the single segment of the track.
+/// - Returns: The track.
+pub fn track_from_meters(points: &[(f64, f64)]) -> Track {
+    track_from_segments(vec![segment_from_meters(points)])
+}
+
+/// Function to build a track from points given in meters (east, north, elevation) around the origin.
+/// - `points`: The points of the single segment of the track.
+/// - Returns: The track.
+pub fn track_from_meters_with_elevation(points: &[(f64, f64, f64)]) -> Track {
+    let mut segment = segment_from_meters(
+        &points
+            .iter()
+            .map(|&(east, north, _)| (east, north))
+            .collect::<Vec<_>>(),
+    );
+    for (waypoint, &(_, _, elevation)) in segment.points.iter_mut().zip(points) {
+        waypoint.elevation = Some(elevation);
+    }
+    track_from_segments(vec![segment])
+}
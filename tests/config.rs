@@ -0,0 +1,96 @@
+use gps_path_average_distance::config::{parse_config, ConfigValue};
+
+/// Function to build a scalar value.
+fn scalar(value: &str) -> ConfigValue {
+    ConfigValue::Scalar(value.to_string())
+}
+
+/// Scalars, lists in both styles and comments are parsed in the order of the file
+#[test]
+fn test_parse_config() {
+    let config = parse_config(
+        "---
+# Shared defaults
+simplify-epsilon: 2.5   # meters
+units: 'imperial'
+section: \"start:finish # not a comment\"
+symmetric: true
+resample: ~
+track: [a.gpx, \"b c.gpx\"]
+max-average:
+  - 10
+reference:
+- ref.gpx
+",
+    )
+    .unwrap();
+
+    assert_eq!(
+        config,
+        vec![
+            ("simplify-epsilon".to_string(), scalar("2.5")),
+            ("units".to_string(), scalar("imperial")),
+            (
+                "section".to_string(),
+                scalar("start:finish # not a comment")
+            ),
+            ("symmetric".to_string(), scalar("true")),
+            (
+                "track".to_string(),
+                ConfigValue::List(vec!["a.gpx".to_string(), "b c.gpx".to_string()])
+            ),
+            (
+                "max-average".to_string(),
+                ConfigValue::List(vec!["10".to_string()])
+            ),
+            (
+                "reference".to_string(),
+                ConfigValue::List(vec!["ref.gpx".to_string()])
+            ),
+        ]
+    );
+    assert_eq!(parse_config("").unwrap(), Vec::new());
+}
+
+/// Anything beyond a flat mapping is rejected with the line of the problem
+#[test]
+fn test_parse_config_errors() {
+    let error = |content: &str| parse_config(content).unwrap_err();
+
+    assert!(error("units: metric\nunits: imperial\n").starts_with("line 2:"));
+    assert!(error("units metric\n").starts_with("line 1:"));
+    assert!(error("- a.gpx\n").starts_with("line 1:"));
+    assert!(error("thresholds:\n  frechet: 10\n").starts_with("line 2:"));
+    assert!(error("track: [a.gpx\n").contains("missing closing ]"));
+    assert!(error("units: 'metric\n").contains("missing closing"));
+}
+
+/// YAML syntax beyond the supported subset is rejected instead of being read as a plain value
+#[test]
+fn test_parse_config_unsupported_syntax() {
+    let error = |content: &str| parse_config(content).unwrap_err();
+
+    assert!(error("units: &units imperial\n").starts_with("line 1: anchors"));
+    assert!(error("units: imperial\nsymmetric: *units\n").starts_with("line 2: aliases"));
+    assert!(error("simplify-epsilon: !!float 2.5\n").starts_with("line 1: tags"));
+    assert!(error("track:\n  - a.gpx\nsection: |\n  start:finish\n")
+        .starts_with("line 3: block scalars"));
+    assert!(error("thresholds: {frechet: 10}\n").starts_with("line 1: nested mappings"));
+    assert!(error("thresholds: frechet: 10\n").starts_with("line 1: nested mappings"));
+    assert!(error("track: [a.gpx, [b.gpx]]\n").starts_with("line 1: nested lists"));
+    assert!(error("track:\n  - [a.gpx]\n").starts_with("line 2: nested lists"));
+    assert!(error("section: \"start\n  finish\"\n").starts_with("line 1: missing closing"));
+    assert!(error("reference: \"C:\\tracks\\ref.gpx\"\n").starts_with("line 1: escape sequences"));
+    assert!(
+        error("units: metric\n---\nunits: imperial\n").starts_with("line 2: multiple documents")
+    );
+
+    // Single quotes take backslashes literally, a single quote is written as two
+    assert_eq!(
+        parse_config("reference: 'C:\\tracks\\Bob''s ride.gpx'\n").unwrap(),
+        vec![(
+            "reference".to_string(),
+            scalar("C:\\tracks\\Bob's ride.gpx")
+        )]
+    );
+}
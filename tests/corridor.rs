@@ -0,0 +1,65 @@
+mod common;
+
+use approx::assert_relative_eq;
+use common::track_from_meters;
+use gps_path_average_distance::{compare_tracks, CompareOptions};
+
+/// A track following the reference path inside a 20m corridor, then leaving it for the second half
+#[test]
+fn test_corridor_half_outside() {
+    // The offsets are east of the reference path, which runs north, so that they are exact
+    let reference = track_from_meters(&[(0.0, 0.0), (0.0, 1000.0)]);
+    // 500m at 5m (inside), 20m east to 25m (5m inside, 15m outside), 500m at 25m (outside by 15m)
+    let track = track_from_meters(&[(5.0, 0.0), (5.0, 500.0), (25.0, 500.0), (25.0, 1000.0)]);
+
+    let comparison = compare_tracks(
+        &reference,
+        &track,
+        &CompareOptions {
+            corridor_width: Some(20.0),
+            ..CompareOptions::default()
+        },
+    )
+    .expect("The reference path has points");
+    let corridor = comparison.corridor.expect("A corridor width was given");
+    // A degree of latitude is shorter than `METERS_PER_DEGREE`, so the length of the two halves along the reference path
+    // is taken from the measured track length
+    let half = (comparison.current_track_length_m - 20.0) / 2.0;
+
+    assert_relative_eq!(corridor.width_m, 20.0);
+    assert_relative_eq!(
+        corridor.inside_length_fraction,
+        (half + 5.0) / (2.0 * half + 20.0),
+        max_relative = 1e-6
+    );
+    assert_relative_eq!(corridor.outside_length_m, half + 15.0, max_relative = 1e-6);
+    // 15m outside the edge for the second half, plus the triangle of the 15m leaving it
+    assert_relative_eq!(
+        corridor.outside_area_m2,
+        half * 15.0 + 15.0 * 15.0 / 2.0,
+        max_relative = 1e-6
+    );
+    assert_eq!(corridor.indeterminate_length_m, 0.0);
+}
+
+/// Without a reference line the track is neither inside nor outside of the corridor
+#[test]
+fn test_corridor_without_reference_line() {
+    let reference = track_from_meters(&[(0.0, 0.0)]);
+    let track = track_from_meters(&[(5.0, 0.0), (105.0, 0.0)]);
+
+    let comparison = compare_tracks(
+        &reference,
+        &track,
+        &CompareOptions {
+            corridor_width: Some(20.0),
+            ..CompareOptions::default()
+        },
+    )
+    .expect("The reference path has points");
+    let corridor = comparison.corridor.expect("A corridor width was given");
+
+    assert!(corridor.inside_length_fraction.is_nan());
+    assert_eq!(corridor.outside_length_m, 0.0);
+    assert_relative_eq!(corridor.indeterminate_length_m, 100.0, max_relative = 1e-5);
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use common::{segment_from_meters, track_from_segments};
+use gps_path_average_distance::{
+    check_reference, compare_tracks, compare_tracks_with_projection, reference_projection,
+    CompareOptions, EmptyReferenceError, ReferenceSegments,
+};
+use gpx::Track;
+
+/// A straight reference path of 1km
+fn reference() -> Track {
+    track_from_segments(vec![segment_from_meters(&[(0.0, 0.0), (1000.0, 0.0)])])
+}
+
+#[test]
+fn zero_point_segment_has_no_metrics() {
+    let track = track_from_segments(vec![segment_from_meters(&[])]);
+
+    let comparison = compare_tracks(&reference(), &track, &CompareOptions::default())
+        .expect("The reference path has points");
+
+    assert_eq!(comparison.point_count, 0);
+    assert_eq!(comparison.simplified_point_count, 0);
+    assert_eq!(comparison.current_track_length_m, 0.0);
+    assert!(comparison.average_distance_m.is_nan());
+    assert_eq!(comparison.max_distance_point_index, None);
+    assert_eq!(comparison.frechet_distance_m, None);
+    assert_eq!(comparison.hausdorff_distance_m, None);
+    assert_eq!(comparison.dtw_distance_m, None);
+    assert_eq!(comparison.area_between_m2, None);
+}
+
+#[test]
+fn single_point_segment_only_has_point_distances() {
+    let track = track_from_segments(vec![segment_from_meters(&[(500.0, 20.0)])]);
+
+    let comparison = compare_tracks(&reference(), &track, &CompareOptions::default())
+        .expect("The reference path has points");
+
+    assert_eq!(comparison.point_count, 1);
+    assert_eq!(comparison.current_track_length_m, 0.0);
+    // The single point is about 20m north of the reference (the projection scale differs slightly from the constant above)
+    assert!((comparison.average_distance_m - 20.0).abs() < 0.5);
+    assert_eq!(comparison.average_distance_m, comparison.max_distance_m);
+    assert_eq!(comparison.max_distance_point_index, Some(0));
+    assert_eq!(comparison.frechet_distance_m, None);
+    assert_eq!(comparison.hausdorff_distance_m, None);
+    assert_eq!(comparison.dtw_distance_m, None);
+    assert_eq!(comparison.area_between_m2, None);
+}
+
+#[test]
+fn empty_segments_are_ignored_next_to_other_segments() {
+    let track = track_from_segments(vec![
+        segment_from_meters(&[]),
+        segment_from_meters(&[(0.0, 10.0), (1000.0, 10.0)]),
+        segment_from_meters(&[]),
+    ]);
+
+    let comparison = compare_tracks(&reference(), &track, &CompareOptions::default())
+        .expect("The reference path has points");
+
+    assert_eq!(comparison.point_count, 2);
+    assert!(comparison.frechet_distance_m.is_some());
+    assert!(comparison.hausdorff_distance_m.is_some());
+    assert!(comparison.dtw_distance_m.is_some());
+    assert!(comparison.area_between_m2.is_some());
+}
+
+#[test]
+fn duplicate_reference_points_have_a_closest_point() {
+    // The track starts before the reference path, so its first points are closest to the zero-length segment
+    let reference = track_from_segments(vec![segment_from_meters(&[
+        (0.0, 0.0),
+        (0.0, 0.0),
+        (1000.0, 0.0),
+    ])]);
+    let track = track_from_segments(vec![segment_from_meters(&[(-100.0, 0.0), (500.0, 10.0)])]);
+
+    let comparison = compare_tracks(&reference, &track, &CompareOptions::default())
+        .expect("The reference path has points");
+
+    assert_eq!(comparison.indeterminate_points, 0);
+    assert_eq!(comparison.point_count, 2);
+    assert!(comparison.average_distance_m.is_finite());
+    assert!((comparison.max_distance_m - 100.0).abs() < 1.0);
+}
+
+#[test]
+fn single_point_reference_counts_indeterminate_points() {
+    let reference = track_from_segments(vec![segment_from_meters(&[(0.0, 0.0)])]);
+    let track = track_from_segments(vec![segment_from_meters(&[(0.0, 10.0), (1000.0, 10.0)])]);
+
+    let comparison = compare_tracks(&reference, &track, &CompareOptions::default())
+        .expect("The reference path has points");
+
+    assert_eq!(comparison.indeterminate_points, 2);
+    assert_eq!(comparison.point_count, 0);
+    // The averages are not available instead of infinitely large
+    assert!(comparison.average_distance_m.is_nan());
+    assert!(comparison.rms_distance_m.is_nan());
+}
+
+#[test]
+fn empty_reference_is_rejected() {
+    // Segments without points do not count as points of the reference path
+    let empty = track_from_segments(vec![segment_from_meters(&[]), segment_from_meters(&[])]);
+
+    assert_eq!(check_reference(&empty), Err(EmptyReferenceError));
+    assert_eq!(
+        EmptyReferenceError.to_string(),
+        "the reference path does not contain any points"
+    );
+    assert_eq!(check_reference(&reference()), Ok(()));
+}
+
+#[test]
+fn empty_reference_is_an_error() {
+    let reference = track_from_segments(vec![segment_from_meters(&[])]);
+    let track = track_from_segments(vec![segment_from_meters(&[(0.0, 10.0), (1000.0, 10.0)])]);
+
+    assert_eq!(
+        compare_tracks(&reference, &track, &CompareOptions::default()).err(),
+        Some(EmptyReferenceError)
+    );
+    // Also with a projection created around the reference path, whose origin is NaN
+    for reference_segments in [ReferenceSegments::Join, ReferenceSegments::BestMatch] {
+        assert_eq!(
+            compare_tracks_with_projection(
+                &reference,
+                &track,
+                &CompareOptions {
+                    reference_segments,
+                    ..CompareOptions::default()
+                },
+                &reference_projection(&reference),
+            )
+            .err(),
+            Some(EmptyReferenceError)
+        );
+    }
+}
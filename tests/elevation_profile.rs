@@ -0,0 +1,122 @@
+mod common;
+
+use common::{assert_distance, track_from_meters};
+use gps_path_average_distance::{
+    ascent_descent, compare_tracks, elevation_profile, CompareOptions,
+};
+use gpx::Track;
+
+/// Function to set the elevation of every point of a single segment track.
+fn with_elevations(mut track: Track, elevations: &[Option<f64>]) -> Track {
+    for (point, elevation) in track.segments[0].points.iter_mut().zip(elevations) {
+        point.elevation = *elevation;
+    }
+    track
+}
+
+/// Ascent and descent add up the changes between the points with an elevation
+#[test]
+fn test_ascent_descent() {
+    let track = with_elevations(
+        track_from_meters(&[
+            (0.0, 0.0),
+            (10.0, 0.0),
+            (20.0, 0.0),
+            (30.0, 0.0),
+            (40.0, 0.0),
+        ]),
+        &[Some(100.0), Some(110.0), None, Some(104.0), Some(105.0)],
+    );
+
+    let (ascent, descent) = ascent_descent(&track);
+    assert_distance("ascent", ascent, 11.0);
+    assert_distance("descent", descent, 6.0);
+}
+
+/// The elevation of the reference path is interpolated at the same fraction of its length as every track point
+#[test]
+fn test_elevation_profile() {
+    let reference = with_elevations(
+        track_from_meters(&[(0.0, 0.0), (100.0, 0.0)]),
+        &[Some(100.0), Some(120.0)],
+    );
+    // 5m beside the reference path, at its start, half and end, where it is at 100m, 110m and 120m
+    let track = with_elevations(
+        track_from_meters(&[(0.0, 5.0), (50.0, 5.0), (100.0, 5.0)]),
+        &[Some(101.0), Some(112.0), Some(118.0)],
+    );
+
+    let profile = elevation_profile(&reference, &track).expect("Both tracks have elevations");
+    assert_distance(
+        "mean elevation difference",
+        profile.mean_elevation_diff_m.unwrap(),
+        5.0 / 3.0,
+    );
+    assert_distance("reference ascent", profile.reference_ascent_m, 20.0);
+    assert_distance("track ascent", profile.track_ascent_m, 17.0);
+
+    // Without elevations on one side there is nothing to compare
+    let opts = CompareOptions {
+        elevation_profile: true,
+        ..CompareOptions::default()
+    };
+    let flat_track = track_from_meters(&[(25.0, 5.0), (75.0, 5.0)]);
+    assert!(compare_tracks(&reference, &flat_track, &opts)
+        .expect("The reference path has points")
+        .elevation_profile
+        .is_none());
+    assert!(compare_tracks(&reference, &track, &opts)
+        .expect("The reference path has points")
+        .elevation_profile
+        .is_some());
+}
+
+/// Where the reference path crosses itself, the points are matched to the pass at the same distance along the path,
+/// even if a point is closer to the other pass
+#[test]
+fn test_elevation_profile_at_crossing() {
+    // Climbing by 1m every 10m, the crossing at (50, 0) is passed at 105m and at 135m
+    let reference = with_elevations(
+        track_from_meters(&[
+            (0.0, 0.0),
+            (100.0, 0.0),
+            (100.0, 100.0),
+            (50.0, 100.0),
+            (50.0, -100.0),
+        ]),
+        &[
+            Some(100.0),
+            Some(110.0),
+            Some(120.0),
+            Some(125.0),
+            Some(145.0),
+        ],
+    );
+    // On the second pass, the track passes the crossing closer to the first pass
+    let track = with_elevations(
+        track_from_meters(&[
+            (0.0, 0.0),
+            (100.0, 0.0),
+            (100.0, 100.0),
+            (50.0, 100.0),
+            (50.5, 0.2),
+            (50.0, -100.0),
+        ]),
+        &[
+            Some(100.0),
+            Some(110.0),
+            Some(120.0),
+            Some(125.0),
+            Some(135.0),
+            Some(145.0),
+        ],
+    );
+
+    let profile = elevation_profile(&reference, &track).expect("Both tracks have elevations");
+    let difference = profile.mean_elevation_diff_m.unwrap();
+    assert!(
+        difference < 0.01,
+        "The mean elevation difference is {}",
+        difference
+    );
+}
@@ -0,0 +1,150 @@
+//! Golden values of the metrics of the fixtures in `tests/fixtures`, to notice changes which shift the reported numbers.
+//! If a change shifts them on purpose, update the expected values together with the change and explain why in its description.
+
+mod common;
+
+use common::{assert_distance, fixture_track};
+use gps_path_average_distance::{compare_tracks, CompareOptions, DistanceMode, TrackComparison};
+
+/// The golden track runs about 4m north of the 1km eastward leg of the golden reference path and about 3m east of its
+/// 500m northward leg, both with a small sinusoidal wobble.
+fn golden_comparison(distance_mode: DistanceMode) -> TrackComparison {
+    compare_tracks(
+        &fixture_track("golden_reference.gpx"),
+        &fixture_track("golden_track.gpx"),
+        &CompareOptions {
+            distance_mode,
+            ..CompareOptions::default()
+        },
+    )
+    .expect("The reference path has points")
+}
+
+#[test]
+fn golden_metrics_flat() {
+    let comparison = golden_comparison(DistanceMode::Flat);
+
+    assert_distance(
+        "current_track_length_m",
+        comparison.current_track_length_m,
+        1500.6115696114582,
+    );
+    assert_distance(
+        "reference_track_length_m",
+        comparison.reference_track_length_m,
+        1502.9352252659785,
+    );
+    assert_distance(
+        "average_distance_m",
+        comparison.average_distance_m,
+        3.856239722244626,
+    );
+    assert_distance(
+        "simplified_average_distance_m",
+        comparison.simplified_average_distance_m,
+        3.5524441085330367,
+    );
+    assert_distance(
+        "rms_distance_m",
+        comparison.rms_distance_m,
+        4.1290352701624125,
+    );
+    assert_distance(
+        "median_distance_m",
+        comparison.median_distance_m,
+        3.9361379694622527,
+    );
+    assert_distance(
+        "max_distance_m",
+        comparison.max_distance_m,
+        5.993159224145145,
+    );
+    assert_distance(
+        "frechet_distance_m",
+        comparison.frechet_distance_m.unwrap(),
+        25.780031119365383,
+    );
+    assert_distance(
+        "hausdorff_distance_m",
+        comparison.hausdorff_distance_m.unwrap(),
+        25.780031119365383,
+    );
+    assert_distance(
+        "dtw_distance_m",
+        comparison.dtw_distance_m.unwrap(),
+        882.367187690612,
+    );
+    assert_distance(
+        "area_between_m2",
+        comparison.area_between_m2.unwrap(),
+        5780.5634080680065,
+    );
+    assert_eq!(comparison.point_count, 61);
+    assert_eq!(comparison.simplified_point_count, 8);
+}
+
+#[test]
+fn golden_metrics_geodesic() {
+    let comparison = golden_comparison(DistanceMode::Geodesic);
+
+    assert_distance(
+        "current_track_length_m",
+        comparison.current_track_length_m,
+        1497.6703321428415,
+    );
+    assert_distance(
+        "reference_track_length_m",
+        comparison.reference_track_length_m,
+        1499.997421011417,
+    );
+    assert_distance(
+        "average_distance_m",
+        comparison.average_distance_m,
+        3.853392882650391,
+    );
+    assert_distance(
+        "simplified_average_distance_m",
+        comparison.simplified_average_distance_m,
+        3.5486080695096685,
+    );
+    assert_distance(
+        "rms_distance_m",
+        comparison.rms_distance_m,
+        4.126745794942734,
+    );
+    assert_distance(
+        "median_distance_m",
+        comparison.median_distance_m,
+        3.9362513646348343,
+    );
+    assert_distance(
+        "max_distance_m",
+        comparison.max_distance_m,
+        5.993414823519083,
+    );
+    assert_distance(
+        "frechet_distance_m",
+        comparison.frechet_distance_m.unwrap(),
+        25.70813289354839,
+    );
+    assert_distance(
+        "dtw_distance_m",
+        comparison.dtw_distance_m.unwrap(),
+        880.8243528378413,
+    );
+    assert_distance(
+        "area_between_m2",
+        comparison.area_between_m2.unwrap(),
+        5780.536101721226,
+    );
+}
+
+#[test]
+fn both_distance_modes_agree_on_short_tracks() {
+    let flat = golden_comparison(DistanceMode::Flat);
+    let geodesic = golden_comparison(DistanceMode::Geodesic);
+
+    // The flat projection is accurate to well below a centimeter for tracks of about a kilometer
+    assert!((flat.average_distance_m - geodesic.average_distance_m).abs() < 0.01);
+    assert!((flat.max_distance_m - geodesic.max_distance_m).abs() < 0.01);
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use approx::assert_relative_eq;
+use common::{track_from_meters, METERS_PER_DEGREE};
+use gps_path_average_distance::{compare_tracks, CompareOptions, DistanceMode};
+
+/// The point of the reference path matched to the point with the largest distance is reported in LatLon coordinates
+#[test]
+fn test_max_distance_reference_point() {
+    let reference = track_from_meters(&[(0.0, 0.0), (100.0, 0.0)]);
+    // Excursion of 20m north of the middle of the reference path
+    let track = track_from_meters(&[(0.0, 2.0), (50.0, 20.0), (100.0, 2.0)]);
+
+    for distance_mode in [DistanceMode::Flat, DistanceMode::Geodesic] {
+        let comparison = compare_tracks(
+            &reference,
+            &track,
+            &CompareOptions {
+                distance_mode,
+                ..CompareOptions::default()
+            },
+        )
+        .expect("The reference path has points");
+        assert_eq!(comparison.max_distance_point_index, Some(1));
+        let reference_point = comparison
+            .max_distance_reference_point
+            .expect("The track has points");
+        assert_relative_eq!(
+            reference_point.x(),
+            50.0 / METERS_PER_DEGREE,
+            epsilon = 1e-8
+        );
+        assert_relative_eq!(reference_point.y(), 0.0, epsilon = 1e-8);
+    }
+
+    // Without points there is no excursion
+    let empty = compare_tracks(&reference, &gpx::Track::new(), &CompareOptions::default())
+        .expect("The reference path has points");
+    assert_eq!(empty.max_distance_reference_point, None);
+}
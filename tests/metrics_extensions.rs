@@ -0,0 +1,56 @@
+use geo::Point;
+use gps_path_average_distance::export::{
+    add_metrics_extensions, read_metrics_extensions, ExportedMetrics,
+};
+use gpx::{Gpx, GpxVersion, Track, TrackSegment, Waypoint};
+
+/// Function to build a GPX file with the given number of named single point tracks.
+fn gpx_with_tracks(count: usize) -> Gpx {
+    let mut gpx = Gpx {
+        version: GpxVersion::Gpx11,
+        ..Gpx::default()
+    };
+    for index in 0..count {
+        let mut track = Track::new();
+        track.name = Some(format!("Track <{}>", index + 1));
+        track.segments.push(TrackSegment {
+            points: vec![Waypoint::new(Point::new(11.0, 48.0))],
+        });
+        gpx.tracks.push(track);
+    }
+    gpx
+}
+
+/// The metrics written into the extensions of the tracks are recovered exactly, and the file stays readable
+#[test]
+fn test_metrics_extensions_round_trip() {
+    let mut content = Vec::new();
+    gpx::write(&gpx_with_tracks(3), &mut content).unwrap();
+    let metrics = vec![
+        Some(ExportedMetrics {
+            average_distance_m: Some(2.114343903177985),
+            simplified_average_distance_m: Some(2.2314210566204653),
+            frechet_distance_m: None,
+            hausdorff_distance_m: Some(6.964251899165053),
+            dtw_distance_m: Some(1010.2724314137008),
+        }),
+        // The second track was not compared
+        None,
+        Some(ExportedMetrics {
+            average_distance_m: Some(0.1),
+            ..ExportedMetrics::default()
+        }),
+    ];
+
+    let exported = add_metrics_extensions(&String::from_utf8(content).unwrap(), &metrics);
+    assert_eq!(
+        read_metrics_extensions(exported.as_bytes()).unwrap(),
+        metrics
+    );
+
+    // The extensions are placed where the GPX schema allows them, so the tracks are still read as before
+    let gpx = gpx::read(exported.as_bytes()).unwrap();
+    assert_eq!(gpx.tracks.len(), 3);
+    assert_eq!(gpx.tracks[0].name.as_deref(), Some("Track <1>"));
+    assert_eq!(gpx.tracks[2].segments[0].points.len(), 1);
+}
@@ -0,0 +1,61 @@
+use gps_path_average_distance::{percentile, DistanceAccumulator};
+
+#[test]
+fn percentile_interpolates_between_ranks() {
+    let values = [1.0, 2.0, 3.0, 4.0, 10.0];
+
+    assert_eq!(percentile(&values, 0.0), 1.0);
+    assert_eq!(percentile(&values, 50.0), 3.0);
+    assert_eq!(percentile(&values, 100.0), 10.0);
+    // Rank 0.9 * 4 = 3.6 lies between 4 and 10
+    assert!((percentile(&values, 90.0) - 7.6).abs() < 1e-12);
+    // Rank 0.95 * 4 = 3.8 lies between 4 and 10
+    assert!((percentile(&values, 95.0) - 8.8).abs() < 1e-12);
+}
+
+#[test]
+fn percentile_of_even_count_is_mean_of_middle_values() {
+    assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0], 50.0), 2.5);
+}
+
+#[test]
+fn percentile_of_no_values_is_nan() {
+    assert!(percentile(&[], 50.0).is_nan());
+}
+
+#[test]
+fn accumulator_sorts_distances() {
+    let mut accumulator = DistanceAccumulator::default();
+    for (index, distance) in [4.0, 1.0, 10.0, 3.0, 2.0].into_iter().enumerate() {
+        accumulator.add(index, distance);
+    }
+
+    assert_eq!(
+        accumulator.sorted_distances(),
+        vec![1.0, 2.0, 3.0, 4.0, 10.0]
+    );
+    assert_eq!(accumulator.max_distance_index, Some(2));
+}
+
+#[test]
+fn rms_is_at_least_the_average() {
+    let mut accumulator = DistanceAccumulator::default();
+    for (index, distance) in [1.0, 2.0, 3.0, 4.0, 10.0].into_iter().enumerate() {
+        accumulator.add(index, distance);
+    }
+
+    // sqrt((1 + 4 + 9 + 16 + 100) / 5) = sqrt(26)
+    assert!((accumulator.rms() - 26.0_f64.sqrt()).abs() < 1e-12);
+    assert_eq!(accumulator.average(), 4.0);
+    assert!(accumulator.rms() >= accumulator.average());
+}
+
+#[test]
+fn rms_of_constant_distances_is_the_average() {
+    let mut accumulator = DistanceAccumulator::default();
+    for index in 0..4 {
+        accumulator.add(index, 2.5);
+    }
+
+    assert!((accumulator.rms() - accumulator.average()).abs() < 1e-12);
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::track_from_meters;
+use gps_path_average_distance::{bounding_boxes_overlap, reference_projection};
+use gpx::Track;
+
+/// The bounding boxes overlap if the gap between them is at most the margin
+#[test]
+fn test_bounding_boxes_overlap() {
+    let reference = track_from_meters(&[(0.0, 0.0), (100.0, 100.0)]);
+    let projector = reference_projection(&reference);
+    // Crosses the reference path
+    let crossing = track_from_meters(&[(0.0, 100.0), (100.0, 0.0)]);
+    // Starts 50m east of the bounding box of the reference path
+    let beside = track_from_meters(&[(150.0, 0.0), (250.0, 100.0)]);
+
+    assert!(bounding_boxes_overlap(
+        &reference, &crossing, 0.0, &projector
+    ));
+    assert!(!bounding_boxes_overlap(
+        &reference, &beside, 0.0, &projector
+    ));
+    assert!(!bounding_boxes_overlap(
+        &reference, &beside, 45.0, &projector
+    ));
+    assert!(bounding_boxes_overlap(
+        &reference, &beside, 55.0, &projector
+    ));
+
+    // Tracks without points are never filtered, so they are still reported as empty
+    assert!(bounding_boxes_overlap(
+        &reference,
+        &Track::new(),
+        0.0,
+        &projector
+    ));
+}
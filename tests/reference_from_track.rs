@@ -0,0 +1,89 @@
+mod common;
+
+use common::{segment_from_meters, track_from_segments};
+use gps_path_average_distance::{exclude_reference_track, split_segments};
+use gpx::Track;
+
+/// Four tracks named by their 1-based index, every track has as many segments as its index
+fn group_recording() -> Vec<Track> {
+    (1..=4)
+        .map(|number| {
+            let mut track = track_from_segments(
+                (0..number)
+                    .map(|segment| segment_from_meters(&[(0.0, segment as f64), (100.0, 0.0)]))
+                    .collect(),
+            );
+            track.name = Some(number.to_string());
+            track
+        })
+        .collect()
+}
+
+/// Names of the tracks, to check which of them are left
+fn names(tracks: &[Track]) -> Vec<&str> {
+    tracks
+        .iter()
+        .map(|track| track.name.as_deref().unwrap_or_default())
+        .collect()
+}
+
+/// The tracks 1, 2 and 4 are compared to track 3 and keep their index in the file
+#[test]
+fn test_reference_track_is_excluded() {
+    let mut tracks = group_recording();
+    let mut origins: Vec<(usize, Option<usize>)> =
+        (0..tracks.len()).map(|index| (index, None)).collect();
+
+    assert_eq!(
+        exclude_reference_track(&mut tracks, &mut origins, 2, false),
+        1
+    );
+    assert_eq!(names(&tracks), ["1", "2", "4"]);
+    assert_eq!(origins, [(0, None), (1, None), (3, None)]);
+}
+
+/// With --keep-reference-track the reference track is compared to itself as well
+#[test]
+fn test_reference_track_is_kept() {
+    let mut tracks = group_recording();
+    let mut origins: Vec<(usize, Option<usize>)> =
+        (0..tracks.len()).map(|index| (index, None)).collect();
+
+    assert_eq!(
+        exclude_reference_track(&mut tracks, &mut origins, 2, true),
+        0
+    );
+    assert_eq!(names(&tracks), ["1", "2", "3", "4"]);
+    assert_eq!(origins, [(0, None), (1, None), (2, None), (3, None)]);
+}
+
+/// With --per-segment all segments of the reference track are excluded, the segments of the other tracks keep their origins
+#[test]
+fn test_reference_segments_are_excluded() {
+    let mut tracks = Vec::new();
+    let mut origins = Vec::new();
+    for (index, track) in group_recording().iter().enumerate() {
+        for (segment_index, segment_track) in split_segments(track).into_iter().enumerate() {
+            tracks.push(segment_track);
+            origins.push((index, Some(segment_index)));
+        }
+    }
+
+    assert_eq!(
+        exclude_reference_track(&mut tracks, &mut origins, 2, false),
+        3
+    );
+    assert_eq!(tracks.len(), origins.len());
+    assert_eq!(
+        origins,
+        [
+            (0, Some(0)),
+            (1, Some(0)),
+            (1, Some(1)),
+            (3, Some(0)),
+            (3, Some(1)),
+            (3, Some(2)),
+            (3, Some(3)),
+        ]
+    );
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::track_from_meters;
+use gps_path_average_distance::{
+    compare_tracks, resample_track, track_projection, CompareOptions, ProjectionOrigin,
+};
+use gpx::Track;
+
+/// Function to build a track from points given in meters (east, north) with the given HDOP values.
+fn track_with_hdop(points: &[(f64, f64, f64)]) -> Track {
+    let mut track = track_from_meters(
+        &points
+            .iter()
+            .map(|&(east, north, _)| (east, north))
+            .collect::<Vec<_>>(),
+    );
+    for (waypoint, &(_, _, hdop)) in track.segments[0].points.iter_mut().zip(points) {
+        waypoint.hdop = Some(hdop);
+    }
+    track
+}
+
+/// The resampled points get the HDOP interpolated between their neighbouring recorded points
+#[test]
+fn test_resampled_points_keep_hdop() {
+    let track = track_with_hdop(&[(0.0, 0.0, 1.0), (0.0, 1000.0, 3.0)]);
+    let projector = track_projection(&track, &track, ProjectionOrigin::Reference);
+
+    let resampled = resample_track(&track, 300.0, &projector);
+    let points = &resampled.segments[0].points;
+    let end = points[points.len() - 1].point().y();
+
+    assert_eq!(points.len(), 5);
+    for point in points {
+        // The track runs north, so the fraction of its length is the fraction of its latitude
+        let fraction = point.point().y() / end;
+        let hdop = point.hdop.expect("Both recorded points have an HDOP");
+        assert!(
+            (hdop - (1.0 + 2.0 * fraction)).abs() < 1e-9,
+            "The HDOP at {} of the track is {}",
+            fraction,
+            hdop
+        );
+    }
+}
+
+/// Points without an HDOP on either side stay without one
+#[test]
+fn test_resampled_points_without_hdop() {
+    let track = track_from_meters(&[(0.0, 0.0), (0.0, 1000.0)]);
+    let projector = track_projection(&track, &track, ProjectionOrigin::Reference);
+
+    let resampled = resample_track(&track, 300.0, &projector);
+
+    assert!(resampled.segments[0]
+        .points
+        .iter()
+        .all(|point| point.hdop.is_none()));
+}
+
+/// With --resample the weighted average still weights the points by their HDOP, instead of counting every resampled point once
+#[test]
+fn test_weighted_average_after_resampling() {
+    let reference = track_from_meters(&[(0.0, 0.0), (0.0, 1000.0)]);
+    // The points furthest away from the reference path, in the middle of the track, are the least accurate ones
+    let track = track_with_hdop(&[(0.0, 0.0, 1.0), (20.0, 500.0, 5.0), (0.0, 1000.0, 1.0)]);
+
+    let comparison = compare_tracks(
+        &reference,
+        &track,
+        &CompareOptions {
+            resample: Some(50.0),
+            weight_by_hdop: true,
+            ..CompareOptions::default()
+        },
+    )
+    .expect("The reference path has points");
+    let weighted = comparison
+        .weighted_average_distance_m
+        .expect("The weighting by HDOP was requested");
+
+    // Without the HDOP of the resampled points all of them would have the weight 1, matching the unweighted average
+    assert!(
+        weighted < comparison.average_distance_m * 0.9,
+        "The weighted average {} is not below the average {}",
+        weighted,
+        comparison.average_distance_m
+    );
+}
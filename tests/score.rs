@@ -0,0 +1,39 @@
+use gps_path_average_distance::{distance_score, match_score, ScoreWeights};
+
+#[test]
+fn test_distance_score_halves_every_ten_meters() {
+    assert_eq!(distance_score(0.0), 100.0);
+    assert!((distance_score(10.0) - 50.0).abs() < 1e-9);
+    assert!((distance_score(20.0) - 25.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_match_score_weights() {
+    let weights: ScoreWeights = "1,0,3".parse().unwrap();
+    // The Hausdorff distance has no weight, so its score does not matter
+    let score = match_score(Some(10.0), Some(1000.0), Some(0.0), &weights).unwrap();
+    assert!((score - (50.0 + 3.0 * 100.0) / 4.0).abs() < 1e-9);
+
+    // Missing curve metrics are left out of the mean
+    let score = match_score(None, None, Some(10.0), &ScoreWeights::default()).unwrap();
+    assert!((score - 50.0).abs() < 1e-9);
+    assert_eq!(
+        match_score(None, Some(1.0), Some(1.0), &"1,0,0".parse().unwrap()),
+        None
+    );
+}
+
+#[test]
+fn test_parse_score_weights() {
+    assert_eq!(
+        "1, 2,0.5".parse::<ScoreWeights>(),
+        Ok(ScoreWeights {
+            frechet: 1.0,
+            hausdorff: 2.0,
+            average: 0.5,
+        })
+    );
+    assert!("0,0,0".parse::<ScoreWeights>().is_err());
+    assert!("1,-1,1".parse::<ScoreWeights>().is_err());
+    assert!("1,1".parse::<ScoreWeights>().is_err());
+}
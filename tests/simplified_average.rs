@@ -0,0 +1,44 @@
+mod common;
+
+use common::track_from_meters;
+use gps_path_average_distance::{compare_tracks, CompareOptions};
+use gpx::Track;
+
+/// A current track running 5m next to the reference, except for a single 15m excursion at x = 500m,
+/// where the recording stood still for `repeats` points.
+fn noisy_track(repeats: usize) -> Track {
+    let mut points = Vec::new();
+    for step in 0..=100 {
+        let east = step as f64 * 10.0;
+        if step == 50 {
+            points.extend(std::iter::repeat_n((east, 15.0), repeats));
+        } else {
+            points.push((east, 5.0));
+        }
+    }
+    track_from_meters(&points)
+}
+
+#[test]
+fn simplified_average_ignores_stationary_points() {
+    let reference = track_from_meters(&[(0.0, 0.0), (500.0, 0.0), (1000.0, 0.0)]);
+    let options = CompareOptions::default();
+
+    let moving = compare_tracks(&reference, &noisy_track(1), &options)
+        .expect("The reference path has points");
+    let stationary = compare_tracks(&reference, &noisy_track(200), &options)
+        .expect("The reference path has points");
+
+    // The average in time is dominated by the stationary points
+    assert!(stationary.average_distance_m > moving.average_distance_m + 5.0);
+
+    // The simplified current track only keeps the corners of the excursion plus the endpoints,
+    // so its points are 5m, 5m, 15m, 5m, 5m away from the reference, independent of how long the excursion lasted
+    assert_eq!(moving.simplified_point_count, 5);
+    assert_eq!(stationary.simplified_point_count, 5);
+    assert!((moving.simplified_average_distance_m - 7.0).abs() < 0.05);
+    assert!(
+        (moving.simplified_average_distance_m - stationary.simplified_average_distance_m).abs()
+            < 1e-9
+    );
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::track_from_meters_with_elevation;
+use gps_path_average_distance::export::simplified_waypoints;
+use gps_path_average_distance::{compare_tracks, CompareOptions};
+
+#[test]
+fn simplified_waypoints_keep_their_elevation() {
+    let reference = track_from_meters_with_elevation(&[(0.0, 0.0, 0.0), (1000.0, 0.0, 0.0)]);
+    // A straight line with a single 20m excursion, every point has its own elevation
+    let current = track_from_meters_with_elevation(
+        &(0..=100)
+            .map(|step| {
+                let north = if step == 50 { 20.0 } else { 5.0 };
+                (step as f64 * 10.0, north, 100.0 + step as f64)
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    let comparison = compare_tracks(&reference, &current, &CompareOptions::default())
+        .expect("The reference path has points");
+    let waypoints = simplified_waypoints(&current, &comparison.simplified_track);
+
+    // Only the endpoints and the corners of the excursion are retained
+    assert_eq!(waypoints.len(), 5);
+    let elevations: Vec<Option<f64>> = waypoints
+        .iter()
+        .map(|waypoint| waypoint.elevation)
+        .collect();
+    assert_eq!(
+        elevations,
+        vec![
+            Some(100.0),
+            Some(149.0),
+            Some(150.0),
+            Some(151.0),
+            Some(200.0)
+        ]
+    );
+}
+
+#[test]
+fn repeated_coordinates_are_matched_in_order() {
+    let current = track_from_meters_with_elevation(&[
+        (0.0, 0.0, 1.0),
+        (10.0, 0.0, 2.0),
+        (0.0, 0.0, 3.0),
+        (10.0, 0.0, 4.0),
+    ]);
+    let simplified = current.segments[0]
+        .points
+        .iter()
+        .skip(1)
+        .map(|waypoint| waypoint.point())
+        .collect();
+
+    let elevations: Vec<Option<f64>> = simplified_waypoints(&current, &simplified)
+        .iter()
+        .map(|waypoint| waypoint.elevation)
+        .collect();
+    assert_eq!(elevations, vec![Some(2.0), Some(3.0), Some(4.0)]);
+}
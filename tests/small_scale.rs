@@ -0,0 +1,86 @@
+mod common;
+
+use approx::assert_relative_eq;
+use common::{assert_distance, track_from_meters};
+use gps_path_average_distance::{compare_tracks, CompareOptions, DistanceMode};
+
+/// Function to build a 5m long track heading north in steps of 0.5m, moved by the given meters to the east.
+fn rig_track(east_m: f64) -> Vec<(f64, f64)> {
+    (0..=10)
+        .map(|index| (east_m, f64::from(index) * 0.5))
+        .collect()
+}
+
+/// Distances, lengths and areas of tracks only a few meters long are not affected by computing them in kilometers
+#[test]
+fn test_sub_meter_distances() {
+    let reference = track_from_meters(&rig_track(0.0));
+    let track = track_from_meters(&rig_track(0.25));
+    let comparison = compare_tracks(&reference, &track, &CompareOptions::default())
+        .expect("The reference path has points");
+
+    // The test tracks convert meters to degrees with the equatorial circumference, which the flat projection reproduces east-west
+    assert_distance("average distance", comparison.average_distance_m, 0.25);
+    assert_distance("maximum distance", comparison.max_distance_m, 0.25);
+    assert_distance(
+        "Fréchet distance",
+        comparison.frechet_distance_m.unwrap(),
+        0.25,
+    );
+    assert_relative_eq!(
+        comparison.area_between_m2.unwrap(),
+        comparison.average_distance_m * comparison.reference_track_length_m,
+        max_relative = 1e-9
+    );
+    assert_relative_eq!(
+        comparison.reference_track_length_m,
+        5.0,
+        max_relative = 1e-2
+    );
+
+    // The geodesic mode measures on a sphere, which only differs by the size of the earth model
+    let geodesic = compare_tracks(
+        &reference,
+        &track,
+        &CompareOptions {
+            distance_mode: DistanceMode::Geodesic,
+            ..CompareOptions::default()
+        },
+    )
+    .expect("The reference path has points");
+    assert_relative_eq!(
+        geodesic.average_distance_m,
+        comparison.average_distance_m,
+        max_relative = 2e-3
+    );
+}
+
+/// Epsilons below a meter simplify tracks at the scale of centimeters
+#[test]
+fn test_sub_meter_simplification() {
+    // Zig-zag of 2cm around a straight line
+    let points: Vec<(f64, f64)> = (0..=10)
+        .map(|index| {
+            let east = if index % 2 == 0 { 0.0 } else { 0.02 };
+            (east, f64::from(index) * 0.5)
+        })
+        .collect();
+    let reference = track_from_meters(&rig_track(0.0));
+    let track = track_from_meters(&points);
+
+    let simplified_count = |simplify_epsilon: f64| {
+        compare_tracks(
+            &reference,
+            &track,
+            &CompareOptions {
+                simplify_epsilon,
+                ..CompareOptions::default()
+            },
+        )
+        .expect("The reference path has points")
+        .simplified_point_count
+    };
+    // 1cm keeps the zig-zag, 5cm removes it
+    assert_eq!(simplified_count(0.01), 11);
+    assert_eq!(simplified_count(0.05), 2);
+}
@@ -0,0 +1,88 @@
+use geo::{Closest, ClosestPoint, EuclideanDistance, LineString, Point};
+use gps_path_average_distance::{calculate_average_distance, DistanceAccumulator, ReferenceIndex};
+
+/// Distance from a point to the closest point returned by a closest point search
+fn closest_distance(point: &Point, closest: Closest<f64>) -> f64 {
+    match closest {
+        Closest::Intersection(p) | Closest::SinglePoint(p) => p.euclidean_distance(point),
+        Closest::Indeterminate => f64::INFINITY,
+    }
+}
+
+/// A winding reference path with a thousand segments (in kilometers, like projected tracks)
+fn reference_path() -> LineString {
+    (0..1000)
+        .map(|i| {
+            let t = i as f64 * 0.03;
+            (
+                t * 0.3 + (t * 1.7).sin() * 0.2,
+                (t * 0.9).cos() * 1.5 + t * 0.05,
+            )
+        })
+        .collect()
+}
+
+/// A track loosely following the reference path with a deterministic jitter
+fn current_track() -> LineString {
+    (0..2000)
+        .map(|i| {
+            let t = i as f64 * 0.015;
+            let jitter = ((i * 7919) % 101) as f64 / 101.0 - 0.5;
+            (
+                t * 0.3 + (t * 1.7).sin() * 0.2 + jitter * 0.05,
+                (t * 0.9).cos() * 1.5 + t * 0.05 - jitter * 0.03,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn index_matches_brute_force_closest_point() {
+    let reference = reference_path();
+    let index = ReferenceIndex::new(&reference);
+
+    for point in current_track().points() {
+        let brute_force = closest_distance(&point, reference.closest_point(&point));
+        let indexed = closest_distance(&point, index.closest_point(&point));
+        assert!(
+            (brute_force - indexed).abs() < 1e-12,
+            "closest distance of {:?} differs: {} vs {}",
+            point,
+            brute_force,
+            indexed
+        );
+    }
+}
+
+#[test]
+fn average_distance_matches_brute_force() {
+    let reference = reference_path();
+    let current = current_track();
+
+    let brute_force_total: f64 = current
+        .points()
+        .map(|point| closest_distance(&point, reference.closest_point(&point)))
+        .sum();
+
+    let mut accumulator = DistanceAccumulator::default();
+    calculate_average_distance(
+        &current,
+        None,
+        &ReferenceIndex::new(&reference),
+        &mut accumulator,
+        None,
+    );
+
+    assert_eq!(accumulator.points, current.0.len());
+    assert!((brute_force_total - accumulator.total_distance).abs() < 1e-9);
+}
+
+#[test]
+fn index_without_segments_is_indeterminate() {
+    let single_point: LineString = vec![(1.0, 1.0)].into();
+    let index = ReferenceIndex::new(&single_point);
+    assert_eq!(
+        index.closest_point(&Point::new(0.0, 0.0)),
+        Closest::Indeterminate
+    );
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::{assert_distance, fixture_path, fixture_track, track_from_meters};
+use gps_path_average_distance::input::{stream_file, StreamEvent};
+use gps_path_average_distance::{
+    compare_tracks, reference_projection, CompareOptions, DistanceMode, StreamingComparison,
+};
+
+/// The streamed metrics match those of the regular comparison, in both distance modes
+#[test]
+fn test_streaming_matches_comparison() {
+    let reference = fixture_track("golden_reference.gpx");
+    let track = fixture_track("golden_track.gpx");
+
+    for distance_mode in [DistanceMode::Flat, DistanceMode::Geodesic] {
+        let opts = CompareOptions {
+            distance_mode,
+            ..CompareOptions::default()
+        };
+        let mut streaming =
+            StreamingComparison::new(&reference, &opts, &reference_projection(&reference));
+        stream_file(&fixture_path("golden_track.gpx"), |event| {
+            streaming.handle(event)
+        })
+        .expect("The fixture can be streamed");
+        let statistics = streaming.finish();
+        assert_eq!(statistics.len(), 1);
+
+        let comparison =
+            compare_tracks(&reference, &track, &opts).expect("The reference path has points");
+        let streamed = &statistics[0];
+        assert_eq!(streamed.track_name, track.name);
+        assert_eq!(streamed.point_count, comparison.point_count);
+        assert_distance(
+            "current_track_length_m",
+            streamed.current_track_length_m,
+            comparison.current_track_length_m,
+        );
+        assert_distance(
+            "reference_track_length_m",
+            streamed.reference_track_length_m,
+            comparison.reference_track_length_m,
+        );
+        assert_distance(
+            "average_distance_m",
+            streamed.average_distance_m.unwrap(),
+            comparison.average_distance_m,
+        );
+        assert_distance(
+            "rms_distance_m",
+            streamed.rms_distance_m.unwrap(),
+            comparison.rms_distance_m,
+        );
+        assert_distance(
+            "max_distance_m",
+            streamed.max_distance_m.unwrap(),
+            comparison.max_distance_m,
+        );
+    }
+}
+
+/// Points without a track or segment event start one, and the line between segments only counts if the gaps are included
+#[test]
+fn test_streaming_segment_gaps() {
+    let reference = track_from_meters(&[(0.0, 0.0), (1000.0, 0.0)]);
+    let first = track_from_meters(&[(0.0, 10.0), (100.0, 10.0)]);
+    let second = track_from_meters(&[(200.0, 10.0), (300.0, 10.0)]);
+
+    for (exclude_segment_gaps, expected_length) in [(false, 300.0), (true, 200.0)] {
+        let opts = CompareOptions {
+            exclude_segment_gaps,
+            ..CompareOptions::default()
+        };
+        let mut streaming =
+            StreamingComparison::new(&reference, &opts, &reference_projection(&reference));
+        for segment in [&first, &second] {
+            streaming.handle(StreamEvent::Segment);
+            for point in &segment.segments[0].points {
+                streaming.handle(StreamEvent::Point(point.point()));
+            }
+        }
+        let statistics = streaming.finish();
+        assert_eq!(statistics.len(), 1);
+        assert_eq!(statistics[0].point_count, 4);
+        assert!((statistics[0].current_track_length_m - expected_length).abs() < 0.1);
+        assert!((statistics[0].average_distance_m.unwrap() - 10.0).abs() < 0.1);
+    }
+}
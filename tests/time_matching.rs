@@ -0,0 +1,57 @@
+mod common;
+
+use approx::assert_relative_eq;
+use common::track_from_meters;
+use gps_path_average_distance::{
+    compare_tracks, reference_projection, time_matched_distance, CompareOptions,
+};
+use gpx::Track;
+use time::{Duration, OffsetDateTime};
+
+/// Function to set the timestamps of the points of a single segment track, in seconds after a fixed start.
+fn with_times(mut track: Track, seconds: &[Option<i64>]) -> Track {
+    let start = OffsetDateTime::from_unix_timestamp(1_714_557_600).unwrap();
+    for (point, second) in track.segments[0].points.iter_mut().zip(seconds) {
+        point.time = second.map(|second| (start + Duration::seconds(second)).into());
+    }
+    track
+}
+
+/// A track on the same line as the reference path, but lagging behind it, has no geometric but a temporal distance
+#[test]
+fn test_time_matched_distance() {
+    // The reference path covers 10m per second
+    let reference = with_times(
+        track_from_meters(&[(0.0, 0.0), (100.0, 0.0), (200.0, 0.0)]),
+        &[Some(0), Some(10), Some(20)],
+    );
+    // At 5s and 15s the reference path is at 50m and 150m, the track is 20m behind
+    // The untimed point and the point after the end of the reference path are not matched
+    let track = with_times(
+        track_from_meters(&[(30.0, 0.0), (130.0, 0.0), (150.0, 0.0), (190.0, 0.0)]),
+        &[Some(5), Some(15), None, Some(25)],
+    );
+
+    let distance = time_matched_distance(&reference, &track, &reference_projection(&reference))
+        .expect("The track has timestamps within the time span of the reference path");
+    assert_eq!(distance.matched_points, 2);
+    // The test tracks convert meters to degrees with a slightly different scale than the flat projection
+    assert_relative_eq!(distance.average_distance_m, 20.0, max_relative = 1e-6);
+    assert_relative_eq!(distance.max_distance_m, 20.0, max_relative = 1e-6);
+
+    let opts = CompareOptions {
+        match_by_time: true,
+        ..CompareOptions::default()
+    };
+    let comparison =
+        compare_tracks(&reference, &track, &opts).expect("The reference path has points");
+    assert!(comparison.average_distance_m < 1e-6);
+    assert!(comparison.time_matched_distance.is_some());
+
+    // Without timestamps there is nothing to match
+    let untimed = track_from_meters(&[(30.0, 0.0), (130.0, 0.0)]);
+    assert!(compare_tracks(&reference, &untimed, &opts)
+        .expect("The reference path has points")
+        .time_matched_distance
+        .is_none());
+}
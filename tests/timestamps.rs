@@ -0,0 +1,46 @@
+use gps_path_average_distance::input::{read_bytes, InputFormat};
+use gps_path_average_distance::{validate_track, TrackIssue};
+use time::{OffsetDateTime, UtcOffset};
+
+const TIMED_GPX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1">
+  <trk>
+    <trkseg>
+      <trkpt lat="48.0" lon="11.0"><time>2024-05-01T12:00:00+02:00</time></trkpt>
+      <trkpt lat="48.0001" lon="11.0"><time>2024-05-01T10:00:05Z</time></trkpt>
+      <trkpt lat="48.0002" lon="11.0"><time>2024-05-01T10:00:05Z</time></trkpt>
+    </trkseg>
+    <trkseg>
+      <trkpt lat="48.0003" lon="11.0"><time>2024-05-01T10:00:02Z</time></trkpt>
+      <trkpt lat="48.0004" lon="11.0"></trkpt>
+      <trkpt lat="48.0005" lon="11.0"><time>2024-05-01T10:00:10Z</time></trkpt>
+    </trkseg>
+  </trk>
+</gpx>"#;
+
+/// Times with an offset are converted to UTC when the file is read
+#[test]
+fn test_times_are_normalized_to_utc() {
+    let gpx = read_bytes(TIMED_GPX.as_bytes(), InputFormat::Gpx).expect("The GPX is valid");
+    let first = OffsetDateTime::from(gpx.tracks[0].segments[0].points[0].time.unwrap());
+
+    assert_eq!(first.offset(), UtcOffset::UTC);
+    assert_eq!(first.hour(), 10);
+}
+
+/// Duplicate and decreasing timestamps are reported, also across segments, while points without a time are skipped
+#[test]
+fn test_out_of_order_timestamps_are_reported() {
+    let gpx = read_bytes(TIMED_GPX.as_bytes(), InputFormat::Gpx).expect("The GPX is valid");
+
+    assert_eq!(
+        validate_track(&gpx.tracks[0], None),
+        vec![
+            TrackIssue::DuplicateTime { index: 2 },
+            TrackIssue::TimeBackwards {
+                index: 3,
+                seconds: 3.0
+            },
+        ]
+    );
+}
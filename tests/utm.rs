@@ -0,0 +1,122 @@
+mod common;
+
+use common::{assert_distance, fixture_track, track_from_segments};
+use geo::Point;
+use gps_path_average_distance::{
+    compare_tracks, CompareOptions, DistanceMode, Projection, UtmProjection, UTM_HALF_ZONE_WIDTH,
+};
+use gpx::{TrackSegment, Waypoint};
+
+/// The zone is selected from the longitude, the hemisphere from the latitude
+#[test]
+fn test_zone_selection() {
+    let berlin = UtmProjection::for_point(Point::new(13.4, 52.5));
+    assert_eq!((berlin.zone, berlin.north), (33, true));
+    assert_eq!(berlin.central_meridian(), 15.0);
+
+    let sydney = UtmProjection::for_point(Point::new(151.2, -33.9));
+    assert_eq!((sydney.zone, sydney.north), (56, false));
+
+    // The antimeridian belongs to the last zone
+    assert_eq!(UtmProjection::for_point(Point::new(180.0, 0.0)).zone, 60);
+}
+
+/// Points further than half a zone from the central meridian lie outside of the zone, also across the antimeridian
+#[test]
+fn test_max_meridian_offset() {
+    let track_at = |longitudes: &[f64]| {
+        track_from_segments(vec![TrackSegment {
+            points: longitudes
+                .iter()
+                .map(|&longitude| Waypoint::new(Point::new(longitude, 50.0)))
+                .collect(),
+        }])
+    };
+    let zone = UtmProjection::for_point(Point::new(13.4, 52.5));
+
+    assert_eq!(zone.max_meridian_offset(&track_at(&[])), 0.0);
+    let inside = zone.max_meridian_offset(&track_at(&[12.5, 15.0, 17.5]));
+    assert!(inside <= UTM_HALF_ZONE_WIDTH);
+    assert_distance("offset", inside, 2.5);
+    let outside = zone.max_meridian_offset(&track_at(&[13.0, 19.0]));
+    assert!(outside > UTM_HALF_ZONE_WIDTH);
+    assert_distance("offset", outside, 4.0);
+
+    let last_zone = UtmProjection::for_point(Point::new(179.0, 0.0));
+    assert_distance(
+        "offset",
+        last_zone.max_meridian_offset(&track_at(&[-178.0])),
+        5.0,
+    );
+}
+
+/// Points on the central meridian have the false easting, the equator has a northing of zero
+#[test]
+fn test_project_central_meridian() {
+    let zone = UtmProjection::for_point(Point::new(3.0, 0.0));
+    let projected = zone.project(Point::new(3.0, 0.0));
+    assert_distance("easting", projected.x(), 500_000.0);
+    assert_distance("northing", projected.y(), 0.0);
+
+    // One degree of latitude along the central meridian is about 110.6km, scaled by 0.9996
+    let projected = zone.project(Point::new(3.0, 1.0));
+    assert!((projected.y() - 110_574.4 * 0.9996).abs() < 1.0);
+}
+
+/// Projecting and unprojecting a point returns the original point, up to the precision of the series (about a millimeter)
+#[test]
+fn test_round_trip() {
+    for point in [
+        Point::new(13.4, 52.5),
+        Point::new(11.9, 48.1),
+        Point::new(-70.6, -33.4),
+    ] {
+        let zone = UtmProjection::for_point(point);
+        let round_trip = zone.unproject(zone.project(point));
+        assert!((round_trip.x() - point.x()).abs() < 1e-7);
+        assert!((round_trip.y() - point.y()).abs() < 1e-7);
+    }
+}
+
+/// Distances in the UTM projection differ from the flat projection only by the scale of the zone
+#[test]
+fn test_compare_with_utm_projection() {
+    let reference = fixture_track("golden_reference.gpx");
+    let track = fixture_track("golden_track.gpx");
+
+    let flat = compare_tracks(&reference, &track, &CompareOptions::default())
+        .expect("The reference path has points");
+    let utm = compare_tracks(
+        &reference,
+        &track,
+        &CompareOptions {
+            projection: Projection::Utm,
+            ..CompareOptions::default()
+        },
+    )
+    .expect("The reference path has points");
+    assert!((utm.average_distance_m / flat.average_distance_m - 1.0).abs() < 1e-3);
+    assert!((utm.current_track_length_m / flat.current_track_length_m - 1.0).abs() < 1e-3);
+
+    // The geodesic mode does not project, so the projection is ignored
+    let geodesic = CompareOptions {
+        distance_mode: DistanceMode::Geodesic,
+        ..CompareOptions::default()
+    };
+    assert_distance(
+        "average_distance_m",
+        compare_tracks(
+            &reference,
+            &track,
+            &CompareOptions {
+                projection: Projection::Utm,
+                ..geodesic.clone()
+            },
+        )
+        .expect("The reference path has points")
+        .average_distance_m,
+        compare_tracks(&reference, &track, &geodesic)
+            .expect("The reference path has points")
+            .average_distance_m,
+    );
+}